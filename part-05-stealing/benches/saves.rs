@@ -0,0 +1,36 @@
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use part_05_stealing::saveload_system::{benchmark, restore, snapshot, SaveFormatKind};
+
+/// Compares save and load time for each format on the same dungeon. Throughput is reported
+/// against the encoded size, so the formats' sizes can be compared too.
+fn save_formats(c: &mut Criterion) {
+    let mut ecs = benchmark::fixture();
+    for kind in [SaveFormatKind::Json, SaveFormatKind::Binary] {
+        let format = kind.format();
+        let document = snapshot(&mut ecs).expect("Unable to snapshot the fixture");
+        let data = format
+            .encode(&document)
+            .expect("Unable to encode the fixture");
+
+        let mut group = c.benchmark_group(format.name());
+        group.throughput(Throughput::Bytes(data.len() as u64));
+        group.bench_function("save", |b| {
+            b.iter(|| {
+                let document = snapshot(&mut ecs).expect("Unable to snapshot the fixture");
+                format
+                    .encode(&document)
+                    .expect("Unable to encode the fixture")
+            })
+        });
+        group.bench_function("load", |b| {
+            b.iter(|| {
+                let document = format.decode(&data).expect("Unable to decode the fixture");
+                restore(&mut ecs, document).expect("Unable to restore the fixture");
+            })
+        });
+        group.finish();
+    }
+}
+
+criterion_group!(benches, save_formats);
+criterion_main!(benches);
@@ -13,9 +13,11 @@ macro_rules! provide_all_components {
     (
         $callback:ident,
         $ecs:expr
+        $(, $extra:expr)*
     ) => {
         $callback!(
             $ecs,
+            $($extra,)*
             Position,
             Renderable,
             Player,
@@ -59,6 +61,7 @@ macro_rules! provide_all_components {
             Initiative,
             MyTurn,
             Faction,
+            CrimeRecord,
             WantsToApproach,
             WantsToFlee,
             MoveMode,
@@ -150,6 +153,8 @@ pub struct Initiative {
 #[derive(Component, Debug, Serialize, Deserialize, Clone)]
 pub struct Vendor {
     pub categories: Vec<String>,
+    /// The turn the vendor last topped up their stock, or `None` if they never have.
+    pub last_restock: Option<i32>,
 }
 
 #[derive(Component, Debug, Serialize, Deserialize, Clone)]
@@ -160,6 +165,21 @@ pub struct Faction {
     pub name: String,
 }
 
+/// Crimes witnesses have reported to the guards, by the depth of the town they happened in.
+#[derive(Component, Debug, Serialize, Deserialize, Clone, Default)]
+pub struct CrimeRecord {
+    pub towns: HashMap<i32, TownCrimes>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TownCrimes {
+    pub thefts: i32,
+    /// The gold it takes to clear the player's name here. Zero once paid.
+    pub bounty: f32,
+    /// The turn the latest crime was reported.
+    pub last_reported: i32,
+}
+
 #[derive(Component, Debug, Serialize, Deserialize, Clone)]
 pub struct ApplyMove {
     pub dest_idx: usize,
@@ -594,6 +614,6 @@ pub struct SerializationHelper {
 #[derive(Component, Serialize, Deserialize, Clone)]
 pub struct DMSerializationHelper {
     pub map: super::map::MasterDungeonMap,
-    pub log: Vec<Vec<crate::gamelog::LogFragment>>,
+    pub log: Vec<crate::gamelog::LogEntry>,
     pub events: HashMap<String, i32>,
 }
@@ -0,0 +1,363 @@
+use crate::gamelog;
+use crate::raws::{faction_reaction, Reaction, RAWS};
+use crate::rltk;
+use crate::{
+    Attributes, CrimeRecord, Faction, Item, Map, Name, Pools, Position, TownCrimes, Viewshed,
+};
+use specs::prelude::*;
+
+/// The faction that acts on reported crimes.
+const GUARD_FACTION: &str = "Town Guard";
+/// The faction older saves put caught thieves in, hostile to nearly everyone.
+const THIEF_FACTION: &str = "Thief";
+/// What clearing the old "Thief" faction costs, since those saves kept no bounty.
+const THIEF_FINE: f32 = 100.0;
+/// Who will take payment of a fine.
+const FINE_COLLECTORS: &[&str] = &["Town Guard", "Priest"];
+/// How long after the latest report guards keep after the player. The bounty stands until paid.
+const GUARD_PURSUIT_TURNS: i32 = 1000;
+
+impl CrimeRecord {
+    /// Records a theft of something worth `price` reported in the town on `depth` on `turn`.
+    /// The nth theft reported there adds n times its price to the bounty.
+    pub fn report_theft(&mut self, depth: i32, price: f32, turn: i32) {
+        let crimes = self.towns.entry(depth).or_insert(TownCrimes {
+            thefts: 0,
+            bounty: 0.0,
+            last_reported: turn,
+        });
+        crimes.thefts += 1;
+        crimes.bounty += price * crimes.thefts as f32;
+        crimes.last_reported = turn;
+    }
+
+    /// Whether the guards of the town on `depth` are still after the player on `turn`, counted
+    /// from the start of the run like `last_reported`.
+    pub fn is_wanted(&self, depth: i32, turn: i32) -> bool {
+        self.towns.get(&depth).is_some_and(|crimes| {
+            crimes.bounty > 0.0 && turn - crimes.last_reported < GUARD_PURSUIT_TURNS
+        })
+    }
+}
+
+/// How someone in `my_faction` reacts to `other`, who is in `other_faction`. Town guards attack
+/// anyone wanted in the town on `depth`, whatever their faction says.
+pub fn reaction(
+    my_faction: &str,
+    other: Entity,
+    other_faction: &str,
+    records: &ReadStorage<CrimeRecord>,
+    depth: i32,
+) -> Reaction {
+    if my_faction == GUARD_FACTION
+        && records
+            .get(other)
+            .is_some_and(|r| r.is_wanted(depth, gamelog::current_turn()))
+    {
+        return Reaction::Attack;
+    }
+    faction_reaction(my_faction, other_faction, &RAWS.lock().unwrap())
+}
+
+/// `reaction` towards the player, for code holding the whole world.
+pub fn reaction_to_player(ecs: &World, my_faction: &str) -> Reaction {
+    let player_entity = *ecs.fetch::<Entity>();
+    let player_faction = ecs
+        .read_storage::<Faction>()
+        .get(player_entity)
+        .map_or_else(|| "Player".to_string(), |faction| faction.name.clone());
+    reaction(
+        my_faction,
+        player_entity,
+        &player_faction,
+        &ecs.read_storage::<CrimeRecord>(),
+        ecs.fetch::<Map>().depth,
+    )
+}
+
+/// Gives everyone who can see `thief` a chance to spot them taking `item`; the higher
+/// `stealth`, the harder that is. The first to notice reports it to the guards of the town
+/// on this depth, adding the item's price to the bounty once for each theft reported there.
+/// Returns whether anyone did.
+pub fn witness_theft(ecs: &mut World, thief: Entity, item: Entity, stealth: i32) -> bool {
+    let witness = match spot_theft(ecs, thief, stealth) {
+        Some(witness) => witness,
+        None => return false,
+    };
+
+    let names = ecs.read_storage::<Name>();
+    let witness_name = names.get(witness).map_or("Someone", |n| n.name.as_str());
+    let item_name = names.get(item).map_or("something", |n| n.name.as_str());
+    gamelog::Logger::new()
+        .category(gamelog::LogCategory::Dialogue)
+        .npc_name(witness_name)
+        .append("saw you take the")
+        .item_name(item_name)
+        .append("and runs to tell the guards!")
+        .log();
+    std::mem::drop(names);
+
+    let price = ecs
+        .read_storage::<Item>()
+        .get(item)
+        .map_or(0.0, |item| item.base_value * 1.2);
+    let depth = ecs.fetch::<Map>().depth;
+    let mut records = ecs.write_storage::<CrimeRecord>();
+    if records.get(thief).is_none() {
+        records
+            .insert(thief, CrimeRecord::default())
+            .expect("Unable to insert");
+    }
+    records
+        .get_mut(thief)
+        .unwrap()
+        .report_theft(depth, price, gamelog::current_turn());
+    true
+}
+
+/// Whether `name` is someone who will take payment of a fine.
+pub fn collects_fines(name: &str) -> bool {
+    FINE_COLLECTORS.contains(&name)
+}
+
+/// What the player owes in the town on `depth`, or `None` if their name is clear there.
+pub fn fine_owed(
+    faction: Option<&Faction>,
+    record: Option<&CrimeRecord>,
+    depth: i32,
+) -> Option<f32> {
+    let bounty = record
+        .and_then(|record| record.towns.get(&depth))
+        .map_or(0.0, |crimes| crimes.bounty);
+    if bounty > 0.0 {
+        Some(bounty)
+    } else if faction.is_some_and(|faction| faction.name == THIEF_FACTION) {
+        Some(THIEF_FINE)
+    } else {
+        None
+    }
+}
+
+/// `fine_owed` by the player in the current town.
+pub fn player_fine(ecs: &World) -> Option<f32> {
+    let player_entity = *ecs.fetch::<Entity>();
+    fine_owed(
+        ecs.read_storage::<Faction>().get(player_entity),
+        ecs.read_storage::<CrimeRecord>().get(player_entity),
+        ecs.fetch::<Map>().depth,
+    )
+}
+
+/// Hands the player's fine to `collector` and clears their name in the current town, putting
+/// them back in the Player faction. Does nothing if they can't afford it.
+pub fn pay_fine(ecs: &mut World, collector: Entity) {
+    let fine = match player_fine(ecs) {
+        Some(fine) => fine,
+        None => return,
+    };
+    let player_entity = *ecs.fetch::<Entity>();
+    let mut pools = ecs.write_storage::<Pools>();
+    if pools
+        .get(player_entity)
+        .is_none_or(|pools| pools.gold < fine)
+    {
+        return;
+    }
+    pools.get_mut(player_entity).unwrap().gold -= fine;
+    if let Some(collector_pools) = pools.get_mut(collector) {
+        collector_pools.gold += fine;
+    }
+    std::mem::drop(pools);
+
+    let depth = ecs.fetch::<Map>().depth;
+    if let Some(crimes) = ecs
+        .write_storage::<CrimeRecord>()
+        .get_mut(player_entity)
+        .and_then(|record| record.towns.get_mut(&depth))
+    {
+        // The thefts stay on record, so reoffending costs more
+        crimes.bounty = 0.0;
+    }
+    ecs.write_storage::<Faction>()
+        .insert(
+            player_entity,
+            Faction {
+                name: "Player".to_string(),
+            },
+        )
+        .expect("Unable to insert");
+
+    gamelog::Logger::new()
+        .category(gamelog::LogCategory::Loot)
+        .append(format!("You pay a fine of {:.1} gold.", fine))
+        .append("Your name is clear in this town.")
+        .log();
+}
+
+/// The first onlooker to see through the theft. Everyone who would usually leave the thief be
+/// and has them in view gets a perception roll, with a penalty for distance.
+fn spot_theft(ecs: &World, thief: Entity, stealth: i32) -> Option<Entity> {
+    let entities = ecs.entities();
+    let viewsheds = ecs.read_storage::<Viewshed>();
+    let positions = ecs.read_storage::<Position>();
+    let factions = ecs.read_storage::<Faction>();
+    let attributes = ecs.read_storage::<Attributes>();
+    let thief_pos = positions.get(thief)?;
+    let thief_point = rltk::Point::new(thief_pos.x, thief_pos.y);
+    let thief_faction = factions
+        .get(thief)
+        .map_or("Player", |faction| &faction.name);
+
+    for (entity, viewshed, pos, faction) in (&entities, &viewsheds, &positions, &factions).join() {
+        if entity == thief
+            || !viewshed.visible_tiles.contains(&thief_point)
+            || faction_reaction(&faction.name, thief_faction, &RAWS.lock().unwrap())
+                == Reaction::Attack
+        {
+            continue;
+        }
+        let distance = rltk::DistanceAlg::Pythagoras
+            .distance2d(thief_point, rltk::Point::new(pos.x, pos.y)) as i32;
+        let perception = attributes
+            .get(entity)
+            .map_or(0, |attr| attr.intelligence.bonus);
+        if crate::rng::combat::roll_dice(1, 20) + perception - distance / 2 >= stealth {
+            return Some(entity);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Pool;
+
+    const TOWN: i32 = 1;
+
+    fn record_with_theft(turn: i32) -> CrimeRecord {
+        let mut record = CrimeRecord::default();
+        record.report_theft(TOWN, 10.0, turn);
+        record
+    }
+
+    #[test]
+    fn guards_give_up_the_chase_after_a_while() {
+        let record = record_with_theft(200);
+        assert!(record.is_wanted(TOWN, 200));
+        assert!(record.is_wanted(TOWN, 200 + GUARD_PURSUIT_TURNS - 1));
+        assert!(!record.is_wanted(TOWN, 200 + GUARD_PURSUIT_TURNS));
+        assert!(!record.is_wanted(TOWN + 1, 200));
+    }
+
+    #[test]
+    fn a_new_report_restarts_the_chase() {
+        let mut record = record_with_theft(0);
+        record.report_theft(TOWN, 10.0, GUARD_PURSUIT_TURNS);
+        assert!(record.is_wanted(TOWN, GUARD_PURSUIT_TURNS + 1));
+    }
+
+    #[test]
+    fn each_theft_costs_more_than_the_last() {
+        let mut record = CrimeRecord::default();
+        record.report_theft(TOWN, 10.0, 0);
+        record.report_theft(TOWN, 10.0, 1);
+        record.report_theft(TOWN, 20.0, 2);
+        let crimes = &record.towns[&TOWN];
+        assert_eq!(crimes.thefts, 3);
+        assert_eq!(crimes.bounty, 10.0 + 2.0 * 10.0 + 3.0 * 20.0);
+        assert_eq!(crimes.last_reported, 2);
+    }
+
+    #[test]
+    fn fines_are_owed_only_where_there_is_a_bounty() {
+        let record = record_with_theft(0);
+        assert_eq!(fine_owed(None, Some(&record), TOWN), Some(10.0));
+        assert_eq!(fine_owed(None, Some(&record), TOWN + 1), None);
+        assert_eq!(fine_owed(None, None, TOWN), None);
+
+        let mut paid = record;
+        paid.towns.get_mut(&TOWN).unwrap().bounty = 0.0;
+        assert_eq!(fine_owed(None, Some(&paid), TOWN), None);
+    }
+
+    #[test]
+    fn old_thieves_owe_a_flat_fine() {
+        let thief = Faction {
+            name: THIEF_FACTION.to_string(),
+        };
+        assert_eq!(fine_owed(Some(&thief), None, TOWN), Some(THIEF_FINE));
+    }
+
+    fn pools(gold: f32) -> Pools {
+        Pools {
+            hit_points: Pool {
+                max: 10,
+                current: 10,
+            },
+            mana: Pool { max: 0, current: 0 },
+            xp: 0,
+            level: 1,
+            total_weight: 0.0,
+            total_initiative_penalty: 0.0,
+            gold,
+            god_mode: false,
+        }
+    }
+
+    /// A town with a player who has `gold` and is in `faction` with a bounty of 10, and a guard.
+    fn town(gold: f32, faction: &str) -> (World, Entity, Entity) {
+        let mut ecs = World::new();
+        ecs.register::<Pools>();
+        ecs.register::<Faction>();
+        ecs.register::<CrimeRecord>();
+        ecs.insert(Map::new(TOWN, 10, 10, "Town"));
+        let player = ecs
+            .create_entity()
+            .with(pools(gold))
+            .with(Faction {
+                name: faction.to_string(),
+            })
+            .with(record_with_theft(0))
+            .build();
+        let guard = ecs.create_entity().with(pools(0.0)).build();
+        ecs.insert(player);
+        (ecs, player, guard)
+    }
+
+    #[test]
+    fn paying_a_fine_clears_the_players_name() {
+        let (mut ecs, player, guard) = town(25.0, THIEF_FACTION);
+        pay_fine(&mut ecs, guard);
+
+        let pools = ecs.read_storage::<Pools>();
+        assert_eq!(pools.get(player).unwrap().gold, 15.0);
+        assert_eq!(pools.get(guard).unwrap().gold, 10.0);
+        assert_eq!(
+            ecs.read_storage::<Faction>().get(player).unwrap().name,
+            "Player"
+        );
+        let records = ecs.read_storage::<CrimeRecord>();
+        let crimes = &records.get(player).unwrap().towns[&TOWN];
+        assert_eq!(crimes.bounty, 0.0);
+        assert_eq!(crimes.thefts, 1);
+        assert!(!records.get(player).unwrap().is_wanted(TOWN, 1));
+    }
+
+    #[test]
+    fn fines_cant_be_paid_on_credit() {
+        let (mut ecs, player, guard) = town(5.0, THIEF_FACTION);
+        pay_fine(&mut ecs, guard);
+
+        assert_eq!(ecs.read_storage::<Pools>().get(player).unwrap().gold, 5.0);
+        assert_eq!(
+            ecs.read_storage::<Faction>().get(player).unwrap().name,
+            THIEF_FACTION
+        );
+        assert!(ecs
+            .read_storage::<CrimeRecord>()
+            .get(player)
+            .unwrap()
+            .is_wanted(TOWN, 1));
+    }
+}
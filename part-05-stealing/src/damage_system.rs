@@ -1,4 +1,4 @@
-use super::{Equipped, InBackpack, LootTable, Name, Player, Pools, Position, RunState};
+use super::{Equipped, InBackpack, LootTable, Name, Player, Pools, Position, RunState, Vendor};
 use crate::rltk;
 use specs::prelude::*;
 
@@ -18,6 +18,7 @@ pub fn delete_the_dead(ecs: &mut World) {
                         let victim_name = names.get(entity);
                         if let Some(victim_name) = victim_name {
                             crate::gamelog::Logger::new()
+                                .category(crate::gamelog::LogCategory::Combat)
                                 .color(rltk::RED)
                                 .append(&victim_name.name)
                                 .append("is dead!")
@@ -36,6 +37,7 @@ pub fn delete_the_dead(ecs: &mut World) {
 
     // Drop everything held by dead people
     let mut to_spawn: Vec<(String, Position)> = Vec::new();
+    let mut to_destroy: Vec<Entity> = Vec::new();
     {
         // To avoid keeping hold of borrowed entries, use a scope
         let mut to_drop: Vec<(Entity, Position)> = Vec::new();
@@ -44,6 +46,7 @@ pub fn delete_the_dead(ecs: &mut World) {
         let mut carried = ecs.write_storage::<InBackpack>();
         let mut positions = ecs.write_storage::<Position>();
         let loot_tables = ecs.read_storage::<LootTable>();
+        let vendors = ecs.read_storage::<Vendor>();
         for victim in dead.iter() {
             let pos = positions.get(*victim);
             for (entity, equipped) in (&entities, &equipped).join() {
@@ -56,8 +59,11 @@ pub fn delete_the_dead(ecs: &mut World) {
             }
             for (entity, backpack) in (&entities, &carried).join() {
                 if backpack.owner == *victim {
-                    // Drop their stuff
-                    if let Some(pos) = pos {
+                    // A vendor's backpack is their shop's stock, which they don't carry about
+                    if vendors.get(*victim).is_some() {
+                        to_destroy.push(entity);
+                    } else if let Some(pos) = pos {
+                        // Drop their stuff
                         to_drop.push((entity, pos.clone()));
                     }
                 }
@@ -105,7 +111,7 @@ pub fn delete_the_dead(ecs: &mut World) {
         let death_effects = ecs.read_storage::<OnDeath>();
         if let Some(death_effect) = death_effects.get(*victim) {
             for effect in death_effect.abilities.iter() {
-                if crate::rng::roll_dice(1, 100) <= (effect.chance * 100.0) as i32 {
+                if crate::rng::combat::roll_dice(1, 100) <= (effect.chance * 100.0) as i32 {
                     let map = ecs.fetch::<Map>();
                     if let Some(pos) = ecs.read_storage::<Position>().get(*victim) {
                         let spell_entity =
@@ -138,4 +144,68 @@ pub fn delete_the_dead(ecs: &mut World) {
     for victim in dead {
         ecs.delete_entity(victim).expect("Unable to delete");
     }
+    for item in to_destroy {
+        ecs.delete_entity(item).expect("Unable to delete");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Item, Vendor};
+
+    fn carried_item(ecs: &mut World, owner: Entity) -> Entity {
+        ecs.create_entity()
+            .with(Item {
+                initiative_penalty: 0.0,
+                weight_lbs: 1.0,
+                base_value: 1.0,
+            })
+            .with(InBackpack { owner })
+            .build()
+    }
+
+    fn dead_body(ecs: &mut World) -> EntityBuilder<'_> {
+        ecs.create_entity()
+            .with(Position { x: 1, y: 1 })
+            .with(Pools {
+                hit_points: crate::Pool {
+                    max: 10,
+                    current: 0,
+                },
+                mana: crate::Pool { max: 0, current: 0 },
+                xp: 0,
+                level: 1,
+                total_weight: 0.0,
+                total_initiative_penalty: 0.0,
+                gold: 0.0,
+                god_mode: false,
+            })
+    }
+
+    #[test]
+    fn dead_vendors_take_their_stock_with_them() {
+        let mut ecs = World::new();
+        crate::init_world(&mut ecs, 1);
+        let vendor = dead_body(&mut ecs)
+            .with(Vendor {
+                categories: Vec::new(),
+                last_restock: None,
+            })
+            .build();
+        let stock = carried_item(&mut ecs, vendor);
+        let traveller = dead_body(&mut ecs).build();
+        let belongings = carried_item(&mut ecs, traveller);
+
+        delete_the_dead(&mut ecs);
+
+        assert!(!ecs.is_alive(vendor));
+        assert!(!ecs.is_alive(stock));
+        assert!(!ecs.is_alive(traveller));
+        // Anyone else's things are left where they fell
+        assert!(ecs.read_storage::<InBackpack>().get(belongings).is_none());
+        let positions = ecs.read_storage::<Position>();
+        let dropped = positions.get(belongings).unwrap();
+        assert_eq!((dropped.x, dropped.y), (1, 1));
+    }
 }
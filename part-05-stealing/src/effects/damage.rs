@@ -6,7 +6,7 @@ use crate::components::{
 use crate::gamesystem::{mana_at_level, player_hp_at_level};
 use crate::map::Map;
 use crate::rltk;
-use crate::systems::sound_system::SoundSystem;
+use crate::systems::sound_system::{play_event_from, play_stinger};
 use specs::saveload::{MarkedBuilder, SimpleMarker};
 
 pub fn inflict_damage(ecs: &mut World, damage: &EffectSpawner, target: Entity) {
@@ -19,11 +19,17 @@ pub fn inflict_damage(ecs: &mut World, damage: &EffectSpawner, target: Entity) {
                     return;
                 }
             }
-            if let EffectType::Damage { amount } = damage.effect_type {
-                ecs.read_resource::<SoundSystem>()
-                    .play_sound_effects(vec![String::from("hit.wav")]);
+            if let EffectType::Damage { amount, sound } = &damage.effect_type {
+                let amount = *amount;
                 pool.hit_points.current -= amount;
                 add_effect(None, EffectType::Bloodstain, Targets::Single { target });
+                add_effect(
+                    damage.creator,
+                    EffectType::Sound {
+                        event: sound.clone(),
+                    },
+                    Targets::Single { target },
+                );
                 add_effect(
                     None,
                     EffectType::Particle {
@@ -62,6 +68,8 @@ pub fn bloodstain(ecs: &mut World, tile_idx: i32) {
 }
 
 pub fn death(ecs: &mut World, effect: &EffectSpawner, target: Entity) {
+    play_event_from(ecs, "death", target);
+
     let mut xp_gain = 0;
     let mut gold_gain = 0.0f32;
 
@@ -86,21 +94,22 @@ pub fn death(ecs: &mut World, effect: &EffectSpawner, target: Entity) {
                 player_stats.gold += gold_gain;
                 if player_stats.xp >= player_stats.level * 1000 {
                     // We've gone up a level!
-                    ecs.read_resource::<SoundSystem>()
-                        .play_sound_effects(vec![String::from("level_up.wav")]);
+                    play_stinger(ecs, "level_up");
                     player_stats.level += 1;
                     crate::gamelog::Logger::new()
+                        .category(crate::gamelog::LogCategory::Combat)
                         .color(rltk::MAGENTA)
                         .append("Congratulations, you are now level")
                         .append(format!("{}", player_stats.level))
                         .log();
 
                     // Improve a random attribute
-                    let attr_to_boost = crate::rng::roll_dice(1, 4);
+                    let attr_to_boost = crate::rng::combat::roll_dice(1, 4);
                     match attr_to_boost {
                         1 => {
                             player_attributes.might.base += 1;
                             crate::gamelog::Logger::new()
+                                .category(crate::gamelog::LogCategory::Combat)
                                 .color(rltk::GREEN)
                                 .append("You feel stronger!")
                                 .log();
@@ -108,6 +117,7 @@ pub fn death(ecs: &mut World, effect: &EffectSpawner, target: Entity) {
                         2 => {
                             player_attributes.fitness.base += 1;
                             crate::gamelog::Logger::new()
+                                .category(crate::gamelog::LogCategory::Combat)
                                 .color(rltk::GREEN)
                                 .append("You feel healthier!")
                                 .log();
@@ -115,6 +125,7 @@ pub fn death(ecs: &mut World, effect: &EffectSpawner, target: Entity) {
                         3 => {
                             player_attributes.quickness.base += 1;
                             crate::gamelog::Logger::new()
+                                .category(crate::gamelog::LogCategory::Combat)
                                 .color(rltk::GREEN)
                                 .append("You feel quicker!")
                                 .log();
@@ -122,6 +133,7 @@ pub fn death(ecs: &mut World, effect: &EffectSpawner, target: Entity) {
                         _ => {
                             player_attributes.intelligence.base += 1;
                             crate::gamelog::Logger::new()
+                                .category(crate::gamelog::LogCategory::Combat)
                                 .color(rltk::GREEN)
                                 .append("You feel quicker!")
                                 .log();
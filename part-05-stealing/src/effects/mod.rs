@@ -19,6 +19,8 @@ lazy_static! {
 pub enum EffectType {
     Damage {
         amount: i32,
+        /// The sound event heard if the damage lands.
+        sound: String,
     },
     Bloodstain,
     Particle {
@@ -71,6 +73,9 @@ pub enum EffectType {
     DamageOverTime {
         damage: i32,
     },
+    Sound {
+        event: String,
+    },
 }
 
 #[derive(Clone, Debug)]
@@ -140,6 +145,7 @@ fn tile_effect_hits_entities(effect: &EffectType) -> bool {
         EffectType::AttributeEffect { .. } => true,
         EffectType::Slow { .. } => true,
         EffectType::DamageOverTime { .. } => true,
+        EffectType::Sound { .. } => true,
         _ => false,
     }
 }
@@ -185,6 +191,12 @@ fn affect_entity(ecs: &mut World, effect: &mut EffectSpawner, target: Entity) {
         EffectType::AttributeEffect { .. } => damage::attribute_effect(ecs, effect, target),
         EffectType::Slow { .. } => damage::slow(ecs, effect, target),
         EffectType::DamageOverTime { .. } => damage::damage_over_time(ecs, effect, target),
+        EffectType::Sound { event } => crate::systems::sound_system::play_event(
+            ecs,
+            event,
+            effect.creator.unwrap_or(target),
+            target,
+        ),
         _ => {}
     }
 }
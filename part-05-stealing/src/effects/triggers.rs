@@ -1,7 +1,7 @@
 use super::*;
 use crate::components::*;
 use crate::rltk;
-use crate::systems::sound_system::SoundSystem;
+use crate::systems::sound_system::play_sound_effects_from;
 use crate::RunState;
 
 pub fn item_trigger(creator: Option<Entity>, item: Entity, targets: &Targets, ecs: &mut World) {
@@ -10,6 +10,7 @@ pub fn item_trigger(creator: Option<Entity>, item: Entity, targets: &Targets, ec
         if c.charges < 1 {
             // Cancel
             crate::gamelog::Logger::new()
+                .category(crate::gamelog::LogCategory::Loot)
                 .color(rltk::CYAN)
                 .append(&ecs.read_storage::<Name>().get(item).unwrap().name)
                 .color(rltk::WHITE)
@@ -144,6 +145,7 @@ fn event_trigger(
     if ecs.read_storage::<MagicMapper>().get(entity).is_some() {
         let mut runstate = ecs.fetch_mut::<RunState>();
         crate::gamelog::Logger::new()
+            .category(crate::gamelog::LogCategory::Loot)
             .append("The map is revealed to you!")
             .log();
         *runstate = RunState::MagicMapReveal { row: 0 };
@@ -177,10 +179,12 @@ fn event_trigger(
         let map = ecs.fetch::<Map>();
         if map.depth == 1 {
             crate::gamelog::Logger::new()
+                .category(crate::gamelog::LogCategory::Loot)
                 .append("You are already in town, so the scroll does nothing.")
                 .log();
         } else {
             crate::gamelog::Logger::new()
+                .category(crate::gamelog::LogCategory::Loot)
                 .append("You are telported back to town!")
                 .log();
             let mut runstate = ecs.fetch_mut::<RunState>();
@@ -219,6 +223,7 @@ fn event_trigger(
             creator,
             EffectType::Damage {
                 amount: damage.damage,
+                sound: "spell_hit".to_string(),
             },
             targets.clone(),
         );
@@ -318,8 +323,11 @@ fn event_trigger(
     // Play sounds if available
     if did_something {
         if let Some(sounds) = ecs.read_storage::<Sounds>().get(entity) {
-            ecs.read_resource::<SoundSystem>()
-                .play_sound_effects(sounds.file_names.clone());
+            // A spell is heard from its caster, anything else from where it lies
+            let source = creator
+                .filter(|creator| ecs.read_storage::<Position>().get(*creator).is_some())
+                .unwrap_or(entity);
+            play_sound_effects_from(ecs, sounds.file_names.clone(), source);
         }
     }
 
@@ -1,8 +1,9 @@
-use super::{append_entry, LogFragment};
+use super::{append_entry, LogCategory, LogFragment};
 use crate::rltk;
 
 pub struct Logger {
     current_color: rltk::RGB,
+    category: LogCategory,
     fragments: Vec<LogFragment>,
 }
 
@@ -10,10 +11,17 @@ impl Logger {
     pub fn new() -> Self {
         Logger {
             current_color: rltk::RGB::named(rltk::WHITE),
+            category: LogCategory::System,
             fragments: Vec::new(),
         }
     }
 
+    /// Files the entry under `category`. Entries are `System` messages unless told otherwise.
+    pub fn category(mut self, category: LogCategory) -> Self {
+        self.category = category;
+        self
+    }
+
     pub fn color(mut self, color: (u8, u8, u8)) -> Self {
         self.current_color = rltk::RGB::named(color);
         self
@@ -52,6 +60,6 @@ impl Logger {
     }
 
     pub fn log(self) {
-        append_entry(self.fragments)
+        append_entry(self.category, self.fragments)
     }
 }
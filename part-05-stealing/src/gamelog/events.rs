@@ -31,6 +31,12 @@ pub fn get_event_count<T: ToString>(event: T) -> i32 {
     }
 }
 
+/// How many turns the run has lasted. Events are counted from the start of the run, across
+/// levels, and saved with it.
+pub fn current_turn() -> i32 {
+    get_event_count("Turn")
+}
+
 pub fn clone_events() -> HashMap<String, i32> {
     EVENTS.lock().unwrap().clone()
 }
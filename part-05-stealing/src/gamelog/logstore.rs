@@ -1,13 +1,24 @@
-use super::LogFragment;
+use super::{LogCategory, LogEntry, LogFragment};
 use crate::rltk;
 use std::sync::Mutex;
 
+/// The log keeps a whole run, up to this many entries; past that the oldest are dropped.
+const MAX_LOG_ENTRIES: usize = 10_000;
+
 lazy_static! {
-    static ref LOG: Mutex<Vec<Vec<LogFragment>>> = Mutex::new(Vec::new());
+    static ref LOG: Mutex<Vec<LogEntry>> = Mutex::new(Vec::new());
 }
 
-pub fn append_entry(fragments: Vec<LogFragment>) {
-    LOG.lock().unwrap().push(fragments);
+pub fn append_entry(category: LogCategory, fragments: Vec<LogFragment>) {
+    let mut log = LOG.lock().unwrap();
+    log.push(LogEntry {
+        category,
+        fragments,
+    });
+    if log.len() > MAX_LOG_ENTRIES {
+        let excess = log.len() - MAX_LOG_ENTRIES;
+        log.drain(..excess);
+    }
 }
 
 pub fn clear_log() {
@@ -18,7 +29,7 @@ pub fn print_log(console: &mut Box<dyn rltk::Console>, pos: rltk::Point) {
     let mut y = pos.y;
     let mut x = pos.x;
     LOG.lock().unwrap().iter().rev().take(6).for_each(|log| {
-        log.iter().for_each(|frag| {
+        log.fragments.iter().for_each(|frag| {
             console.print_color(
                 x,
                 y,
@@ -26,7 +37,7 @@ pub fn print_log(console: &mut Box<dyn rltk::Console>, pos: rltk::Point) {
                 rltk::RGBA::named(rltk::BLACK),
                 &frag.text,
             );
-            x += frag.text.len() as i32;
+            x += frag.text.chars().count() as i32;
             x += 1;
         });
         y += 1;
@@ -34,11 +45,24 @@ pub fn print_log(console: &mut Box<dyn rltk::Console>, pos: rltk::Point) {
     });
 }
 
-pub fn clone_log() -> Vec<Vec<crate::gamelog::LogFragment>> {
+pub fn clone_log() -> Vec<LogEntry> {
     LOG.lock().unwrap().clone()
 }
 
-pub fn restore_log(log: &mut Vec<Vec<crate::gamelog::LogFragment>>) {
+/// The entries in `category` (or every category, for `None`) whose text contains `search`,
+/// ignoring case. Oldest first, like the log itself.
+pub fn find_entries(category: Option<LogCategory>, search: &str) -> Vec<LogEntry> {
+    let search = search.to_lowercase();
+    LOG.lock()
+        .unwrap()
+        .iter()
+        .filter(|entry| category.is_none_or(|category| entry.category == category))
+        .filter(|entry| search.is_empty() || entry.text().to_lowercase().contains(&search))
+        .cloned()
+        .collect()
+}
+
+pub fn restore_log(log: &mut Vec<LogEntry>) {
     LOG.lock().unwrap().clear();
     LOG.lock().unwrap().append(log);
 }
@@ -2,15 +2,62 @@ use crate::rltk::RGB;
 
 mod logstore;
 use logstore::*;
-pub use logstore::{clear_log, clone_log, print_log, restore_log};
+pub use logstore::{clear_log, clone_log, find_entries, print_log, restore_log};
 use serde::{Deserialize, Serialize};
 mod builder;
 pub use builder::*;
 mod events;
-pub use events::{clear_events, clone_events, get_event_count, load_events, record_event};
+pub use events::{
+    clear_events, clone_events, current_turn, get_event_count, load_events, record_event,
+};
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct LogFragment {
     pub color: RGB,
     pub text: String,
 }
+
+/// What a log entry is about, so the message history can be filtered.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LogCategory {
+    Combat,
+    Loot,
+    Dialogue,
+    System,
+}
+
+impl LogCategory {
+    pub const ALL: [LogCategory; 4] = [
+        LogCategory::Combat,
+        LogCategory::Loot,
+        LogCategory::Dialogue,
+        LogCategory::System,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            LogCategory::Combat => "Combat",
+            LogCategory::Loot => "Loot",
+            LogCategory::Dialogue => "Dialogue",
+            LogCategory::System => "System",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct LogEntry {
+    pub category: LogCategory,
+    pub fragments: Vec<LogFragment>,
+}
+
+impl LogEntry {
+    /// The entry as plain text, the way it reads on screen.
+    pub fn text(&self) -> String {
+        let text: Vec<&str> = self
+            .fragments
+            .iter()
+            .map(|frag| frag.text.as_str())
+            .collect();
+        text.join(" ")
+    }
+}
@@ -1,3 +1,5 @@
+use crate::keymap::{Action, Keymap};
+use crate::player::KeyPress;
 use crate::rltk;
 use crate::State;
 
@@ -17,10 +19,12 @@ pub enum CheatMenuResult {
 }
 
 pub fn show_cheat_mode(gs: &mut State, ctx: &mut rltk::BTerm) -> CheatMenuResult {
-    if !gs.game_options.show_cheat_menu {
+    // Ironman runs can't be cheated, whatever the options say
+    if !gs.game_options.show_cheat_menu || gs.is_ironman() {
         return CheatMenuResult::Cancel;
     }
     let mut draw_batch = rltk::DrawBatch::new();
+    let keymap = gs.ecs.fetch::<Keymap>();
     let count = 6;
     let mut y = (25 - (count / 2)) as i32;
     menu_box(
@@ -28,6 +32,7 @@ pub fn show_cheat_mode(gs: &mut State, ctx: &mut rltk::BTerm) -> CheatMenuResult
         y,
         (count + 3) as i32,
         "Cheating!",
+        &keymap,
         Vec::new(),
     );
 
@@ -88,6 +93,9 @@ pub fn show_cheat_mode(gs: &mut State, ctx: &mut rltk::BTerm) -> CheatMenuResult
         .submit(6000)
         .expect("Failed to submit cheat menu draw batch");
 
+    if keymap.is(Action::CloseMenu, KeyPress::from_ctx(ctx)) {
+        return CheatMenuResult::Cancel;
+    }
     match ctx.key {
         None => CheatMenuResult::NoResponse,
         Some(key) => match key {
@@ -97,7 +105,6 @@ pub fn show_cheat_mode(gs: &mut State, ctx: &mut rltk::BTerm) -> CheatMenuResult
             rltk::VirtualKeyCode::G => CheatMenuResult::GodMode,
             rltk::VirtualKeyCode::S => CheatMenuResult::LearnSpells,
             rltk::VirtualKeyCode::I => CheatMenuResult::AllItems,
-            rltk::VirtualKeyCode::Escape => CheatMenuResult::Cancel,
             _ => CheatMenuResult::NoResponse,
         },
     }
@@ -0,0 +1,78 @@
+use super::{menu_box, menu_option};
+use crate::keymap::{Action, Keymap};
+use crate::player::KeyPress;
+use crate::rltk;
+use crate::{Name, Pools, State};
+use specs::prelude::*;
+
+const PRICE_X: i32 = 34;
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum FineResult {
+    NoResponse,
+    Cancel,
+    Pay,
+}
+
+/// Offers to pay the player's fine in this town to `collector`.
+pub fn fine_menu(gs: &mut State, ctx: &mut rltk::BTerm, collector: Entity) -> FineResult {
+    let fine = match crate::crime::player_fine(&gs.ecs) {
+        Some(fine) => fine,
+        None => return FineResult::Cancel,
+    };
+    let mut draw_batch = rltk::DrawBatch::new();
+    let player_entity = gs.ecs.fetch::<Entity>();
+    let gold = gs
+        .ecs
+        .read_storage::<Pools>()
+        .get(*player_entity)
+        .map_or(0.0, |pools| pools.gold);
+    let affordable = gold >= fine;
+    let names = gs.ecs.read_storage::<Name>();
+    let collector_name = names
+        .get(collector)
+        .map_or("Collector", |n| n.name.as_str());
+    let keymap = gs.ecs.fetch::<Keymap>();
+
+    let y = 24;
+    menu_box(
+        &mut draw_batch,
+        y,
+        4,
+        format!("{}: Pay Your Fine?", collector_name),
+        &keymap,
+        Vec::new(),
+    );
+    let color = if affordable {
+        rltk::RGB::named(rltk::WHITE)
+    } else {
+        rltk::RGB::named(rltk::GRAY)
+    };
+    menu_option(
+        &mut draw_batch,
+        y,
+        rltk::to_cp437('a'),
+        "Pay the bounty",
+        color,
+    );
+    draw_batch.print_color(
+        rltk::Point::new(PRICE_X, y),
+        format!("{:.1} gp", fine),
+        rltk::ColorPair::new(color, rltk::RGB::named(rltk::BLACK)),
+    );
+
+    draw_batch.submit(6000).expect("Failed to submit");
+
+    let input = KeyPress::from_ctx(ctx);
+    let key = match input.key {
+        None => return FineResult::NoResponse,
+        Some(key) => key,
+    };
+    if keymap.is(Action::CloseMenu, input) {
+        FineResult::Cancel
+    } else if rltk::letter_to_option(key) == 0 && affordable {
+        FineResult::Pay
+    } else {
+        FineResult::NoResponse
+    }
+}
@@ -2,7 +2,7 @@ use super::{draw_tooltips, get_item_color, get_item_display_name};
 use crate::rltk;
 use crate::{
     gamelog, Attribute, Attributes, Consumable, Duration, Equipped, InBackpack, KnownSpells, Map,
-    Name, Pools, StatusEffect, Weapon,
+    MasterDungeonMap, Name, Pools, StatusEffect, Weapon,
 };
 use specs::prelude::*;
 
@@ -119,6 +119,29 @@ pub fn map_label(ecs: &World, draw_batch: &mut rltk::DrawBatch) {
     );
 }
 
+fn seed_label(ecs: &World, draw_batch: &mut rltk::DrawBatch) {
+    let box_gray: rltk::RGB = rltk::RGB::from_hex("#999999").expect("Oops");
+    let black = rltk::RGB::named(rltk::BLACK);
+
+    let label = format!("Seed: {}", ecs.fetch::<MasterDungeonMap>().seed);
+    let x_pos = 78 - label.len() as i32 - 1;
+    draw_batch.set(
+        rltk::Point::new(x_pos - 1, 59),
+        rltk::ColorPair::new(box_gray, black),
+        rltk::to_cp437('┤'),
+    );
+    draw_batch.set(
+        rltk::Point::new(x_pos + label.len() as i32, 59),
+        rltk::ColorPair::new(box_gray, black),
+        rltk::to_cp437('├'),
+    );
+    draw_batch.print_color(
+        rltk::Point::new(x_pos, 59),
+        &label,
+        rltk::ColorPair::new(box_gray, black),
+    );
+}
+
 fn draw_stats(ecs: &World, draw_batch: &mut rltk::DrawBatch, player_entity: &Entity) {
     let black = rltk::RGB::named(rltk::BLACK);
     let white = rltk::RGB::named(rltk::WHITE);
@@ -346,6 +369,7 @@ pub fn draw_ui(ecs: &World, ctx: &mut rltk::BTerm) {
 
     box_framework(&mut draw_batch);
     map_label(ecs, &mut draw_batch);
+    seed_label(ecs, &mut draw_batch);
     draw_stats(ecs, &mut draw_batch, &player_entity);
     draw_attributes(ecs, &mut draw_batch, &player_entity);
     initiative_weight(ecs, &mut draw_batch, &player_entity);
@@ -1,4 +1,6 @@
 use super::{get_item_color, get_item_display_name, item_result_menu, ItemMenuResult};
+use crate::keymap::Keymap;
+use crate::player::KeyPress;
 use crate::rltk;
 use crate::{Equipped, InBackpack, Item, MasterDungeonMap, Name, ObfuscatedName, State};
 use specs::prelude::*;
@@ -57,7 +59,14 @@ pub fn identify_menu(
             ))
         });
 
-    let result = item_result_menu(&mut draw_batch, "Inventory", &items, ctx.key, page);
+    let result = item_result_menu(
+        &mut draw_batch,
+        "Inventory",
+        &items,
+        KeyPress::from_ctx(ctx),
+        &gs.ecs.fetch::<Keymap>(),
+        page,
+    );
     draw_batch.submit(6000).expect("Failed to submit");
     result
 }
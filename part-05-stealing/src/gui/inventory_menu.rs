@@ -1,4 +1,6 @@
 use super::{get_item_color, get_item_display_name, item_result_menu};
+use crate::keymap::Keymap;
+use crate::player::KeyPress;
 use crate::rltk;
 use crate::{InBackpack, State};
 use specs::prelude::*;
@@ -35,7 +37,14 @@ pub fn show_inventory(
             ))
         });
 
-    let result = item_result_menu(&mut draw_batch, "Inventory", &items, ctx.key, page);
+    let result = item_result_menu(
+        &mut draw_batch,
+        "Inventory",
+        &items,
+        KeyPress::from_ctx(ctx),
+        &gs.ecs.fetch::<Keymap>(),
+        page,
+    );
     draw_batch.submit(6000).expect("Failed to submit");
     result
 }
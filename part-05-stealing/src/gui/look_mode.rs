@@ -0,0 +1,65 @@
+use super::draw_look_tooltips;
+use crate::keymap::{Action, KeyContext, Keymap};
+use crate::player::KeyPress;
+use crate::rltk;
+use crate::{camera, Map, State};
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum LookResult {
+    NoResponse { x: i32, y: i32, index: usize },
+    Cancel,
+}
+
+/// Moves a cursor over the map with the movement keys, describing whatever it is on. `index`
+/// picks which of the things stacked on the tile is described.
+pub fn look_mode(
+    gs: &mut State,
+    ctx: &mut rltk::BTerm,
+    x: i32,
+    y: i32,
+    index: usize,
+) -> LookResult {
+    let mut draw_batch = rltk::DrawBatch::new();
+    let keymap = gs.ecs.fetch::<Keymap>();
+    let cursor = rltk::Point::new(x, y);
+
+    draw_batch.print_color(
+        rltk::Point::new(5, 0),
+        format!(
+            "Look: ({}) next thing here, ({}) done",
+            keymap.label(Action::LookCycle),
+            keymap.label(Action::CloseMenu)
+        ),
+        rltk::ColorPair::new(
+            rltk::RGB::named(rltk::YELLOW),
+            rltk::RGB::named(rltk::BLACK),
+        ),
+    );
+    draw_batch.set_bg(
+        camera::map_to_screen(&gs.ecs, ctx, cursor),
+        rltk::RGB::named(rltk::CYAN),
+    );
+    draw_batch.submit(5000).expect("Failed to submit");
+    draw_look_tooltips(&gs.ecs, ctx, cursor, index);
+
+    let input = KeyPress::from_ctx(ctx);
+    if keymap.is(Action::CloseMenu, input) || keymap.is(Action::Look, input) {
+        return LookResult::Cancel;
+    }
+    let (x, y, index) = match keymap.action(KeyContext::Game, input) {
+        Some(Action::LookCycle) => (x, y, index + 1),
+        Some(action) => match action.direction() {
+            Some((dx, dy)) => {
+                // The cursor stays on the part of the map that is on screen
+                let (min_x, max_x, min_y, max_y) = camera::get_screen_bounds(&gs.ecs, ctx);
+                let map = gs.ecs.fetch::<Map>();
+                let x = (x + dx).clamp(i32::max(min_x, 0), i32::min(max_x, map.width) - 1);
+                let y = (y + dy).clamp(i32::max(min_y, 0), i32::min(max_y, map.height) - 1);
+                (x, y, 0)
+            }
+            None => (x, y, index),
+        },
+        None => (x, y, index),
+    };
+    LookResult::NoResponse { x, y, index }
+}
@@ -1,9 +1,17 @@
+use crate::keymap::{Action, KeyContext, Keymap};
+use crate::player::KeyPress;
 use crate::rltk;
+use crate::saveload_system::SaveListing;
 use crate::{rex_assets::RexAssets, RunState, State};
+use serde::{Deserialize, Serialize};
 
-#[derive(PartialEq, Copy, Clone)]
+#[derive(PartialEq, Copy, Clone, Serialize, Deserialize)]
 pub enum MainMenuSelection {
     NewGame,
+    NewSeededGame,
+    NewIronmanGame,
+    ResumeIronman,
+    Continue,
     LoadGame,
     SaveGame,
     Options,
@@ -16,16 +24,52 @@ pub enum MainMenuResult {
     Selected { selected: MainMenuSelection },
 }
 
+fn menu_title(
+    selection: MainMenuSelection,
+    in_progress_game: bool,
+    ironman: bool,
+    listing: &SaveListing,
+) -> String {
+    match selection {
+        MainMenuSelection::NewGame if in_progress_game => "Resume".to_string(),
+        MainMenuSelection::NewGame => "Begin New Game".to_string(),
+        MainMenuSelection::NewSeededGame => "Begin Seeded Game".to_string(),
+        MainMenuSelection::NewIronmanGame => "Begin Ironman Game".to_string(),
+        MainMenuSelection::ResumeIronman => "Resume Ironman Game".to_string(),
+        MainMenuSelection::Continue => match &listing.autosave {
+            Some(metadata) => format!(
+                "Continue (Level {}, {})",
+                metadata.character_level, metadata.map_name
+            ),
+            None => "Continue".to_string(),
+        },
+        MainMenuSelection::LoadGame => "Load Game".to_string(),
+        MainMenuSelection::SaveGame if ironman => "Save and Quit".to_string(),
+        MainMenuSelection::SaveGame => "Save Game".to_string(),
+        MainMenuSelection::Options => "Options".to_string(),
+        MainMenuSelection::Quit => "Quit".to_string(),
+    }
+}
+
 pub fn main_menu(gs: &mut State, ctx: &mut rltk::BTerm) -> MainMenuResult {
     let mut draw_batch = rltk::DrawBatch::new();
-    let save_exists = crate::saveload_system::does_save_exist();
-    let in_progress_game = crate::gamelog::get_event_count("Turn") > 0;
+    let listing = &gs.save_listing;
+    let save_exists = listing.any_slot_saved();
+    let in_progress_game = gs.run_in_progress;
+    let autosave_exists = !in_progress_game && listing.autosave_exists;
+    let ironman_save_exists = !in_progress_game && listing.ironman_save_exists;
+    let ironman = in_progress_game
+        && gs
+            .ecs
+            .try_fetch::<crate::saveload_system::RunInfo>()
+            .is_some_and(|run| run.ironman);
     let runstate = gs.ecs.fetch::<RunState>();
     let assets = gs.ecs.fetch::<RexAssets>();
+    let keymap = gs.ecs.fetch::<Keymap>();
     ctx.render_xp_sprite(&assets.menu, 0, 5);
 
     draw_batch.draw_double_box(
-        rltk::Rect::with_size(24, 18, 31, 11),
+        rltk::Rect::with_size(24, 18, 31, 15),
         rltk::ColorPair::new(rltk::RGB::named(rltk::WHEAT), rltk::RGB::named(rltk::BLACK)),
     );
 
@@ -44,127 +88,89 @@ pub fn main_menu(gs: &mut State, ctx: &mut rltk::BTerm) -> MainMenuResult {
     );
     draw_batch.print_color_centered(
         22,
-        "Use Up/Down Arrows and Enter",
+        format!(
+            "Use {}/{} and {}",
+            keymap.label(Action::ScrollUp),
+            keymap.label(Action::ScrollDown),
+            keymap.label(Action::Select)
+        ),
         rltk::ColorPair::new(rltk::RGB::named(rltk::GRAY), rltk::RGB::named(rltk::BLACK)),
     );
 
+    // Only the entries that make sense right now are shown, and navigation skips the rest
+    let mut available = vec![MainMenuSelection::NewGame];
+    if !in_progress_game {
+        available.push(MainMenuSelection::NewSeededGame);
+        available.push(MainMenuSelection::NewIronmanGame);
+    }
+    if ironman_save_exists {
+        available.push(MainMenuSelection::ResumeIronman);
+    }
+    if autosave_exists {
+        available.push(MainMenuSelection::Continue);
+    }
+    // Loading over an ironman run would let the player take back their mistakes
+    if save_exists && !ironman {
+        available.push(MainMenuSelection::LoadGame);
+    }
+    if in_progress_game {
+        available.push(MainMenuSelection::SaveGame);
+    }
+    available.push(MainMenuSelection::Options);
+    available.push(MainMenuSelection::Quit);
+
     let mut y = 24;
     if let RunState::MainMenu {
         menu_selection: selection,
     } = *runstate
     {
-        let new_game_text;
-        if in_progress_game {
-            new_game_text = "Resume";
-        } else {
-            new_game_text = "Begin New Game";
-        }
-        print_menu_option(
-            &mut draw_batch,
-            y,
-            selection == MainMenuSelection::NewGame,
-            new_game_text,
-        );
-        y += 1;
-
-        if save_exists {
+        for option in available.iter() {
             print_menu_option(
                 &mut draw_batch,
                 y,
-                selection == MainMenuSelection::LoadGame,
-                "Load Game",
+                selection == *option,
+                &menu_title(*option, in_progress_game, ironman, listing),
             );
             y += 1;
         }
 
-        if in_progress_game {
-            print_menu_option(
-                &mut draw_batch,
-                y,
-                selection == MainMenuSelection::SaveGame,
-                "Save Game",
-            );
-            y += 1;
-        }
-
-        print_menu_option(
-            &mut draw_batch,
-            y,
-            selection == MainMenuSelection::Options,
-            "Options",
-        );
-        y += 1;
-
-        print_menu_option(
-            &mut draw_batch,
-            y,
-            selection == MainMenuSelection::Quit,
-            "Quit",
-        );
-
         draw_batch.submit(6000).expect("Failed to submit");
 
-        match ctx.key {
-            None => {
+        let current = available
+            .iter()
+            .position(|option| *option == selection)
+            .unwrap_or(0);
+        match keymap.action(KeyContext::Menu, KeyPress::from_ctx(ctx)) {
+            Some(Action::CloseMenu) => {
                 return MainMenuResult::NoSelection {
-                    selected: selection,
+                    selected: MainMenuSelection::Quit,
                 }
             }
-            Some(key) => match key {
-                rltk::VirtualKeyCode::Escape => {
-                    return MainMenuResult::NoSelection {
-                        selected: MainMenuSelection::Quit,
-                    }
-                }
-                rltk::VirtualKeyCode::Up => {
-                    let mut newselection;
-                    match selection {
-                        MainMenuSelection::NewGame => newselection = MainMenuSelection::Quit,
-                        MainMenuSelection::LoadGame => newselection = MainMenuSelection::NewGame,
-                        MainMenuSelection::SaveGame => newselection = MainMenuSelection::LoadGame,
-                        MainMenuSelection::Options => newselection = MainMenuSelection::SaveGame,
-                        MainMenuSelection::Quit => newselection = MainMenuSelection::Options,
-                    }
-                    if newselection == MainMenuSelection::SaveGame && !in_progress_game {
-                        newselection = MainMenuSelection::LoadGame;
-                    }
-                    if newselection == MainMenuSelection::LoadGame && !save_exists {
-                        newselection = MainMenuSelection::NewGame;
-                    }
-                    return MainMenuResult::NoSelection {
-                        selected: newselection,
-                    };
-                }
-                rltk::VirtualKeyCode::Down => {
-                    let mut newselection;
-                    match selection {
-                        MainMenuSelection::NewGame => newselection = MainMenuSelection::LoadGame,
-                        MainMenuSelection::LoadGame => newselection = MainMenuSelection::SaveGame,
-                        MainMenuSelection::SaveGame => newselection = MainMenuSelection::Options,
-                        MainMenuSelection::Options => newselection = MainMenuSelection::Quit,
-                        MainMenuSelection::Quit => newselection = MainMenuSelection::NewGame,
-                    }
-                    if newselection == MainMenuSelection::LoadGame && !save_exists {
-                        newselection = MainMenuSelection::SaveGame;
-                    }
-                    if newselection == MainMenuSelection::SaveGame && !in_progress_game {
-                        newselection = MainMenuSelection::Options;
-                    }
-                    return MainMenuResult::NoSelection {
-                        selected: newselection,
-                    };
-                }
-                rltk::VirtualKeyCode::Return => {
-                    return MainMenuResult::Selected {
-                        selected: selection,
-                    }
+            Some(Action::ScrollUp) => {
+                let newselection = if current == 0 {
+                    available[available.len() - 1]
+                } else {
+                    available[current - 1]
+                };
+                return MainMenuResult::NoSelection {
+                    selected: newselection,
+                };
+            }
+            Some(Action::ScrollDown) => {
+                return MainMenuResult::NoSelection {
+                    selected: available[(current + 1) % available.len()],
+                };
+            }
+            Some(Action::Select) => {
+                return MainMenuResult::Selected {
+                    selected: available[current],
                 }
-                _ => {
-                    return MainMenuResult::NoSelection {
-                        selected: selection,
-                    }
+            }
+            _ => {
+                return MainMenuResult::NoSelection {
+                    selected: selection,
                 }
-            },
+            }
         }
     }
 
@@ -1,4 +1,6 @@
 use super::ItemMenuResult;
+use crate::keymap::{Action, Keymap};
+use crate::player::KeyPress;
 use crate::rltk;
 use specs::prelude::*;
 
@@ -13,7 +15,8 @@ pub fn menu_box<T: ToString>(
     y: i32,
     width: i32,
     title: T,
-    help_options: Vec<(&str, &str)>,
+    keymap: &Keymap,
+    help_options: Vec<(Action, &str)>,
 ) {
     draw_batch.draw_box(
         rltk::Rect::with_size(MENU_X, y - MENU_PADDING, MENU_WIDTH, width),
@@ -27,7 +30,7 @@ pub fn menu_box<T: ToString>(
             rltk::RGB::named(rltk::BLACK),
         ),
     );
-    help_menu(draw_batch, y + width - MENU_PADDING, help_options);
+    help_menu(draw_batch, y + width - MENU_PADDING, keymap, help_options);
 }
 
 pub fn menu_option<T: ToString>(
@@ -62,12 +65,19 @@ pub fn menu_option<T: ToString>(
     );
 }
 
-pub fn help_menu(draw_batch: &mut rltk::DrawBatch, y: i32, extra_options: Vec<(&str, &str)>) {
-    print_help_text(draw_batch, y, ("ESC", "Cancel"));
-    print_help_text(draw_batch, y + 1, (",", "Previous Page"));
-    print_help_text(draw_batch, y + 2, (".", "Next Page"));
-    for (j, option) in extra_options.iter().enumerate() {
-        print_help_text(draw_batch, j as i32 + y + 3, *option);
+pub fn help_menu(
+    draw_batch: &mut rltk::DrawBatch,
+    y: i32,
+    keymap: &Keymap,
+    extra_options: Vec<(Action, &str)>,
+) {
+    let options = [
+        (Action::CloseMenu, "Cancel"),
+        (Action::PreviousPage, "Previous Page"),
+        (Action::NextPage, "Next Page"),
+    ];
+    for (j, (action, text)) in options.iter().chain(extra_options.iter()).enumerate() {
+        print_help_text(draw_batch, j as i32 + y, (&keymap.label(*action), *text));
     }
     draw_batch.print_color(
         rltk::Point::new(MENU_X + MENU_PADDING, extra_options.len() as i32 + y + 3),
@@ -107,14 +117,15 @@ pub fn item_result_menu<S: ToString>(
     draw_batch: &mut rltk::DrawBatch,
     title: S,
     items: &[(Entity, String, rltk::RGB)],
-    key: Option<rltk::VirtualKeyCode>,
+    input: KeyPress,
+    keymap: &Keymap,
     page: usize,
 ) -> (ItemMenuResult, Option<Entity>) {
     let paged_items = page_list(items, page);
     let count = paged_items.len();
 
     let mut y = (25 - (count / 2)) as i32;
-    menu_box(draw_batch, y, (count + 3) as i32, title, Vec::new());
+    menu_box(draw_batch, y, (count + 3) as i32, title, keymap, Vec::new());
 
     let mut item_list: Vec<Entity> = Vec::new();
     let mut item_num = 0;
@@ -131,34 +142,32 @@ pub fn item_result_menu<S: ToString>(
         item_num += 1;
     }
 
-    match key {
-        None => (ItemMenuResult::NoResponse, None),
-        Some(key) => match key {
-            rltk::VirtualKeyCode::Escape => (ItemMenuResult::Cancel, None),
-            rltk::VirtualKeyCode::Comma => {
-                if page > 0 && items.len() > ITEMS_PER_PAGE {
-                    (ItemMenuResult::PreviousPage, None)
-                } else {
-                    (ItemMenuResult::NoResponse, None)
-                }
-            }
-            rltk::VirtualKeyCode::Period => {
-                if item_num == ITEMS_PER_PAGE && items.len() > ITEMS_PER_PAGE {
-                    (ItemMenuResult::NextPage, None)
-                } else {
-                    (ItemMenuResult::NoResponse, None)
-                }
-            }
-            _ => {
-                let selection = rltk::letter_to_option(key);
-                if selection > -1 && selection < count as i32 {
-                    return (
-                        ItemMenuResult::Selected,
-                        Some(item_list[selection as usize]),
-                    );
-                }
-                (ItemMenuResult::NoResponse, None)
-            }
-        },
+    let key = match input.key {
+        None => return (ItemMenuResult::NoResponse, None),
+        Some(key) => key,
+    };
+    if keymap.is(Action::CloseMenu, input) {
+        (ItemMenuResult::Cancel, None)
+    } else if keymap.is(Action::PreviousPage, input) {
+        if page > 0 && items.len() > ITEMS_PER_PAGE {
+            (ItemMenuResult::PreviousPage, None)
+        } else {
+            (ItemMenuResult::NoResponse, None)
+        }
+    } else if keymap.is(Action::NextPage, input) {
+        if item_num == ITEMS_PER_PAGE && items.len() > ITEMS_PER_PAGE {
+            (ItemMenuResult::NextPage, None)
+        } else {
+            (ItemMenuResult::NoResponse, None)
+        }
+    } else {
+        let selection = rltk::letter_to_option(key);
+        if selection > -1 && selection < count as i32 {
+            return (
+                ItemMenuResult::Selected,
+                Some(item_list[selection as usize]),
+            );
+        }
+        (ItemMenuResult::NoResponse, None)
     }
 }
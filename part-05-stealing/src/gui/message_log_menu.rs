@@ -0,0 +1,196 @@
+use super::key_to_char;
+use crate::gamelog::{find_entries, LogCategory};
+use crate::keymap::{Action, Keymap};
+use crate::player::KeyPress;
+use crate::rltk;
+use crate::State;
+
+/// How many log entries fit on the screen at once.
+const VISIBLE_ENTRIES: usize = 46;
+/// Searches longer than this can't be typed.
+const MAX_SEARCH_LENGTH: usize = 30;
+const LEFT: i32 = 4;
+const RIGHT: i32 = 75;
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum MessageLogResult {
+    NoResponse {
+        scroll: usize,
+        category: Option<LogCategory>,
+        searching: bool,
+    },
+    Close,
+}
+
+/// The filter after `category`: every category, then each one in turn.
+fn next_category(category: Option<LogCategory>) -> Option<LogCategory> {
+    match category {
+        None => Some(LogCategory::ALL[0]),
+        Some(category) => {
+            let index = LogCategory::ALL
+                .iter()
+                .position(|c| *c == category)
+                .unwrap_or(0);
+            LogCategory::ALL.get(index + 1).copied()
+        }
+    }
+}
+
+/// Pages back through the whole message log. `scroll` counts entries back from the newest.
+pub fn message_log_menu(
+    gs: &mut State,
+    ctx: &mut rltk::BTerm,
+    scroll: usize,
+    category: Option<LogCategory>,
+    searching: bool,
+) -> MessageLogResult {
+    let mut draw_batch = rltk::DrawBatch::new();
+    let entries = find_entries(category, &gs.log_search);
+    let scroll = usize::min(scroll, entries.len().saturating_sub(VISIBLE_ENTRIES));
+
+    draw_batch.draw_double_box(
+        rltk::Rect::with_size(2, 2, 75, 55),
+        rltk::ColorPair::new(rltk::RGB::named(rltk::WHEAT), rltk::RGB::named(rltk::BLACK)),
+    );
+    draw_batch.print_color_centered(
+        3,
+        "Message History",
+        rltk::ColorPair::new(
+            rltk::RGB::named(rltk::YELLOW),
+            rltk::RGB::named(rltk::BLACK),
+        ),
+    );
+
+    let filter = category.map_or("All", LogCategory::name);
+    let search = if searching {
+        format!("{}_", gs.log_search)
+    } else {
+        gs.log_search.clone()
+    };
+    draw_batch.print_color(
+        rltk::Point::new(LEFT, 5),
+        format!("Showing: {:10} Search: {}", filter, search),
+        rltk::ColorPair::new(rltk::RGB::named(rltk::CYAN), rltk::RGB::named(rltk::BLACK)),
+    );
+
+    // Newest at the bottom, as in the log under the map
+    let end = entries.len() - scroll;
+    let start = end.saturating_sub(VISIBLE_ENTRIES);
+    for (y, entry) in (7..).zip(&entries[start..end]) {
+        let mut x = LEFT;
+        for frag in entry.fragments.iter() {
+            let room = (RIGHT - x).max(0) as usize;
+            let text: String = frag.text.chars().take(room).collect();
+            draw_batch.print_color(
+                rltk::Point::new(x, y),
+                &text,
+                rltk::ColorPair::new(frag.color, rltk::RGB::named(rltk::BLACK)),
+            );
+            x += frag.text.chars().count() as i32 + 1;
+        }
+    }
+    if entries.is_empty() {
+        draw_batch.print_color_centered(
+            7,
+            "No messages match",
+            rltk::ColorPair::new(rltk::RGB::named(rltk::GRAY), rltk::RGB::named(rltk::BLACK)),
+        );
+    }
+
+    let help_text = if searching {
+        "Type to search, Enter to finish, Escape to clear".to_string()
+    } else {
+        let keymap = gs.ecs.fetch::<Keymap>();
+        format!(
+            "{}/{} Scroll  {}/{} Page  {} Filter  {} Search  {} Close",
+            keymap.label(Action::ScrollUp),
+            keymap.label(Action::ScrollDown),
+            keymap.label(Action::PreviousPage),
+            keymap.label(Action::NextPage),
+            keymap.label(Action::LogFilter),
+            keymap.label(Action::LogSearch),
+            keymap.label(Action::CloseMenu),
+        )
+    };
+    draw_batch.print_color_centered(
+        55,
+        &help_text,
+        rltk::ColorPair::new(rltk::RGB::named(rltk::GRAY), rltk::RGB::named(rltk::BLACK)),
+    );
+
+    draw_batch.submit(6000).expect("Failed to submit");
+
+    let input = KeyPress::from_ctx(ctx);
+    let key = match input.key {
+        None => {
+            return MessageLogResult::NoResponse {
+                scroll,
+                category,
+                searching,
+            }
+        }
+        Some(key) => key,
+    };
+
+    // While typing a search, keys are text rather than commands
+    if searching {
+        let mut searching = true;
+        match key {
+            rltk::VirtualKeyCode::Escape => {
+                gs.log_search.clear();
+                searching = false;
+            }
+            rltk::VirtualKeyCode::Return => searching = false,
+            rltk::VirtualKeyCode::Back => {
+                gs.log_search.pop();
+            }
+            rltk::VirtualKeyCode::Space if gs.log_search.len() < MAX_SEARCH_LENGTH => {
+                gs.log_search.push(' ');
+            }
+            _ => {
+                if let Some(c) = key_to_char(key) {
+                    if gs.log_search.len() < MAX_SEARCH_LENGTH {
+                        gs.log_search.push(c);
+                    }
+                }
+            }
+        }
+        return MessageLogResult::NoResponse {
+            scroll: 0,
+            category,
+            searching,
+        };
+    }
+
+    let keymap = gs.ecs.fetch::<Keymap>();
+    let scroll = if keymap.is(Action::CloseMenu, input) {
+        return MessageLogResult::Close;
+    } else if keymap.is(Action::ScrollUp, input) {
+        scroll + 1
+    } else if keymap.is(Action::ScrollDown, input) {
+        scroll.saturating_sub(1)
+    } else if keymap.is(Action::PreviousPage, input) {
+        scroll + VISIBLE_ENTRIES
+    } else if keymap.is(Action::NextPage, input) {
+        scroll.saturating_sub(VISIBLE_ENTRIES)
+    } else if keymap.is(Action::LogFilter, input) {
+        return MessageLogResult::NoResponse {
+            scroll: 0,
+            category: next_category(category),
+            searching,
+        };
+    } else if keymap.is(Action::LogSearch, input) {
+        return MessageLogResult::NoResponse {
+            scroll: 0,
+            category,
+            searching: true,
+        };
+    } else {
+        scroll
+    };
+    MessageLogResult::NoResponse {
+        scroll,
+        category,
+        searching,
+    }
+}
@@ -24,7 +24,17 @@ mod cheat_menu;
 pub use cheat_menu::*;
 mod vendor_menu;
 pub use vendor_menu::*;
+mod fine_menu;
+pub use fine_menu::*;
 mod menus;
 pub use menus::*;
 mod options_menu;
 pub use options_menu::*;
+mod save_slot_menu;
+pub use save_slot_menu::*;
+mod seed_entry_menu;
+pub use seed_entry_menu::*;
+mod message_log_menu;
+pub use message_log_menu::*;
+mod look_mode;
+pub use look_mode::*;
@@ -1,4 +1,7 @@
+use crate::keymap::{key_press_name, Action, KeyContext, Keymap};
+use crate::player::KeyPress;
 use crate::rltk;
+use crate::saveload_system::SaveFormatKind;
 use crate::systems::sound_system::SoundSystem;
 use crate::{rex_assets::RexAssets, RunState, State};
 
@@ -9,8 +12,11 @@ pub enum OptionsMenuSelection {
     ToggleFps,
     ToggleMapVisualizer,
     ToggleCheatMenu,
+    ToggleAutoPickup,
+    ToggleSaveFormat,
     BackgroundVolume { change: VolumeChange },
     EffectsVolume { change: VolumeChange },
+    KeyBindings,
     Quit,
 }
 
@@ -32,10 +38,11 @@ pub fn options_menu(gs: &mut State, ctx: &mut rltk::BTerm) -> OptionsMenuResult
     let runstate = gs.ecs.fetch::<RunState>();
     let assets = gs.ecs.fetch::<RexAssets>();
     let sound_system = gs.ecs.fetch::<SoundSystem>();
+    let keymap = gs.ecs.fetch::<Keymap>();
     ctx.render_xp_sprite(&assets.menu, 0, 5);
 
     draw_batch.draw_double_box(
-        rltk::Rect::with_size(20, 18, 40, 12),
+        rltk::Rect::with_size(20, 18, 40, 15),
         rltk::ColorPair::new(rltk::RGB::named(rltk::WHEAT), rltk::RGB::named(rltk::BLACK)),
     );
 
@@ -49,7 +56,11 @@ pub fn options_menu(gs: &mut State, ctx: &mut rltk::BTerm) -> OptionsMenuResult
     );
     draw_batch.print_color_centered(
         21,
-        "Use Up/Down Arrows to Select Option",
+        format!(
+            "Use {}/{} to Select Option",
+            keymap.label(Action::ScrollUp),
+            keymap.label(Action::ScrollDown)
+        ),
         rltk::ColorPair::new(rltk::RGB::named(rltk::GRAY), rltk::RGB::named(rltk::BLACK)),
     );
 
@@ -60,15 +71,21 @@ pub fn options_menu(gs: &mut State, ctx: &mut rltk::BTerm) -> OptionsMenuResult
         menu_selection: selection,
     } = *runstate
     {
-        let help_text: &str = match selection {
+        let select = keymap.label(Action::Select);
+        let help_text = match selection {
             OptionsMenuSelection::BackgroundVolume { .. }
-            | OptionsMenuSelection::EffectsVolume { .. } => "Use Left/Right Arrows to Change Value",
-            OptionsMenuSelection::Quit => "Use Enter to Go Back",
-            _ => "Use Enter to Toggle Value",
+            | OptionsMenuSelection::EffectsVolume { .. } => format!(
+                "Use {}/{} to Change Value",
+                keymap.label(Action::DecreaseValue),
+                keymap.label(Action::IncreaseValue)
+            ),
+            OptionsMenuSelection::KeyBindings => format!("Use {} to Change Keys", select),
+            OptionsMenuSelection::Quit => format!("Use {} to Go Back", select),
+            _ => format!("Use {} to Toggle Value", select),
         };
         draw_batch.print_color_centered(
             22,
-            help_text,
+            &help_text,
             rltk::ColorPair::new(rltk::RGB::named(rltk::GRAY), rltk::RGB::named(rltk::BLACK)),
         );
 
@@ -102,8 +119,29 @@ pub fn options_menu(gs: &mut State, ctx: &mut rltk::BTerm) -> OptionsMenuResult
         );
         y += 1;
 
+        let auto_pickup_title = format!("Explore Picks Up Items: {}", gs.game_options.auto_pickup);
+        print_menu_option(
+            &mut draw_batch,
+            y,
+            selection == OptionsMenuSelection::ToggleAutoPickup,
+            &auto_pickup_title,
+        );
+        y += 1;
+
+        let save_format_title = match gs.game_options.save_format {
+            SaveFormatKind::Json => "Save Format: JSON",
+            SaveFormatKind::Binary => "Save Format: Binary",
+        };
+        print_menu_option(
+            &mut draw_batch,
+            y,
+            selection == OptionsMenuSelection::ToggleSaveFormat,
+            save_format_title,
+        );
+        y += 1;
+
         let background_volume_title = format!(
-            "Background Volume: {}",
+            "Background Volume: {:.1}",
             sound_system.get_background_volume(),
         );
         print_menu_option(
@@ -117,8 +155,10 @@ pub fn options_menu(gs: &mut State, ctx: &mut rltk::BTerm) -> OptionsMenuResult
         );
         y += 1;
 
-        let effect_volume_title =
-            format!("Sound Effect Volume: {}", sound_system.get_effects_volume(),);
+        let effect_volume_title = format!(
+            "Sound Effect Volume: {:.1}",
+            sound_system.get_effects_volume(),
+        );
         print_menu_option(
             &mut draw_batch,
             y,
@@ -130,6 +170,14 @@ pub fn options_menu(gs: &mut State, ctx: &mut rltk::BTerm) -> OptionsMenuResult
         );
         y += 1;
 
+        print_menu_option(
+            &mut draw_batch,
+            y,
+            selection == OptionsMenuSelection::KeyBindings,
+            "Key Bindings",
+        );
+        y += 1;
+
         print_menu_option(
             &mut draw_batch,
             y,
@@ -139,133 +187,136 @@ pub fn options_menu(gs: &mut State, ctx: &mut rltk::BTerm) -> OptionsMenuResult
 
         draw_batch.submit(6000).expect("Failed to submit");
 
-        match ctx.key {
-            None => {
+        match keymap.action(KeyContext::Menu, KeyPress::from_ctx(ctx)) {
+            Some(Action::CloseMenu) => {
                 return OptionsMenuResult::NoSelection {
-                    selected: selection,
+                    selected: OptionsMenuSelection::Quit,
                 }
             }
-            Some(key) => match key {
-                rltk::VirtualKeyCode::Escape => {
-                    return OptionsMenuResult::NoSelection {
-                        selected: OptionsMenuSelection::Quit,
+            Some(Action::ScrollUp) => {
+                let newselection;
+                match selection {
+                    OptionsMenuSelection::ToggleFps => newselection = OptionsMenuSelection::Quit,
+                    OptionsMenuSelection::ToggleMapVisualizer => {
+                        newselection = OptionsMenuSelection::ToggleFps
                     }
-                }
-                rltk::VirtualKeyCode::Up => {
-                    let newselection;
-                    match selection {
-                        OptionsMenuSelection::ToggleFps => {
-                            newselection = OptionsMenuSelection::Quit
-                        }
-                        OptionsMenuSelection::ToggleMapVisualizer => {
-                            newselection = OptionsMenuSelection::ToggleFps
-                        }
-                        OptionsMenuSelection::ToggleCheatMenu => {
-                            newselection = OptionsMenuSelection::ToggleMapVisualizer
-                        }
-                        OptionsMenuSelection::BackgroundVolume { .. } => {
-                            newselection = OptionsMenuSelection::ToggleCheatMenu
-                        }
-                        OptionsMenuSelection::EffectsVolume { .. } => {
-                            newselection = OptionsMenuSelection::BackgroundVolume {
-                                change: VolumeChange::None,
-                            }
+                    OptionsMenuSelection::ToggleCheatMenu => {
+                        newselection = OptionsMenuSelection::ToggleMapVisualizer
+                    }
+                    OptionsMenuSelection::ToggleAutoPickup => {
+                        newselection = OptionsMenuSelection::ToggleCheatMenu
+                    }
+                    OptionsMenuSelection::ToggleSaveFormat => {
+                        newselection = OptionsMenuSelection::ToggleAutoPickup
+                    }
+                    OptionsMenuSelection::BackgroundVolume { .. } => {
+                        newselection = OptionsMenuSelection::ToggleSaveFormat
+                    }
+                    OptionsMenuSelection::EffectsVolume { .. } => {
+                        newselection = OptionsMenuSelection::BackgroundVolume {
+                            change: VolumeChange::None,
                         }
-                        OptionsMenuSelection::Quit => {
-                            newselection = OptionsMenuSelection::EffectsVolume {
-                                change: VolumeChange::None,
-                            }
+                    }
+                    OptionsMenuSelection::KeyBindings => {
+                        newselection = OptionsMenuSelection::EffectsVolume {
+                            change: VolumeChange::None,
                         }
                     }
-                    return OptionsMenuResult::NoSelection {
-                        selected: newselection,
-                    };
+                    OptionsMenuSelection::Quit => newselection = OptionsMenuSelection::KeyBindings,
                 }
-                rltk::VirtualKeyCode::Down => {
-                    let newselection;
-                    match selection {
-                        OptionsMenuSelection::ToggleFps => {
-                            newselection = OptionsMenuSelection::ToggleMapVisualizer
-                        }
-                        OptionsMenuSelection::ToggleMapVisualizer => {
-                            newselection = OptionsMenuSelection::ToggleCheatMenu
-                        }
-                        OptionsMenuSelection::ToggleCheatMenu => {
-                            newselection = OptionsMenuSelection::BackgroundVolume {
-                                change: VolumeChange::None,
-                            }
-                        }
-                        OptionsMenuSelection::BackgroundVolume { .. } => {
-                            newselection = OptionsMenuSelection::EffectsVolume {
-                                change: VolumeChange::None,
-                            }
-                        }
-                        OptionsMenuSelection::EffectsVolume { .. } => {
-                            newselection = OptionsMenuSelection::Quit
+                return OptionsMenuResult::NoSelection {
+                    selected: newselection,
+                };
+            }
+            Some(Action::ScrollDown) => {
+                let newselection;
+                match selection {
+                    OptionsMenuSelection::ToggleFps => {
+                        newselection = OptionsMenuSelection::ToggleMapVisualizer
+                    }
+                    OptionsMenuSelection::ToggleMapVisualizer => {
+                        newselection = OptionsMenuSelection::ToggleCheatMenu
+                    }
+                    OptionsMenuSelection::ToggleCheatMenu => {
+                        newselection = OptionsMenuSelection::ToggleAutoPickup
+                    }
+                    OptionsMenuSelection::ToggleAutoPickup => {
+                        newselection = OptionsMenuSelection::ToggleSaveFormat
+                    }
+                    OptionsMenuSelection::ToggleSaveFormat => {
+                        newselection = OptionsMenuSelection::BackgroundVolume {
+                            change: VolumeChange::None,
                         }
-                        OptionsMenuSelection::Quit => {
-                            newselection = OptionsMenuSelection::ToggleFps
+                    }
+                    OptionsMenuSelection::BackgroundVolume { .. } => {
+                        newselection = OptionsMenuSelection::EffectsVolume {
+                            change: VolumeChange::None,
                         }
                     }
-                    return OptionsMenuResult::NoSelection {
-                        selected: newselection,
-                    };
+                    OptionsMenuSelection::EffectsVolume { .. } => {
+                        newselection = OptionsMenuSelection::KeyBindings
+                    }
+                    OptionsMenuSelection::KeyBindings => newselection = OptionsMenuSelection::Quit,
+                    OptionsMenuSelection::Quit => newselection = OptionsMenuSelection::ToggleFps,
                 }
-                rltk::VirtualKeyCode::Left => {
-                    match selection {
-                        OptionsMenuSelection::BackgroundVolume { .. } => {
-                            return OptionsMenuResult::Selected {
-                                selected: OptionsMenuSelection::BackgroundVolume {
-                                    change: VolumeChange::Decrease,
-                                },
-                            }
+                return OptionsMenuResult::NoSelection {
+                    selected: newselection,
+                };
+            }
+            Some(Action::DecreaseValue) => {
+                match selection {
+                    OptionsMenuSelection::BackgroundVolume { .. } => {
+                        return OptionsMenuResult::Selected {
+                            selected: OptionsMenuSelection::BackgroundVolume {
+                                change: VolumeChange::Decrease,
+                            },
                         }
-                        OptionsMenuSelection::EffectsVolume { .. } => {
-                            return OptionsMenuResult::Selected {
-                                selected: OptionsMenuSelection::EffectsVolume {
-                                    change: VolumeChange::Decrease,
-                                },
-                            }
+                    }
+                    OptionsMenuSelection::EffectsVolume { .. } => {
+                        return OptionsMenuResult::Selected {
+                            selected: OptionsMenuSelection::EffectsVolume {
+                                change: VolumeChange::Decrease,
+                            },
                         }
-                        _ => {}
                     }
-                    return OptionsMenuResult::NoSelection {
-                        selected: selection,
-                    };
+                    _ => {}
                 }
-                rltk::VirtualKeyCode::Right => {
-                    match selection {
-                        OptionsMenuSelection::BackgroundVolume { .. } => {
-                            return OptionsMenuResult::Selected {
-                                selected: OptionsMenuSelection::BackgroundVolume {
-                                    change: VolumeChange::Increase,
-                                },
-                            }
+                return OptionsMenuResult::NoSelection {
+                    selected: selection,
+                };
+            }
+            Some(Action::IncreaseValue) => {
+                match selection {
+                    OptionsMenuSelection::BackgroundVolume { .. } => {
+                        return OptionsMenuResult::Selected {
+                            selected: OptionsMenuSelection::BackgroundVolume {
+                                change: VolumeChange::Increase,
+                            },
                         }
-                        OptionsMenuSelection::EffectsVolume { .. } => {
-                            return OptionsMenuResult::Selected {
-                                selected: OptionsMenuSelection::EffectsVolume {
-                                    change: VolumeChange::Increase,
-                                },
-                            }
+                    }
+                    OptionsMenuSelection::EffectsVolume { .. } => {
+                        return OptionsMenuResult::Selected {
+                            selected: OptionsMenuSelection::EffectsVolume {
+                                change: VolumeChange::Increase,
+                            },
                         }
-                        _ => {}
                     }
-                    return OptionsMenuResult::NoSelection {
-                        selected: selection,
-                    };
+                    _ => {}
                 }
-                rltk::VirtualKeyCode::Return => {
-                    return OptionsMenuResult::Selected {
-                        selected: selection,
-                    }
+                return OptionsMenuResult::NoSelection {
+                    selected: selection,
+                };
+            }
+            Some(Action::Select) => {
+                return OptionsMenuResult::Selected {
+                    selected: selection,
                 }
-                _ => {
-                    return OptionsMenuResult::NoSelection {
-                        selected: selection,
-                    }
+            }
+            _ => {
+                return OptionsMenuResult::NoSelection {
+                    selected: selection,
                 }
-            },
+            }
         }
     }
 
@@ -273,3 +324,156 @@ pub fn options_menu(gs: &mut State, ctx: &mut rltk::BTerm) -> OptionsMenuResult
         selected: OptionsMenuSelection::Quit,
     }
 }
+
+/// How many actions the key bindings screen lists at once.
+const VISIBLE_BINDINGS: usize = 36;
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum KeyBindingsResult {
+    NoResponse { selection: usize, capturing: bool },
+    Bind { selection: usize, key: KeyPress },
+    Clear { selection: usize },
+    Reset { selection: usize },
+    Back,
+}
+
+/// Lists every action with its keys. The screen's own keys are fixed, so a bad binding can
+/// always be undone from here.
+pub fn key_bindings_menu(
+    gs: &mut State,
+    ctx: &mut rltk::BTerm,
+    selection: usize,
+    capturing: bool,
+) -> KeyBindingsResult {
+    let mut draw_batch = rltk::DrawBatch::new();
+    let assets = gs.ecs.fetch::<RexAssets>();
+    let keymap = gs.ecs.fetch::<Keymap>();
+    ctx.render_xp_sprite(&assets.menu, 0, 5);
+
+    draw_batch.draw_double_box(
+        rltk::Rect::with_size(8, 6, 64, 47),
+        rltk::ColorPair::new(rltk::RGB::named(rltk::WHEAT), rltk::RGB::named(rltk::BLACK)),
+    );
+    draw_batch.print_color_centered(
+        7,
+        "Key Bindings",
+        rltk::ColorPair::new(
+            rltk::RGB::named(rltk::YELLOW),
+            rltk::RGB::named(rltk::BLACK),
+        ),
+    );
+    let help_text = if capturing {
+        "Press the New Key, or Escape to Cancel"
+    } else {
+        "Enter to Add a Key, Delete to Clear, R to Reset"
+    };
+    draw_batch.print_color_centered(
+        8,
+        help_text,
+        rltk::ColorPair::new(rltk::RGB::named(rltk::GRAY), rltk::RGB::named(rltk::BLACK)),
+    );
+    draw_batch.print_color_centered(
+        9,
+        "Use Up/Down Arrows to Select, Escape to Go Back",
+        rltk::ColorPair::new(rltk::RGB::named(rltk::GRAY), rltk::RGB::named(rltk::BLACK)),
+    );
+
+    let conflicts = keymap.conflicts();
+    let first = usize::min(
+        selection.saturating_sub(VISIBLE_BINDINGS / 2),
+        Action::ALL.len().saturating_sub(VISIBLE_BINDINGS),
+    );
+    let shown = Action::ALL
+        .iter()
+        .enumerate()
+        .skip(first)
+        .take(VISIBLE_BINDINGS);
+    for (y, (index, action)) in (11..).zip(shown) {
+        let color = if index == selection {
+            rltk::MAGENTA
+        } else if conflicts.contains(action) {
+            rltk::RED
+        } else {
+            rltk::WHITE
+        };
+        let keys = if index == selection && capturing {
+            "...".to_string()
+        } else {
+            keymap.describe(*action)
+        };
+        draw_batch.print_color(
+            rltk::Point::new(10, y),
+            format!("{:24}{:.34}", action.description(), keys),
+            rltk::ColorPair::new(rltk::RGB::named(color), rltk::RGB::named(rltk::BLACK)),
+        );
+    }
+
+    if let Some(message) = &gs.key_bindings_message {
+        draw_batch.print_color_centered(
+            50,
+            message,
+            rltk::ColorPair::new(rltk::RGB::named(rltk::RED), rltk::RGB::named(rltk::BLACK)),
+        );
+    } else if !conflicts.is_empty() {
+        draw_batch.print_color_centered(
+            50,
+            "Actions in red share a key with another action",
+            rltk::ColorPair::new(rltk::RGB::named(rltk::RED), rltk::RGB::named(rltk::BLACK)),
+        );
+    }
+
+    draw_batch.submit(6000).expect("Failed to submit");
+
+    let input = KeyPress::from_ctx(ctx);
+    let key = match input.key {
+        None => {
+            return KeyBindingsResult::NoResponse {
+                selection,
+                capturing,
+            }
+        }
+        Some(key) => key,
+    };
+
+    if capturing {
+        return match key {
+            rltk::VirtualKeyCode::Escape => KeyBindingsResult::NoResponse {
+                selection,
+                capturing: false,
+            },
+            // Keys with no name (such as shift held on its own) can't be saved, so keep waiting
+            _ if key_press_name(input).is_none() => KeyBindingsResult::NoResponse {
+                selection,
+                capturing,
+            },
+            _ => KeyBindingsResult::Bind {
+                selection,
+                key: input,
+            },
+        };
+    }
+
+    match key {
+        rltk::VirtualKeyCode::Escape => KeyBindingsResult::Back,
+        rltk::VirtualKeyCode::Up => KeyBindingsResult::NoResponse {
+            selection: (selection + Action::ALL.len() - 1) % Action::ALL.len(),
+            capturing,
+        },
+        rltk::VirtualKeyCode::Down => KeyBindingsResult::NoResponse {
+            selection: (selection + 1) % Action::ALL.len(),
+            capturing,
+        },
+        rltk::VirtualKeyCode::Return => KeyBindingsResult::NoResponse {
+            selection,
+            capturing: true,
+        },
+        rltk::VirtualKeyCode::Delete | rltk::VirtualKeyCode::Back => {
+            KeyBindingsResult::Clear { selection }
+        }
+        rltk::VirtualKeyCode::R => KeyBindingsResult::Reset { selection },
+        _ => KeyBindingsResult::NoResponse {
+            selection,
+            capturing,
+        },
+    }
+}
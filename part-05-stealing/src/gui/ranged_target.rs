@@ -32,17 +32,13 @@ pub fn ranged_target(
         for idx in visible.visible_tiles.iter() {
             let distance = rltk::DistanceAlg::Pythagoras.distance2d(*player_pos, *idx);
             if distance <= range as f32 {
-                let screen_x = idx.x - min_x;
-                let screen_y = idx.y - min_y;
-                if screen_x > 1
-                    && screen_x < (max_x - min_x) - 1
-                    && screen_y > 1
-                    && screen_y < (max_y - min_y) - 1
+                let screen = camera::map_to_screen(&gs.ecs, ctx, *idx);
+                if screen.x > 1
+                    && screen.x < (max_x - min_x) - 1
+                    && screen.y > 1
+                    && screen.y < (max_y - min_y) - 1
                 {
-                    draw_batch.set_bg(
-                        rltk::Point::new(screen_x, screen_y),
-                        rltk::RGB::named(rltk::BLUE),
-                    );
+                    draw_batch.set_bg(screen, rltk::RGB::named(rltk::BLUE));
                     available_cells.push(idx);
                 }
             }
@@ -52,32 +48,16 @@ pub fn ranged_target(
     }
 
     // Draw mouse cursor
-    let mouse_pos = ctx.mouse_pos();
-    let mut mouse_map_pos = mouse_pos;
-    mouse_map_pos.0 += min_x - 1;
-    mouse_map_pos.1 += min_y - 1;
-    let mut valid_target = false;
-    for idx in available_cells.iter() {
-        if idx.x == mouse_map_pos.0 && idx.y == mouse_map_pos.1 {
-            valid_target = true;
-        }
-    }
+    let mouse_pos = ctx.mouse_point();
+    let mouse_map_pos = camera::screen_to_map(&gs.ecs, ctx, mouse_pos);
+    let valid_target = available_cells.iter().any(|idx| **idx == mouse_map_pos);
     if valid_target {
-        draw_batch.set_bg(
-            rltk::Point::new(mouse_pos.0, mouse_pos.1),
-            rltk::RGB::named(rltk::CYAN),
-        );
+        draw_batch.set_bg(mouse_pos, rltk::RGB::named(rltk::CYAN));
         if ctx.left_click {
-            return (
-                ItemMenuResult::Selected,
-                Some(rltk::Point::new(mouse_map_pos.0, mouse_map_pos.1)),
-            );
+            return (ItemMenuResult::Selected, Some(mouse_map_pos));
         }
     } else {
-        draw_batch.set_bg(
-            rltk::Point::new(mouse_pos.0, mouse_pos.1),
-            rltk::RGB::named(rltk::RED),
-        );
+        draw_batch.set_bg(mouse_pos, rltk::RGB::named(rltk::RED));
         if ctx.left_click {
             return (ItemMenuResult::Cancel, None);
         }
@@ -1,4 +1,6 @@
 use super::{get_item_color, get_item_display_name, item_result_menu, ItemMenuResult};
+use crate::keymap::Keymap;
+use crate::player::KeyPress;
 use crate::rltk;
 use crate::{Equipped, State};
 use specs::prelude::*;
@@ -26,7 +28,14 @@ pub fn remove_item_menu(
             ))
         });
 
-    let result = item_result_menu(&mut draw_batch, "Remove which item?", &items, ctx.key, page);
+    let result = item_result_menu(
+        &mut draw_batch,
+        "Remove which item?",
+        &items,
+        KeyPress::from_ctx(ctx),
+        &gs.ecs.fetch::<Keymap>(),
+        page,
+    );
     draw_batch.submit(6000).expect("Failed to submit");
     result
 }
@@ -0,0 +1,251 @@
+use crate::keymap::{Action, KeyContext, Keymap};
+use crate::player::KeyPress;
+use crate::rltk;
+use crate::saveload_system::{compatibility, SaveCompatibility, SaveMetadata, SAVE_SLOTS};
+use crate::{rex_assets::RexAssets, RunState, State};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::print_menu_option;
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum SaveSlotMode {
+    Load,
+    Save,
+}
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum SaveSlotResult {
+    NoSelection { selected: usize },
+    Selected { selected: usize },
+    Delete { selected: usize },
+    Cancel,
+}
+
+fn describe_age(timestamp: u64) -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+    let seconds = now.saturating_sub(timestamp);
+    match seconds {
+        s if s < 60 => "just now".to_string(),
+        s if s < 60 * 60 => format!("{} minutes ago", s / 60),
+        s if s < 60 * 60 * 24 => format!("{} hours ago", s / (60 * 60)),
+        s => format!("{} days ago", s / (60 * 60 * 24)),
+    }
+}
+
+fn slot_title(slot: usize, metadata: &Option<SaveMetadata>) -> String {
+    match metadata {
+        None => format!("Slot {}: Empty", slot + 1),
+        Some(metadata) => format!(
+            "Slot {}: Level {} - {}",
+            slot + 1,
+            metadata.character_level,
+            metadata.map_name
+        ),
+    }
+}
+
+fn is_loadable(metadata: &Option<SaveMetadata>) -> bool {
+    match metadata {
+        None => false,
+        Some(metadata) => !matches!(
+            compatibility(metadata.version),
+            SaveCompatibility::Incompatible { .. }
+        ),
+    }
+}
+
+fn draw_preview(draw_batch: &mut rltk::DrawBatch, y: i32, metadata: &Option<SaveMetadata>) {
+    let gray = rltk::ColorPair::new(rltk::RGB::named(rltk::GRAY), rltk::RGB::named(rltk::BLACK));
+    match metadata {
+        None => {
+            draw_batch.print_color_centered(y, "No game saved in this slot", gray);
+        }
+        Some(metadata) => {
+            draw_batch.print_color_centered(
+                y,
+                format!("Depth {}, {} turns", metadata.depth, metadata.turns),
+                gray,
+            );
+            draw_batch.print_color_centered(
+                y + 1,
+                format!("Saved {}", describe_age(metadata.timestamp)),
+                gray,
+            );
+            if let SaveCompatibility::Incompatible { version } = compatibility(metadata.version) {
+                draw_batch.print_color_centered(
+                    y + 2,
+                    format!("Incompatible save (format version {})", version),
+                    rltk::ColorPair::new(
+                        rltk::RGB::named(rltk::RED),
+                        rltk::RGB::named(rltk::BLACK),
+                    ),
+                );
+            }
+        }
+    }
+}
+
+pub fn save_slot_menu(gs: &mut State, ctx: &mut rltk::BTerm) -> SaveSlotResult {
+    let mut draw_batch = rltk::DrawBatch::new();
+    let runstate = gs.ecs.fetch::<RunState>();
+    let assets = gs.ecs.fetch::<RexAssets>();
+    let keymap = gs.ecs.fetch::<Keymap>();
+    ctx.render_xp_sprite(&assets.menu, 0, 5);
+
+    draw_batch.draw_double_box(
+        rltk::Rect::with_size(18, 18, 44, 16),
+        rltk::ColorPair::new(rltk::RGB::named(rltk::WHEAT), rltk::RGB::named(rltk::BLACK)),
+    );
+
+    if let RunState::SaveSlots { mode, selection } = *runstate {
+        let slots = &gs.save_listing.slots;
+
+        let title = match mode {
+            SaveSlotMode::Load => "Load Game",
+            SaveSlotMode::Save => "Save Game",
+        };
+        draw_batch.print_color_centered(
+            20,
+            title,
+            rltk::ColorPair::new(
+                rltk::RGB::named(rltk::YELLOW),
+                rltk::RGB::named(rltk::BLACK),
+            ),
+        );
+        let select = keymap.label(Action::Select);
+        let delete = keymap.label(Action::DeleteEntry);
+        let help_text = match (mode, slots[selection].is_some()) {
+            (SaveSlotMode::Load, true) if is_loadable(&slots[selection]) => {
+                format!("{} to Load, {} to Remove", select, delete)
+            }
+            (SaveSlotMode::Load, true) => format!("{} to Remove", delete),
+            (SaveSlotMode::Load, false) => format!(
+                "Use {}/{} to Select Slot",
+                keymap.label(Action::ScrollUp),
+                keymap.label(Action::ScrollDown)
+            ),
+            (SaveSlotMode::Save, true) => format!("{} to Overwrite, {} to Remove", select, delete),
+            (SaveSlotMode::Save, false) => format!("{} to Save", select),
+        };
+        draw_batch.print_color_centered(
+            21,
+            &help_text,
+            rltk::ColorPair::new(rltk::RGB::named(rltk::GRAY), rltk::RGB::named(rltk::BLACK)),
+        );
+
+        let mut y = 23;
+        for (slot, metadata) in slots.iter().enumerate() {
+            print_menu_option(
+                &mut draw_batch,
+                y,
+                selection == slot,
+                &slot_title(slot, metadata),
+            );
+            y += 1;
+        }
+
+        draw_preview(&mut draw_batch, y + 1, &slots[selection]);
+
+        draw_batch.submit(6000).expect("Failed to submit");
+
+        match keymap.action(KeyContext::Menu, KeyPress::from_ctx(ctx)) {
+            Some(Action::CloseMenu) => return SaveSlotResult::Cancel,
+            Some(Action::ScrollUp) => {
+                let newselection = if selection == 0 {
+                    SAVE_SLOTS - 1
+                } else {
+                    selection - 1
+                };
+                return SaveSlotResult::NoSelection {
+                    selected: newselection,
+                };
+            }
+            Some(Action::ScrollDown) => {
+                return SaveSlotResult::NoSelection {
+                    selected: (selection + 1) % SAVE_SLOTS,
+                };
+            }
+            Some(Action::DeleteEntry) => {
+                if slots[selection].is_some() {
+                    return SaveSlotResult::Delete {
+                        selected: selection,
+                    };
+                }
+                return SaveSlotResult::NoSelection {
+                    selected: selection,
+                };
+            }
+            Some(Action::Select) => {
+                if mode == SaveSlotMode::Load && !is_loadable(&slots[selection]) {
+                    return SaveSlotResult::NoSelection {
+                        selected: selection,
+                    };
+                }
+                return SaveSlotResult::Selected {
+                    selected: selection,
+                };
+            }
+            _ => {
+                return SaveSlotResult::NoSelection {
+                    selected: selection,
+                }
+            }
+        }
+    }
+
+    SaveSlotResult::Cancel
+}
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum SaveErrorResult {
+    NoResponse,
+    Dismissed,
+}
+
+pub fn save_error_dialog(
+    gs: &mut State,
+    ctx: &mut rltk::BTerm,
+    mode: SaveSlotMode,
+) -> SaveErrorResult {
+    let mut draw_batch = rltk::DrawBatch::new();
+    let assets = gs.ecs.fetch::<RexAssets>();
+    ctx.render_xp_sprite(&assets.menu, 0, 5);
+
+    draw_batch.draw_double_box(
+        rltk::Rect::with_size(10, 18, 60, 8),
+        rltk::ColorPair::new(rltk::RGB::named(rltk::WHEAT), rltk::RGB::named(rltk::BLACK)),
+    );
+    let title = match mode {
+        SaveSlotMode::Load => "Unable to Load Game",
+        SaveSlotMode::Save => "Unable to Save Game",
+    };
+    draw_batch.print_color_centered(
+        20,
+        title,
+        rltk::ColorPair::new(rltk::RGB::named(rltk::RED), rltk::RGB::named(rltk::BLACK)),
+    );
+    if let Some(error) = &gs.save_error {
+        draw_batch.print_color_centered(
+            22,
+            error.to_string(),
+            rltk::ColorPair::new(rltk::RGB::named(rltk::WHITE), rltk::RGB::named(rltk::BLACK)),
+        );
+    }
+    draw_batch.print_color_centered(
+        24,
+        "Press any key to return to the menu.",
+        rltk::ColorPair::new(
+            rltk::RGB::named(rltk::MAGENTA),
+            rltk::RGB::named(rltk::BLACK),
+        ),
+    );
+
+    draw_batch.submit(6000).expect("Failed to submit");
+
+    match ctx.key {
+        None => SaveErrorResult::NoResponse,
+        Some(_) => SaveErrorResult::Dismissed,
+    }
+}
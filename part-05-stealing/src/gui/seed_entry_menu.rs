@@ -0,0 +1,103 @@
+use crate::keymap::{Action, Keymap};
+use crate::player::KeyPress;
+use crate::rltk;
+use crate::{rex_assets::RexAssets, State};
+
+/// Seeds longer than this can't be typed; a u64 never needs more digits.
+const MAX_SEED_LENGTH: usize = 20;
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum SeedEntryResult {
+    NoResponse,
+    Cancel,
+    Selected { seed: u64 },
+}
+
+/// The letter or digit a key types, for menus that take text.
+pub fn key_to_char(key: rltk::VirtualKeyCode) -> Option<char> {
+    use rltk::VirtualKeyCode;
+    let letter = rltk::letter_to_option(key);
+    if letter >= 0 {
+        return Some((b'a' + letter as u8) as char);
+    }
+    let digit = match key {
+        VirtualKeyCode::Key0 | VirtualKeyCode::Numpad0 => 0,
+        VirtualKeyCode::Key1 | VirtualKeyCode::Numpad1 => 1,
+        VirtualKeyCode::Key2 | VirtualKeyCode::Numpad2 => 2,
+        VirtualKeyCode::Key3 | VirtualKeyCode::Numpad3 => 3,
+        VirtualKeyCode::Key4 | VirtualKeyCode::Numpad4 => 4,
+        VirtualKeyCode::Key5 | VirtualKeyCode::Numpad5 => 5,
+        VirtualKeyCode::Key6 | VirtualKeyCode::Numpad6 => 6,
+        VirtualKeyCode::Key7 | VirtualKeyCode::Numpad7 => 7,
+        VirtualKeyCode::Key8 | VirtualKeyCode::Numpad8 => 8,
+        VirtualKeyCode::Key9 | VirtualKeyCode::Numpad9 => 9,
+        _ => return None,
+    };
+    std::char::from_digit(digit, 10)
+}
+
+pub fn seed_entry_menu(gs: &mut State, ctx: &mut rltk::BTerm) -> SeedEntryResult {
+    let mut draw_batch = rltk::DrawBatch::new();
+    {
+        let assets = gs.ecs.fetch::<RexAssets>();
+        ctx.render_xp_sprite(&assets.menu, 0, 5);
+    }
+    let keymap = gs.ecs.fetch::<Keymap>();
+
+    draw_batch.draw_double_box(
+        rltk::Rect::with_size(20, 18, 40, 8),
+        rltk::ColorPair::new(rltk::RGB::named(rltk::WHEAT), rltk::RGB::named(rltk::BLACK)),
+    );
+    draw_batch.print_color_centered(
+        20,
+        "Enter a Seed",
+        rltk::ColorPair::new(
+            rltk::RGB::named(rltk::YELLOW),
+            rltk::RGB::named(rltk::BLACK),
+        ),
+    );
+    draw_batch.print_color_centered(
+        22,
+        format!("{}_", gs.seed_input),
+        rltk::ColorPair::new(rltk::RGB::named(rltk::WHITE), rltk::RGB::named(rltk::BLACK)),
+    );
+    draw_batch.print_color_centered(
+        24,
+        format!(
+            "{} to begin, {} to cancel",
+            keymap.label(Action::Select),
+            keymap.label(Action::CloseMenu)
+        ),
+        rltk::ColorPair::new(rltk::RGB::named(rltk::GRAY), rltk::RGB::named(rltk::BLACK)),
+    );
+
+    draw_batch.submit(6000).expect("Failed to submit");
+
+    let input = KeyPress::from_ctx(ctx);
+    if keymap.is(Action::CloseMenu, input) {
+        return SeedEntryResult::Cancel;
+    }
+    if keymap.is(Action::Select, input) && !gs.seed_input.trim().is_empty() {
+        return SeedEntryResult::Selected {
+            seed: crate::rng::parse_seed(&gs.seed_input),
+        };
+    }
+    // Backspace edits the seed rather than acting as a menu key
+    match input.key {
+        None => SeedEntryResult::NoResponse,
+        Some(key) => match key {
+            rltk::VirtualKeyCode::Back => {
+                gs.seed_input.pop();
+                SeedEntryResult::NoResponse
+            }
+            _ => {
+                if let Some(c) = key_to_char(key) {
+                    if gs.seed_input.len() < MAX_SEED_LENGTH {
+                        gs.seed_input.push(c);
+                    }
+                }
+                SeedEntryResult::NoResponse
+            }
+        },
+    }
+}
@@ -1,7 +1,10 @@
 use super::get_item_display_name;
+use crate::crime::reaction_to_player;
+use crate::map::tile_name;
+use crate::raws::Reaction;
 use crate::rltk;
 use crate::rltk::Algorithm2D;
-use crate::{camera, Attributes, Duration, Hidden, Map, Name, Pools, StatusEffect};
+use crate::{camera, Attributes, Duration, Faction, Hidden, Map, Name, Pools, StatusEffect};
 use specs::prelude::*;
 
 struct Tooltip {
@@ -51,116 +54,95 @@ impl Tooltip {
     }
 }
 
-pub fn draw_tooltips(ecs: &World, ctx: &mut rltk::BTerm) {
-    let mut draw_batch = rltk::DrawBatch::new();
-
-    let (min_x, _max_x, min_y, _max_y) = camera::get_screen_bounds(ecs, ctx);
-    let map = ecs.fetch::<Map>();
-    let hidden = ecs.read_storage::<Hidden>();
+/// Describes `entity` as the tooltips show it: its name, a word on its attributes, its level
+/// and whatever status effects it is under.
+fn entity_tooltip(ecs: &World, entity: Entity) -> Tooltip {
     let attributes = ecs.read_storage::<Attributes>();
     let pools = ecs.read_storage::<Pools>();
+    let mut tip = Tooltip::new();
+    tip.add(get_item_display_name(ecs, entity));
 
-    let mouse_pos = ctx.mouse_pos();
-    let mut mouse_map_pos = mouse_pos;
-    mouse_map_pos.0 += min_x - 1;
-    mouse_map_pos.1 += min_y - 1;
-    if mouse_pos.0 < 1 || mouse_pos.0 > 49 || mouse_pos.1 < 1 || mouse_pos.1 > 40 {
-        return;
-    }
-    if mouse_map_pos.0 >= map.width - 1
-        || mouse_map_pos.1 >= map.height - 1
-        || mouse_map_pos.0 < 1
-        || mouse_map_pos.1 < 1
-    {
-        return;
-    }
-    if !map.in_bounds(rltk::Point::new(mouse_map_pos.0, mouse_map_pos.1)) {
-        return;
+    // Comment on attributes
+    let attr = attributes.get(entity);
+    if let Some(attr) = attr {
+        let mut s = "".to_string();
+        if attr.might.bonus < 0 {
+            s += "Weak. "
+        };
+        if attr.might.bonus > 0 {
+            s += "Strong. "
+        };
+        if attr.quickness.bonus < 0 {
+            s += "Clumsy. "
+        };
+        if attr.quickness.bonus > 0 {
+            s += "Agile. "
+        };
+        if attr.fitness.bonus < 0 {
+            s += "Unheathy. "
+        };
+        if attr.fitness.bonus > 0 {
+            s += "Healthy."
+        };
+        if attr.intelligence.bonus < 0 {
+            s += "Unintelligent. "
+        };
+        if attr.intelligence.bonus > 0 {
+            s += "Smart. "
+        };
+        if s.is_empty() {
+            s = "Quite Average".to_string();
+        }
+        tip.add(s);
     }
-    let mouse_idx = map.xy_idx(mouse_map_pos.0, mouse_map_pos.1);
-    if !map.visible_tiles[mouse_idx] {
-        return;
+
+    // Comment on pools
+    let stat = pools.get(entity);
+    if let Some(stat) = stat {
+        tip.add(format!("Level: {}", stat.level));
     }
 
-    let mut tip_boxes: Vec<Tooltip> = Vec::new();
-    crate::spatial::for_each_tile_content(mouse_idx, |entity| {
-        if hidden.get(entity).is_some() {
-            return;
-        }
-        let mut tip = Tooltip::new();
-        tip.add(get_item_display_name(ecs, entity));
-
-        // Comment on attributes
-        let attr = attributes.get(entity);
-        if let Some(attr) = attr {
-            let mut s = "".to_string();
-            if attr.might.bonus < 0 {
-                s += "Weak. "
-            };
-            if attr.might.bonus > 0 {
-                s += "Strong. "
-            };
-            if attr.quickness.bonus < 0 {
-                s += "Clumsy. "
-            };
-            if attr.quickness.bonus > 0 {
-                s += "Agile. "
-            };
-            if attr.fitness.bonus < 0 {
-                s += "Unheathy. "
-            };
-            if attr.fitness.bonus > 0 {
-                s += "Healthy."
-            };
-            if attr.intelligence.bonus < 0 {
-                s += "Unintelligent. "
-            };
-            if attr.intelligence.bonus > 0 {
-                s += "Smart. "
-            };
-            if s.is_empty() {
-                s = "Quite Average".to_string();
-            }
-            tip.add(s);
+    // Status effects
+    let statuses = ecs.read_storage::<StatusEffect>();
+    let durations = ecs.read_storage::<Duration>();
+    let names = ecs.read_storage::<Name>();
+    for (status, duration, name) in (&statuses, &durations, &names).join() {
+        if status.target == entity {
+            tip.add(format!("{} ({})", name.name, duration.turns));
         }
+    }
 
-        // Comment on pools
-        let stat = pools.get(entity);
-        if let Some(stat) = stat {
-            tip.add(format!("Level: {}", stat.level));
-        }
+    tip
+}
 
-        // Status effects
-        let statuses = ecs.read_storage::<StatusEffect>();
-        let durations = ecs.read_storage::<Duration>();
-        let names = ecs.read_storage::<Name>();
-        for (status, duration, name) in (&statuses, &durations, &names).join() {
-            if status.target == entity {
-                tip.add(format!("{} ({})", name.name, duration.turns));
-            }
+/// The entities on a tile that the player can see, in the order tooltips list them.
+fn visible_entities_at(ecs: &World, idx: usize) -> Vec<Entity> {
+    let hidden = ecs.read_storage::<Hidden>();
+    let mut entities = Vec::new();
+    crate::spatial::for_each_tile_content(idx, |entity| {
+        if hidden.get(entity).is_none() {
+            entities.push(entity);
         }
-
-        tip_boxes.push(tip);
     });
+    entities
+}
 
-    if tip_boxes.is_empty() {
-        return;
-    }
-
+/// Draws `tip_boxes` beside the screen cell `pos`, with an arrow pointing at it.
+fn render_tooltips(draw_batch: &mut rltk::DrawBatch, tip_boxes: &[Tooltip], pos: rltk::Point) {
     let box_gray: rltk::RGB = rltk::RGB::from_hex("#999999").expect("Oops");
     let white = rltk::RGB::named(rltk::WHITE);
 
     let arrow;
     let arrow_x;
-    let arrow_y = mouse_pos.1;
-    if mouse_pos.0 < 40 {
+    let arrow_y = pos.y;
+    if pos.x < 40 {
         // Render to the left
         arrow = rltk::to_cp437('→');
-        arrow_x = mouse_pos.0 - 1;
+        arrow_x = pos.x - 1;
     } else {
         // Render to the right
         arrow = rltk::to_cp437('←');
-        arrow_x = mouse_pos.0 + 1;
+        arrow_x = pos.x + 1;
     }
     draw_batch.set(
         rltk::Point::new(arrow_x, arrow_y),
@@ -173,20 +155,112 @@ pub fn draw_tooltips(ecs: &World, ctx: &mut rltk::BTerm) {
         total_height += tt.height();
     }
 
-    let mut y = mouse_pos.1 - (total_height / 2);
+    let mut y = pos.y - (total_height / 2);
     while y + (total_height / 2) > 50 {
         y -= 1;
     }
 
     for tt in tip_boxes.iter() {
-        let x = if mouse_pos.0 < 40 {
-            mouse_pos.0 - (1 + tt.width())
+        let x = if pos.x < 40 {
+            pos.x - (1 + tt.width())
         } else {
-            mouse_pos.0 + (1 + tt.width())
+            pos.x + (1 + tt.width())
         };
-        tt.render(&mut draw_batch, x, y);
+        tt.render(draw_batch, x, y);
         y += tt.height();
     }
+}
+
+pub fn draw_tooltips(ecs: &World, ctx: &mut rltk::BTerm) {
+    let mut draw_batch = rltk::DrawBatch::new();
+
+    let map = ecs.fetch::<Map>();
+
+    let mouse_pos = ctx.mouse_point();
+    let mouse_map_pos = camera::screen_to_map(ecs, ctx, mouse_pos);
+    if mouse_pos.x < 1 || mouse_pos.x > 49 || mouse_pos.y < 1 || mouse_pos.y > 40 {
+        return;
+    }
+    if mouse_map_pos.x >= map.width - 1
+        || mouse_map_pos.y >= map.height - 1
+        || mouse_map_pos.x < 1
+        || mouse_map_pos.y < 1
+    {
+        return;
+    }
+    if !map.in_bounds(mouse_map_pos) {
+        return;
+    }
+    let mouse_idx = map.xy_idx(mouse_map_pos.x, mouse_map_pos.y);
+    if !map.visible_tiles[mouse_idx] {
+        return;
+    }
+
+    let tip_boxes: Vec<Tooltip> = visible_entities_at(ecs, mouse_idx)
+        .into_iter()
+        .map(|entity| entity_tooltip(ecs, entity))
+        .collect();
+    if tip_boxes.is_empty() {
+        return;
+    }
+
+    render_tooltips(&mut draw_batch, &tip_boxes, mouse_pos);
+    draw_batch.submit(7000).expect("Failed to submit");
+}
+
+/// What the player's attitude reads as to someone in `faction`.
+fn attitude(ecs: &World, faction: &str) -> &'static str {
+    match reaction_to_player(ecs, faction) {
+        Reaction::Attack => "Hostile",
+        Reaction::Flee => "Afraid of you",
+        Reaction::Ignore => "Indifferent",
+    }
+}
+
+/// Describes the map tile at `pos` for look mode: the terrain, then the `index`th of the
+/// entities standing there, with its attitude towards the player.
+pub fn draw_look_tooltips(ecs: &World, ctx: &mut rltk::BTerm, pos: rltk::Point, index: usize) {
+    let mut draw_batch = rltk::DrawBatch::new();
+    let map = ecs.fetch::<Map>();
+    let screen_pos = camera::map_to_screen(ecs, ctx, pos);
+    if !map.in_bounds(pos) {
+        return;
+    }
+    let idx = map.xy_idx(pos.x, pos.y);
+
+    let mut tip_boxes = Vec::new();
+    let mut terrain = Tooltip::new();
+    if !map.revealed_tiles[idx] {
+        terrain.add("Unexplored");
+    } else {
+        terrain.add(tile_name(map.tiles[idx]));
+        if !map.visible_tiles[idx] {
+            terrain.add("(remembered)");
+        }
+    }
+    tip_boxes.push(terrain);
+
+    if map.visible_tiles[idx] {
+        let entities = visible_entities_at(ecs, idx);
+        if !entities.is_empty() {
+            let entity = entities[index % entities.len()];
+            let mut tip = entity_tooltip(ecs, entity);
+            if let Some(faction) = ecs.read_storage::<Faction>().get(entity) {
+                if entity != *ecs.fetch::<Entity>() {
+                    tip.add(attitude(ecs, &faction.name));
+                }
+            }
+            if entities.len() > 1 {
+                tip.add(format!(
+                    "{} of {} here",
+                    index % entities.len() + 1,
+                    entities.len()
+                ));
+            }
+            tip_boxes.push(tip);
+        }
+    }
 
+    render_tooltips(&mut draw_batch, &tip_boxes, screen_pos);
     draw_batch.submit(7000).expect("Failed to submit");
 }
@@ -1,7 +1,9 @@
 use super::{get_item_color, get_item_display_name, menu_box};
 use crate::gui::{menu_option, page_list};
+use crate::keymap::{Action, Keymap};
+use crate::player::KeyPress;
 use crate::rltk;
-use crate::{InBackpack, Item, State, Vendor, VendorMode};
+use crate::{InBackpack, Item, Name, State, VendorMode};
 use specs::prelude::*;
 
 const ITEMS_PER_PAGE: usize = 20;
@@ -26,12 +28,13 @@ fn vendor_sell_menu(
     ctx: &mut rltk::BTerm,
     _vendor: Entity,
     page: usize,
-) -> (VendorResult, Option<Entity>, Option<String>, Option<f32>) {
+) -> (VendorResult, Option<Entity>) {
     let mut draw_batch = rltk::DrawBatch::new();
     let player_entity = gs.ecs.fetch::<Entity>();
     let backpack = gs.ecs.read_storage::<InBackpack>();
     let items = gs.ecs.read_storage::<Item>();
     let entities = gs.ecs.entities();
+    let keymap = gs.ecs.fetch::<Keymap>();
 
     let mut inventory: Vec<(Entity, Item)> = Vec::new();
     (&entities, &backpack, &items)
@@ -47,7 +50,11 @@ fn vendor_sell_menu(
         y,
         (count + 3) as i32,
         "Sell Which Item?",
-        vec![("SPC", "Buy Menu"), ("S", "Steal Menu")],
+        &keymap,
+        vec![
+            (Action::VendorSwitchMode, "Buy Menu"),
+            (Action::VendorSteal, "Steal Menu"),
+        ],
     );
 
     let mut equippable: Vec<Entity> = Vec::new();
@@ -69,39 +76,35 @@ fn vendor_sell_menu(
 
     draw_batch.submit(6000).expect("Failed to submit");
 
-    match ctx.key {
-        None => (VendorResult::NoResponse, None, None, None),
-        Some(key) => match key {
-            rltk::VirtualKeyCode::Space => (VendorResult::BuyMode, None, None, None),
-            rltk::VirtualKeyCode::Escape => (VendorResult::Cancel, None, None, None),
-            rltk::VirtualKeyCode::S => (VendorResult::StealMode, None, None, None),
-            rltk::VirtualKeyCode::Comma => {
-                if page > 0 && inventory.len() > ITEMS_PER_PAGE {
-                    (VendorResult::PreviousPage, None, None, None)
-                } else {
-                    (VendorResult::NoResponse, None, None, None)
-                }
-            }
-            rltk::VirtualKeyCode::Period => {
-                if count == ITEMS_PER_PAGE && inventory.len() > ITEMS_PER_PAGE {
-                    (VendorResult::NextPage, None, None, None)
-                } else {
-                    (VendorResult::NoResponse, None, None, None)
-                }
-            }
-            _ => {
-                let selection = rltk::letter_to_option(key);
-                if selection > -1 && selection < count as i32 {
-                    return (
-                        VendorResult::Sell,
-                        Some(equippable[selection as usize]),
-                        None,
-                        None,
-                    );
-                }
-                (VendorResult::NoResponse, None, None, None)
-            }
-        },
+    let input = KeyPress::from_ctx(ctx);
+    let key = match input.key {
+        None => return (VendorResult::NoResponse, None),
+        Some(key) => key,
+    };
+    if keymap.is(Action::VendorSwitchMode, input) {
+        (VendorResult::BuyMode, None)
+    } else if keymap.is(Action::CloseMenu, input) {
+        (VendorResult::Cancel, None)
+    } else if keymap.is(Action::VendorSteal, input) {
+        (VendorResult::StealMode, None)
+    } else if keymap.is(Action::PreviousPage, input) {
+        if page > 0 && inventory.len() > ITEMS_PER_PAGE {
+            (VendorResult::PreviousPage, None)
+        } else {
+            (VendorResult::NoResponse, None)
+        }
+    } else if keymap.is(Action::NextPage, input) {
+        if count == ITEMS_PER_PAGE && inventory.len() > ITEMS_PER_PAGE {
+            (VendorResult::NextPage, None)
+        } else {
+            (VendorResult::NoResponse, None)
+        }
+    } else {
+        let selection = rltk::letter_to_option(key);
+        if selection > -1 && selection < count as i32 {
+            return (VendorResult::Sell, Some(equippable[selection as usize]));
+        }
+        (VendorResult::NoResponse, None)
     }
 }
 
@@ -110,7 +113,7 @@ fn vendor_buy_menu(
     ctx: &mut rltk::BTerm,
     vendor: Entity,
     page: usize,
-) -> (VendorResult, Option<Entity>, Option<String>, Option<f32>) {
+) -> (VendorResult, Option<Entity>) {
     vendor_inventory_menu(
         gs,
         ctx,
@@ -118,7 +121,10 @@ fn vendor_buy_menu(
         page,
         "Buy Which Item?",
         rltk::RGB::named(rltk::WHITE),
-        vec![("SPC", "Sell Menu"), ("S", "Steal Menu")],
+        vec![
+            (Action::VendorSwitchMode, "Sell Menu"),
+            (Action::VendorSteal, "Steal Menu"),
+        ],
         VendorResult::Buy,
     )
 }
@@ -128,7 +134,7 @@ fn vendor_steal_menu(
     ctx: &mut rltk::BTerm,
     vendor: Entity,
     page: usize,
-) -> (VendorResult, Option<Entity>, Option<String>, Option<f32>) {
+) -> (VendorResult, Option<Entity>) {
     vendor_inventory_menu(
         gs,
         ctx,
@@ -136,7 +142,7 @@ fn vendor_steal_menu(
         page,
         "Steal Which Item?",
         rltk::RGB::named(rltk::RED),
-        vec![("SPC", "Sell Menu")],
+        vec![(Action::VendorSwitchMode, "Sell Menu")],
         VendorResult::Steal,
     )
 }
@@ -148,75 +154,86 @@ fn vendor_inventory_menu(
     page: usize,
     title: &str,
     menu_option_color: rltk::RGB,
-    help_options: Vec<(&str, &str)>,
+    help_options: Vec<(Action, &str)>,
     selection_result: VendorResult,
-) -> (VendorResult, Option<Entity>, Option<String>, Option<f32>) {
-    use crate::raws::*;
+) -> (VendorResult, Option<Entity>) {
     let mut draw_batch = rltk::DrawBatch::new();
+    let backpack = gs.ecs.read_storage::<InBackpack>();
+    let items = gs.ecs.read_storage::<Item>();
+    let names = gs.ecs.read_storage::<Name>();
+    let entities = gs.ecs.entities();
+    let keymap = gs.ecs.fetch::<Keymap>();
 
-    let vendors = gs.ecs.read_storage::<Vendor>();
-
-    let inventory = crate::raws::get_vendor_items(
-        &vendors.get(vendor).unwrap().categories,
-        &RAWS.lock().unwrap(),
-    );
+    // Vendors know what they sell, so their stock goes by its real names
+    let mut inventory: Vec<(Entity, String, f32)> = (&entities, &backpack, &items, &names)
+        .join()
+        .filter(|(_, carried, _, _)| carried.owner == vendor)
+        .map(|(entity, _, item, name)| (entity, name.name.clone(), item.base_value))
+        .collect();
+    inventory.sort_by(|a, b| a.1.cmp(&b.1));
     let paged_inventory = page_list(&inventory, page);
     let count = paged_inventory.len();
 
     let mut y = (25 - (count / 2)) as i32;
-    menu_box(&mut draw_batch, y, (count + 3) as i32, title, help_options);
+    menu_box(
+        &mut draw_batch,
+        y,
+        (count + 3) as i32,
+        title,
+        &keymap,
+        help_options,
+    );
 
     for (j, sale) in paged_inventory.iter().enumerate() {
         menu_option(
             &mut draw_batch,
             y,
             97 + j as rltk::FontCharType,
-            &sale.0,
+            &sale.1,
             menu_option_color,
         );
 
         draw_batch.print(
             rltk::Point::new(PRICE_X, y),
-            &format!("{:.1} gp", sale.1 * 1.2),
+            format!("{:.1} gp", sale.2 * 1.2),
         );
         y += 1;
     }
 
     draw_batch.submit(6000).expect("Failed to submit");
 
-    match ctx.key {
-        None => (VendorResult::NoResponse, None, None, None),
-        Some(key) => match key {
-            rltk::VirtualKeyCode::Space => (VendorResult::SellMode, None, None, None),
-            rltk::VirtualKeyCode::Escape => (VendorResult::Cancel, None, None, None),
-            rltk::VirtualKeyCode::S => (VendorResult::StealMode, None, None, None),
-            rltk::VirtualKeyCode::Comma => {
-                if page > 0 && inventory.len() > paged_inventory.len() {
-                    (VendorResult::PreviousPage, None, None, None)
-                } else {
-                    (VendorResult::NoResponse, None, None, None)
-                }
-            }
-            rltk::VirtualKeyCode::Period => {
-                if paged_inventory.len() == ITEMS_PER_PAGE && inventory.len() > ITEMS_PER_PAGE {
-                    (VendorResult::NextPage, None, None, None)
-                } else {
-                    (VendorResult::NoResponse, None, None, None)
-                }
-            }
-            _ => {
-                let selection = rltk::letter_to_option(key);
-                if selection > -1 && selection < count as i32 {
-                    return (
-                        selection_result,
-                        None,
-                        Some(paged_inventory[selection as usize].0.clone()),
-                        Some(paged_inventory[selection as usize].1),
-                    );
-                }
-                (VendorResult::NoResponse, None, None, None)
-            }
-        },
+    let input = KeyPress::from_ctx(ctx);
+    let key = match input.key {
+        None => return (VendorResult::NoResponse, None),
+        Some(key) => key,
+    };
+    if keymap.is(Action::VendorSwitchMode, input) {
+        (VendorResult::SellMode, None)
+    } else if keymap.is(Action::CloseMenu, input) {
+        (VendorResult::Cancel, None)
+    } else if keymap.is(Action::VendorSteal, input) {
+        (VendorResult::StealMode, None)
+    } else if keymap.is(Action::PreviousPage, input) {
+        if page > 0 && inventory.len() > paged_inventory.len() {
+            (VendorResult::PreviousPage, None)
+        } else {
+            (VendorResult::NoResponse, None)
+        }
+    } else if keymap.is(Action::NextPage, input) {
+        if paged_inventory.len() == ITEMS_PER_PAGE && inventory.len() > ITEMS_PER_PAGE {
+            (VendorResult::NextPage, None)
+        } else {
+            (VendorResult::NoResponse, None)
+        }
+    } else {
+        let selection = rltk::letter_to_option(key);
+        if selection > -1 && selection < count as i32 {
+            return (
+                selection_result,
+                Some(paged_inventory[selection as usize].0),
+            );
+        }
+        (VendorResult::NoResponse, None)
     }
 }
 
@@ -226,7 +243,7 @@ pub fn show_vendor_menu(
     vendor: Entity,
     mode: VendorMode,
     page: usize,
-) -> (VendorResult, Option<Entity>, Option<String>, Option<f32>) {
+) -> (VendorResult, Option<Entity>) {
     match mode {
         VendorMode::Buy => vendor_buy_menu(gs, ctx, vendor, page),
         VendorMode::Sell => vendor_sell_menu(gs, ctx, vendor, page),
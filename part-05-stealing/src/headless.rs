@@ -0,0 +1,188 @@
+use super::{gamelog, systems, Map, Pools, RunState, State};
+use crate::keymap::parse_key_press;
+use crate::player::KeyPress;
+use specs::prelude::*;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard};
+
+/// How many log lines the report ends with.
+const REPORT_LOG_LINES: usize = 10;
+
+lazy_static! {
+    static ref PLAYING: Mutex<()> = Mutex::new(());
+}
+
+/// Plays the game without a window or an audio device, one key press at a time.
+///
+/// The log, event counts, random streams, effect queue and spatial index are globals, so only
+/// one game plays at a time in a process: `new` waits until any other `HeadlessGame` has been
+/// dropped, then starts those over. Tests that each build one can run in parallel, but a test
+/// must drop one game before building the next.
+pub struct HeadlessGame {
+    pub state: State,
+    seed: u64,
+    _playing: MutexGuard<'static, ()>,
+}
+
+impl HeadlessGame {
+    /// Builds a new run from `seed` and plays it up to the player's first turn.
+    pub fn new(seed: u64) -> HeadlessGame {
+        // A test that panicked mid-game leaves nothing behind that `start_run` doesn't reset
+        let playing = PLAYING.lock().unwrap_or_else(|e| e.into_inner());
+        let mut state = State::new();
+        state.headless = true;
+        state
+            .ecs
+            .insert(systems::sound_system::SoundSystem::recording());
+        state.mapgen_next_state = Some(RunState::PreRun);
+        state.start_run(seed);
+
+        let mut game = HeadlessGame {
+            state,
+            seed,
+            _playing: playing,
+        };
+        game.run_until_input();
+        game
+    }
+
+    pub fn runstate(&self) -> RunState {
+        *self.state.ecs.fetch::<RunState>()
+    }
+
+    pub fn is_player_dead(&self) -> bool {
+        self.runstate() == RunState::GameOver
+    }
+
+    /// How many turns the player has taken this run.
+    pub fn turns(&self) -> i32 {
+        gamelog::get_event_count("Turn")
+    }
+
+    /// Plays each key press in turn, stopping early if the player dies.
+    pub fn play(&mut self, script: &[KeyPress]) {
+        for press in script {
+            if self.is_player_dead() {
+                break;
+            }
+            self.press(*press);
+        }
+    }
+
+    /// Plays one key press, and everything it sets in motion, through to the next turn.
+    pub fn press(&mut self, input: KeyPress) {
+        if self.runstate() != RunState::AwaitingInput {
+            return;
+        }
+        let newrunstate = self.state.player_turn(input);
+        self.state.commit_runstate(newrunstate);
+        self.run_until_input();
+    }
+
+    /// Steps the game until it waits on the player again, or the player dies.
+    pub fn run_until_input(&mut self) {
+        loop {
+            let runstate = self.runstate();
+            let newrunstate = match runstate {
+                RunState::AwaitingInput | RunState::GameOver => return,
+                RunState::MapGeneration => self.state.mapgen_next_state.unwrap(),
+                RunState::Travelling => self.state.travel_turn(),
+                RunState::PreRun
+                | RunState::Ticking
+                | RunState::NextLevel
+                | RunState::PreviousLevel
+                | RunState::TownPortal
+                | RunState::TeleportingToOtherLevel { .. }
+                | RunState::MagicMapReveal { .. } => self.state.advance(runstate),
+                _ => {
+                    // Menus and targeting need a screen to answer them
+                    gamelog::Logger::new()
+                        .category(gamelog::LogCategory::System)
+                        .append("A headless run can't use menus; the menu was closed.")
+                        .log();
+                    RunState::AwaitingInput
+                }
+            };
+
+            // Nothing waits on particles without a screen to draw them on
+            systems::particle_system::update_particles(&mut self.state.ecs, f32::MAX);
+            self.state.commit_runstate(newrunstate);
+        }
+    }
+
+    /// The latest sounds the run made, oldest first.
+    pub fn sounds_played(&self) -> Vec<String> {
+        self.state
+            .ecs
+            .fetch::<systems::sound_system::SoundSystem>()
+            .played_sound_effects()
+    }
+
+    /// Describes where the run has got to, ending with the most recent log lines.
+    pub fn report(&self) -> String {
+        let ecs = &self.state.ecs;
+        let player_entity = *ecs.fetch::<Entity>();
+        let map = ecs.fetch::<Map>();
+        let pools = ecs.read_storage::<Pools>();
+
+        let mut report = format!("Seed: {}\n", self.seed);
+        report += &format!("Turns: {}\n", self.turns());
+        report += &format!("Depth: {} ({})\n", map.depth, map.name);
+        if let Some(pools) = pools.get(player_entity) {
+            report += &format!(
+                "Hit points: {}/{}\n",
+                pools.hit_points.current, pools.hit_points.max
+            );
+        }
+        report += &format!(
+            "Player: {}\n",
+            if self.is_player_dead() {
+                "dead"
+            } else {
+                "alive"
+            }
+        );
+
+        report += &format!("Sounds played: {}\n", self.sounds_played().join(", "));
+        let sound_system = ecs.fetch::<systems::sound_system::SoundSystem>();
+        if let Some(music) = sound_system.current_music() {
+            report += &format!("Music: {}\n", music);
+        }
+
+        report += "Log:\n";
+        let log = gamelog::clone_log();
+        for entry in log.iter().skip(log.len().saturating_sub(REPORT_LOG_LINES)) {
+            report += &format!("  {}\n", entry.text());
+        }
+        report
+    }
+}
+
+/// Reads a script of key presses: one key name per line, optionally prefixed with `shift+`
+/// and/or `ctrl+`. Blank lines and anything after a `#` are ignored.
+pub fn parse_script(text: &str) -> Result<Vec<KeyPress>, String> {
+    let mut presses = Vec::new();
+    for (number, line) in text.lines().enumerate() {
+        let name = line.split('#').next().unwrap_or("").trim();
+        if name.is_empty() {
+            continue;
+        }
+
+        let press = parse_key_press(name).map_err(|e| format!("line {}: {}", number + 1, e))?;
+        presses.push(press);
+    }
+    Ok(presses)
+}
+
+/// Plays the script at `path` against a new run, then prints a report to stdout.
+pub fn run(path: &Path, seed: u64) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let script = parse_script(&fs::read_to_string(path)?)
+        .map_err(|e| format!("{}: {}", path.display(), e))?;
+
+    let mut game = HeadlessGame::new(seed);
+    game.play(&script);
+    print!("{}", game.report());
+    Ok(())
+}
@@ -0,0 +1,352 @@
+use crate::key_names::{key_from_name, key_name};
+use crate::player::KeyPress;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+
+/// Where an action applies. Actions in different contexts can share a key, since only one
+/// context reads the keyboard at a time.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum KeyContext {
+    Game,
+    Menu,
+}
+
+/// Lists every rebindable action with its description, context and default keys.
+macro_rules! actions {
+    ($($action:ident, $context:ident, $description:expr, [$($key:expr),*];)*) => {
+        #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Serialize, Deserialize)]
+        pub enum Action {
+            $($action,)*
+        }
+
+        impl Action {
+            pub const ALL: &'static [Action] = &[$(Action::$action,)*];
+
+            pub fn description(self) -> &'static str {
+                match self {
+                    $(Action::$action => $description,)*
+                }
+            }
+
+            pub fn context(self) -> KeyContext {
+                match self {
+                    $(Action::$action => KeyContext::$context,)*
+                }
+            }
+
+            fn default_keys(self) -> &'static [&'static str] {
+                match self {
+                    $(Action::$action => &[$($key),*],)*
+                }
+            }
+        }
+    };
+}
+
+actions!(
+    MoveWest, Game, "Move west", ["Left", "Numpad4", "H"];
+    MoveEast, Game, "Move east", ["Right", "Numpad6", "L"];
+    MoveNorth, Game, "Move north", ["Up", "Numpad8", "K"];
+    MoveSouth, Game, "Move south", ["Down", "Numpad2", "J"];
+    MoveNorthEast, Game, "Move north-east", ["Numpad9", "U"];
+    MoveNorthWest, Game, "Move north-west", ["Numpad7", "Y"];
+    MoveSouthEast, Game, "Move south-east", ["Numpad3", "N"];
+    MoveSouthWest, Game, "Move south-west", ["Numpad1", "B"];
+    Wait, Game, "Wait a turn", ["Numpad5", "Space"];
+    Descend, Game, "Go down stairs", ["Period"];
+    Ascend, Game, "Go up stairs", ["Comma"];
+    PickUp, Game, "Pick up", ["G"];
+    Inventory, Game, "Inventory", ["I"];
+    Drop, Game, "Drop item", ["D"];
+    RemoveItem, Game, "Remove item", ["R"];
+    GameMenu, Game, "Game menu", ["Escape"];
+    CheatMenu, Game, "Cheat menu", ["Backslash"];
+    CycleTarget, Game, "Cycle target", ["V"];
+    Fire, Game, "Fire at target", ["F"];
+    MessageLog, Game, "Message history", ["M"];
+    Look, Game, "Look around", ["X"];
+    AutoExplore, Game, "Explore", ["O"];
+    TravelDownStairs, Game, "Travel to stairs down", ["shift+Period"];
+    TravelUpStairs, Game, "Travel to stairs up", ["shift+Comma"];
+    LookCycle, Game, "Next thing here", ["Tab"];
+    UseItem1, Game, "Use item 1", ["shift+Key1"];
+    UseItem2, Game, "Use item 2", ["shift+Key2"];
+    UseItem3, Game, "Use item 3", ["shift+Key3"];
+    UseItem4, Game, "Use item 4", ["shift+Key4"];
+    UseItem5, Game, "Use item 5", ["shift+Key5"];
+    UseItem6, Game, "Use item 6", ["shift+Key6"];
+    UseItem7, Game, "Use item 7", ["shift+Key7"];
+    UseItem8, Game, "Use item 8", ["shift+Key8"];
+    UseItem9, Game, "Use item 9", ["shift+Key9"];
+    CastSpell1, Game, "Cast spell 1", ["ctrl+Key1"];
+    CastSpell2, Game, "Cast spell 2", ["ctrl+Key2"];
+    CastSpell3, Game, "Cast spell 3", ["ctrl+Key3"];
+    CastSpell4, Game, "Cast spell 4", ["ctrl+Key4"];
+    CastSpell5, Game, "Cast spell 5", ["ctrl+Key5"];
+    CastSpell6, Game, "Cast spell 6", ["ctrl+Key6"];
+    CastSpell7, Game, "Cast spell 7", ["ctrl+Key7"];
+    CastSpell8, Game, "Cast spell 8", ["ctrl+Key8"];
+    CastSpell9, Game, "Cast spell 9", ["ctrl+Key9"];
+    CloseMenu, Menu, "Close menu", ["Escape"];
+    Select, Menu, "Select", ["Return"];
+    DeleteEntry, Menu, "Delete", ["Delete", "Back"];
+    DecreaseValue, Menu, "Decrease value", ["Left"];
+    IncreaseValue, Menu, "Increase value", ["Right"];
+    PreviousPage, Menu, "Previous page", ["Comma"];
+    NextPage, Menu, "Next page", ["Period"];
+    VendorSwitchMode, Menu, "Switch buy/sell", ["Space"];
+    VendorSteal, Menu, "Steal", ["S"];
+    ScrollUp, Menu, "Previous / scroll up", ["Up", "Numpad8"];
+    ScrollDown, Menu, "Next / scroll down", ["Down", "Numpad2"];
+    LogFilter, Menu, "Filter messages", ["Tab"];
+    LogSearch, Menu, "Search messages", ["Slash"];
+);
+
+impl Action {
+    /// Actions the menus can't be opened, navigated or closed without, so they always keep a key.
+    const ESSENTIAL: &'static [Action] = &[
+        Action::GameMenu,
+        Action::CloseMenu,
+        Action::Select,
+        Action::ScrollUp,
+        Action::ScrollDown,
+    ];
+
+    /// The step a movement action takes, for anything that moves by direction.
+    pub fn direction(self) -> Option<(i32, i32)> {
+        match self {
+            Action::MoveWest => Some((-1, 0)),
+            Action::MoveEast => Some((1, 0)),
+            Action::MoveNorth => Some((0, -1)),
+            Action::MoveSouth => Some((0, 1)),
+            Action::MoveNorthEast => Some((1, -1)),
+            Action::MoveNorthWest => Some((-1, -1)),
+            Action::MoveSouthEast => Some((1, 1)),
+            Action::MoveSouthWest => Some((-1, 1)),
+            _ => None,
+        }
+    }
+}
+
+/// Reads a key written the way the config file and scripts write them: a `VirtualKeyCode` name,
+/// optionally prefixed with `shift+` and/or `ctrl+`.
+pub fn parse_key_press(text: &str) -> Result<KeyPress, String> {
+    let mut name = text.trim();
+    let mut press = KeyPress::default();
+    loop {
+        if let Some(rest) = name.strip_prefix("shift+") {
+            press.shift = true;
+            name = rest;
+        } else if let Some(rest) = name.strip_prefix("ctrl+") {
+            press.control = true;
+            name = rest;
+        } else {
+            break;
+        }
+    }
+    press.key = Some(key_from_name(name).ok_or_else(|| format!("unknown key \"{}\"", name))?);
+    Ok(press)
+}
+
+/// The reverse of `parse_key_press`, or `None` for keys that have no name.
+pub fn key_press_name(press: KeyPress) -> Option<String> {
+    let name = key_name(press.key?)?;
+    let mut text = String::new();
+    if press.shift {
+        text.push_str("shift+");
+    }
+    if press.control {
+        text.push_str("ctrl+");
+    }
+    text.push_str(name);
+    Some(text)
+}
+
+/// Which keys trigger which actions. Stored in the options file as lists of key names; actions
+/// the file doesn't mention keep their default keys.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(
+    try_from = "BTreeMap<Action, Vec<String>>",
+    into = "BTreeMap<Action, Vec<String>>"
+)]
+pub struct Keymap {
+    bindings: BTreeMap<Action, Vec<KeyPress>>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let mut keymap = Keymap {
+            bindings: BTreeMap::new(),
+        };
+        for action in Action::ALL {
+            keymap.reset(*action);
+        }
+        keymap
+    }
+}
+
+impl TryFrom<BTreeMap<Action, Vec<String>>> for Keymap {
+    type Error = String;
+
+    fn try_from(names: BTreeMap<Action, Vec<String>>) -> Result<Self, Self::Error> {
+        let mut keymap = Keymap::default();
+        for (action, keys) in names {
+            let presses = keys
+                .iter()
+                .map(|key| parse_key_press(key))
+                .collect::<Result<Vec<KeyPress>, String>>()
+                .map_err(|e| format!("{:?}: {}", action, e))?;
+            keymap.bindings.insert(action, presses);
+        }
+        Ok(keymap)
+    }
+}
+
+impl From<Keymap> for BTreeMap<Action, Vec<String>> {
+    fn from(keymap: Keymap) -> Self {
+        keymap
+            .bindings
+            .into_iter()
+            .map(|(action, presses)| {
+                let names = presses.into_iter().filter_map(key_press_name).collect();
+                (action, names)
+            })
+            .collect()
+    }
+}
+
+impl Keymap {
+    /// The action `input` triggers in `context`, if any. Modifiers have to match exactly, so
+    /// shift+1 doesn't also count as 1.
+    pub fn action(&self, context: KeyContext, input: KeyPress) -> Option<Action> {
+        input.key?;
+        self.bindings
+            .iter()
+            .filter(|(action, _)| action.context() == context)
+            .find(|(_, presses)| presses.contains(&input))
+            .map(|(action, _)| *action)
+    }
+
+    /// The direction shift and a movement key runs in. Keys bound to something else with shift
+    /// held do that instead.
+    pub fn run_direction(&self, input: KeyPress) -> Option<(i32, i32)> {
+        if !input.shift {
+            return None;
+        }
+        let unshifted = KeyPress {
+            shift: false,
+            ..input
+        };
+        self.action(KeyContext::Game, unshifted)?.direction()
+    }
+
+    /// Whether `input` triggers `action`.
+    pub fn is(&self, action: Action, input: KeyPress) -> bool {
+        input.key.is_some() && self.keys(action).contains(&input)
+    }
+
+    pub fn keys(&self, action: Action) -> &[KeyPress] {
+        self.bindings.get(&action).map_or(&[][..], Vec::as_slice)
+    }
+
+    /// The action in the same context that already uses `input`, other than `action` itself.
+    pub fn conflict(&self, action: Action, input: KeyPress) -> Option<Action> {
+        self.bindings
+            .iter()
+            .filter(|(other, _)| **other != action && other.context() == action.context())
+            .find(|(_, presses)| presses.contains(&input))
+            .map(|(other, _)| *other)
+    }
+
+    /// Every action sharing a key with another action in its context, such as after a hand
+    /// edit of the options file.
+    pub fn conflicts(&self) -> Vec<Action> {
+        self.bindings
+            .iter()
+            .filter(|(action, presses)| {
+                presses
+                    .iter()
+                    .any(|press| self.conflict(**action, *press).is_some())
+            })
+            .map(|(action, _)| *action)
+            .collect()
+    }
+
+    /// Adds `input` to the keys for `action`, unless another action in the same context already
+    /// uses it; then that action is returned and nothing changes.
+    pub fn bind(&mut self, action: Action, input: KeyPress) -> Result<(), Action> {
+        if let Some(other) = self.conflict(action, input) {
+            return Err(other);
+        }
+        let presses = self.bindings.entry(action).or_default();
+        if !presses.contains(&input) {
+            presses.push(input);
+        }
+        Ok(())
+    }
+
+    /// Removes the keys for `action`. Essential actions keep their newest key, and clearing
+    /// one that has only a single key is refused; returns whether anything was removed.
+    pub fn clear(&mut self, action: Action) -> bool {
+        let presses = self.bindings.entry(action).or_default();
+        if !Action::ESSENTIAL.contains(&action) {
+            presses.clear();
+            return true;
+        }
+        if presses.len() < 2 {
+            return false;
+        }
+        presses.drain(..presses.len() - 1);
+        true
+    }
+
+    /// Gives essential actions left without keys, such as by a hand edit of the options file,
+    /// their default keys back.
+    pub fn restore_essential(&mut self) {
+        for action in Action::ESSENTIAL {
+            if self.keys(*action).is_empty() {
+                self.reset(*action);
+            }
+        }
+    }
+
+    pub fn reset(&mut self, action: Action) {
+        let presses = action
+            .default_keys()
+            .iter()
+            .map(|key| parse_key_press(key).expect("Bad default key"))
+            .collect();
+        self.bindings.insert(action, presses);
+    }
+
+    /// The keys for `action` written out for the player, such as "Left, Numpad4, H".
+    pub fn describe(&self, action: Action) -> String {
+        let names: Vec<String> = self
+            .keys(action)
+            .iter()
+            .filter_map(|press| key_press_name(*press))
+            .collect();
+        if names.is_empty() {
+            "(unbound)".to_string()
+        } else {
+            names.join(", ")
+        }
+    }
+
+    /// A short name for the first key bound to `action`, for the help lines under menus.
+    pub fn label(&self, action: Action) -> String {
+        let name = match self.keys(action).first() {
+            Some(press) => key_press_name(*press).unwrap_or_default(),
+            None => return "-".to_string(),
+        };
+        match name.as_str() {
+            "Escape" => "ESC".to_string(),
+            "Space" => "SPC".to_string(),
+            "Return" => "Enter".to_string(),
+            "Comma" => ",".to_string(),
+            "Period" => ".".to_string(),
+            _ => name,
+        }
+    }
+}
@@ -0,0 +1,1683 @@
+extern crate bracket_lib;
+extern crate serde;
+
+pub use bracket_lib::prelude as rltk;
+
+use rltk::{BTerm, GameState, Point};
+use specs::prelude::*;
+use specs::saveload::{SimpleMarker, SimpleMarkerAllocator};
+
+mod components;
+pub use components::*;
+mod map;
+pub use map::*;
+mod player;
+pub use player::KeyPress;
+use player::*;
+mod rect;
+pub use rect::Rect;
+mod crime;
+mod damage_system;
+mod gamelog;
+mod gamesystem;
+mod gui;
+pub mod map_builders;
+pub mod random_table;
+pub mod raws;
+pub mod rex_assets;
+pub mod saveload_system;
+mod spawner;
+pub use gamesystem::*;
+
+use crate::systems::sound_system::{event_sounds, MusicDirector, SoundSystem};
+
+pub mod effects;
+#[macro_use]
+extern crate lazy_static;
+pub mod headless;
+pub use headless::HeadlessGame;
+mod key_names;
+mod keymap;
+pub use keymap::parse_key_press;
+mod options;
+mod replay;
+pub mod rng;
+pub mod spatial;
+mod systems;
+mod travel;
+
+macro_rules! register {
+    (
+        $ecs:expr,
+        $(
+            $type:ty
+        ),*
+    ) => {
+        $(
+            $ecs.register::<$type>();
+        )*
+    };
+}
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum VendorMode {
+    Buy,
+    Sell,
+    Steal,
+}
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum RunState {
+    AwaitingInput,
+    PreRun,
+    Ticking,
+    ShowInventory {
+        page: usize,
+    },
+    ShowDropItem {
+        page: usize,
+    },
+    ShowTargeting {
+        range: i32,
+        item: Entity,
+    },
+    MainMenu {
+        menu_selection: gui::MainMenuSelection,
+    },
+    NextLevel,
+    PreviousLevel,
+    TownPortal,
+    ShowRemoveItem {
+        page: usize,
+    },
+    GameOver,
+    MagicMapReveal {
+        row: i32,
+    },
+    MapGeneration,
+    ShowCheatMenu,
+    ShowVendor {
+        vendor: Entity,
+        mode: VendorMode,
+        page: usize,
+    },
+    ShowFine {
+        collector: Entity,
+    },
+    TeleportingToOtherLevel {
+        x: i32,
+        y: i32,
+        depth: i32,
+    },
+    ShowRemoveCurse {
+        page: usize,
+    },
+    ShowIdentify {
+        page: usize,
+    },
+    OptionsMenu {
+        menu_selection: gui::OptionsMenuSelection,
+    },
+    SaveSlots {
+        mode: gui::SaveSlotMode,
+        selection: usize,
+    },
+    SaveError {
+        mode: gui::SaveSlotMode,
+    },
+    SeedEntry,
+    KeyBindings {
+        selection: usize,
+        capturing: bool,
+    },
+    Looking {
+        x: i32,
+        y: i32,
+        index: usize,
+    },
+    MessageLog {
+        scroll: usize,
+        category: Option<gamelog::LogCategory>,
+        searching: bool,
+    },
+    Travelling,
+}
+
+pub struct State {
+    pub ecs: World,
+    mapgen_next_state: Option<RunState>,
+    mapgen_history: Vec<Map>,
+    mapgen_index: usize,
+    mapgen_timer: f32,
+    dispatcher: Box<dyn systems::UnifiedDispatcher + 'static>,
+    game_options: options::GameOptions,
+    save_error: Option<saveload_system::SaveError>,
+    save_listing: saveload_system::SaveListing,
+    last_autosave_turn: i32,
+    seed_input: String,
+    key_bindings_message: Option<String>,
+    log_search: String,
+    travel: Option<travel::Travel>,
+    right_click: bool,
+    right_button_down: bool,
+    recorder: Option<replay::Recorder>,
+    playback: Option<replay::Playback>,
+    record_next_run: bool,
+    /// Whether the player has started playing the current world, rather than it only being
+    /// the backdrop to the main menu.
+    run_in_progress: bool,
+    headless: bool,
+    music: MusicDirector,
+}
+
+impl State {
+    fn new() -> State {
+        State {
+            ecs: World::new(),
+            mapgen_next_state: Some(RunState::MainMenu {
+                menu_selection: gui::MainMenuSelection::NewGame,
+            }),
+            mapgen_index: 0,
+            mapgen_history: Vec::new(),
+            mapgen_timer: 0.0,
+            dispatcher: systems::build(),
+            game_options: options::GameOptions::default(),
+            save_error: None,
+            save_listing: saveload_system::SaveListing::default(),
+            last_autosave_turn: 0,
+            seed_input: String::new(),
+            key_bindings_message: None,
+            log_search: String::new(),
+            travel: None,
+            right_click: false,
+            right_button_down: false,
+            recorder: None,
+            playback: None,
+            record_next_run: false,
+            run_in_progress: false,
+            headless: false,
+            music: MusicDirector::new(),
+        }
+    }
+
+    fn run_systems(&mut self) {
+        self.dispatcher.run_now(&mut self.ecs);
+        self.ecs.maintain();
+    }
+}
+
+impl GameState for State {
+    #[allow(clippy::cognitive_complexity)]
+    fn tick(&mut self, ctx: &mut BTerm) {
+        let mut newrunstate;
+        {
+            let runstate = self.ecs.fetch::<RunState>();
+            newrunstate = *runstate;
+        }
+
+        // BTerm only reports left clicks, so right clicks are picked out of the held buttons
+        let right_button_down = rltk::INPUT.lock().is_mouse_button_pressed(1);
+        self.right_click = right_button_down && !self.right_button_down;
+        self.right_button_down = right_button_down;
+
+        // Replays feed recorded input in place of the keyboard and mouse
+        if self
+            .playback
+            .as_ref()
+            .is_some_and(|playback| playback.is_finished())
+        {
+            self.finish_replay();
+        }
+        if let Some(playback) = &mut self.playback {
+            playback.apply_frame(ctx);
+            self.right_click = playback.right_click();
+        } else if let Some(recorder) = &mut self.recorder {
+            let timed = newrunstate == RunState::MapGeneration
+                || (&self.ecs.read_storage::<ParticleLifetime>())
+                    .join()
+                    .next()
+                    .is_some();
+            recorder.record_frame(ctx, self.right_click, timed);
+        }
+
+        ctx.set_active_console(1);
+        ctx.cls();
+        ctx.set_active_console(0);
+        ctx.cls();
+        systems::particle_system::update_particles(&mut self.ecs, ctx.frame_time_ms);
+        self.ecs.fetch::<SoundSystem>().update(ctx.frame_time_ms);
+
+        match newrunstate {
+            RunState::MainMenu { .. } => {}
+            RunState::GameOver { .. } => {}
+            RunState::OptionsMenu { .. } => {}
+            RunState::SaveSlots { .. } => {}
+            RunState::SaveError { .. } => {}
+            RunState::SeedEntry => {}
+            RunState::KeyBindings { .. } => {}
+            RunState::MessageLog { .. } => {}
+            _ => {
+                camera::render_camera(&self.ecs, ctx);
+                gui::draw_ui(&self.ecs, ctx);
+            }
+        }
+
+        match newrunstate {
+            RunState::MapGeneration => {
+                if !self.game_options.show_map_visualizer {
+                    newrunstate = self.mapgen_next_state.unwrap();
+                } else {
+                    ctx.cls();
+                    if self.mapgen_index < self.mapgen_history.len()
+                        && self.mapgen_index < self.mapgen_history.len()
+                    {
+                        camera::render_debug_map(&self.mapgen_history[self.mapgen_index], ctx);
+                    }
+
+                    self.mapgen_timer += ctx.frame_time_ms;
+                    if self.mapgen_timer > 250.0 {
+                        self.mapgen_timer = 0.0;
+                        self.mapgen_index += 1;
+                        if self.mapgen_index >= self.mapgen_history.len() {
+                            //self.mapgen_index -= 1;
+                            newrunstate = self.mapgen_next_state.unwrap();
+                        }
+                    }
+                }
+            }
+            RunState::AwaitingInput => {
+                newrunstate = if ctx.left_click {
+                    self.click_to_travel(ctx)
+                } else if self.right_click {
+                    match camera::mouse_to_map(&self.ecs, ctx) {
+                        Some(pos) => RunState::Looking {
+                            x: pos.x,
+                            y: pos.y,
+                            index: 0,
+                        },
+                        None => RunState::AwaitingInput,
+                    }
+                } else {
+                    self.player_turn(KeyPress::from_ctx(ctx))
+                };
+                if newrunstate == RunState::AwaitingInput {
+                    if let Some(playback) = &mut self.playback {
+                        playback.skip_idle();
+                    }
+                }
+            }
+            RunState::Travelling => {
+                // Any key or click stops the walk
+                let input = KeyPress::from_ctx(ctx);
+                if input.key.is_some() || ctx.left_click || self.right_click {
+                    self.travel = None;
+                    newrunstate = RunState::AwaitingInput;
+                } else {
+                    newrunstate = self.travel_turn();
+                }
+            }
+            RunState::PreRun
+            | RunState::Ticking
+            | RunState::NextLevel
+            | RunState::PreviousLevel
+            | RunState::TownPortal
+            | RunState::TeleportingToOtherLevel { .. }
+            | RunState::MagicMapReveal { .. } => newrunstate = self.advance(newrunstate),
+            RunState::ShowInventory { page } => {
+                let result = gui::show_inventory(self, ctx, page);
+                match result.0 {
+                    gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::ItemMenuResult::NoResponse => {}
+                    gui::ItemMenuResult::Selected => {
+                        let item_entity = result.1.unwrap();
+                        let is_ranged = self.ecs.read_storage::<Ranged>();
+                        let is_item_ranged = is_ranged.get(item_entity);
+                        if let Some(is_item_ranged) = is_item_ranged {
+                            newrunstate = RunState::ShowTargeting {
+                                range: is_item_ranged.range,
+                                item: item_entity,
+                            };
+                        } else {
+                            let mut intent = self.ecs.write_storage::<WantsToUseItem>();
+                            intent
+                                .insert(
+                                    *self.ecs.fetch::<Entity>(),
+                                    WantsToUseItem {
+                                        item: item_entity,
+                                        target: None,
+                                    },
+                                )
+                                .expect("Unable to insert intent");
+                            newrunstate = RunState::Ticking;
+                        }
+                    }
+                    gui::ItemMenuResult::NextPage => {
+                        newrunstate = RunState::ShowInventory { page: page + 1 }
+                    }
+                    gui::ItemMenuResult::PreviousPage => {
+                        newrunstate = RunState::ShowInventory { page: page - 1 }
+                    }
+                }
+            }
+            RunState::ShowCheatMenu => {
+                let result = gui::show_cheat_mode(self, ctx);
+                match result {
+                    gui::CheatMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::CheatMenuResult::NoResponse => {}
+                    gui::CheatMenuResult::TeleportToExit => {
+                        self.goto_level(1);
+                        self.mapgen_next_state = Some(RunState::PreRun);
+                        newrunstate = RunState::MapGeneration;
+                    }
+                    gui::CheatMenuResult::Heal => {
+                        let player = self.ecs.fetch::<Entity>();
+                        let mut pools = self.ecs.write_storage::<Pools>();
+                        let mut player_pools = pools.get_mut(*player).unwrap();
+                        player_pools.hit_points.current = player_pools.hit_points.max;
+                        newrunstate = RunState::AwaitingInput;
+                    }
+                    gui::CheatMenuResult::Reveal => {
+                        let mut map = self.ecs.fetch_mut::<Map>();
+                        for v in map.revealed_tiles.iter_mut() {
+                            *v = true;
+                        }
+                        newrunstate = RunState::AwaitingInput;
+                    }
+                    gui::CheatMenuResult::GodMode => {
+                        let player = self.ecs.fetch::<Entity>();
+                        let mut pools = self.ecs.write_storage::<Pools>();
+                        let mut player_pools = pools.get_mut(*player).unwrap();
+                        player_pools.god_mode = true;
+                        newrunstate = RunState::AwaitingInput;
+                    }
+                    gui::CheatMenuResult::LearnSpells => {
+                        let player = self.ecs.fetch::<Entity>();
+                        let spells = self.ecs.read_storage::<SpellTemplate>();
+                        let names = self.ecs.read_storage::<Name>();
+                        let mut known_spells = self.ecs.write_storage::<KnownSpells>();
+                        let entities = self.ecs.entities();
+
+                        let mut updated_spells = Vec::new();
+                        for (_entity, name, template) in (&entities, &names, &spells).join() {
+                            updated_spells.push(KnownSpell {
+                                display_name: name.name.clone(),
+                                mana_cost: template.mana_cost,
+                            });
+                        }
+                        known_spells
+                            .insert(
+                                *player,
+                                KnownSpells {
+                                    spells: updated_spells,
+                                },
+                            )
+                            .expect("Unable to insert spells");
+
+                        newrunstate = RunState::AwaitingInput;
+                    }
+                    gui::CheatMenuResult::AllItems => {
+                        let player_entity = *self.ecs.fetch::<Entity>();
+                        raws::give_all_items(&mut self.ecs, player_entity);
+
+                        newrunstate = RunState::AwaitingInput;
+                    }
+                }
+            }
+            RunState::ShowDropItem { page } => {
+                let result = gui::drop_item_menu(self, ctx, page);
+                match result.0 {
+                    gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::ItemMenuResult::NoResponse => {}
+                    gui::ItemMenuResult::Selected => {
+                        let item_entity = result.1.unwrap();
+                        let mut intent = self.ecs.write_storage::<WantsToDropItem>();
+                        intent
+                            .insert(
+                                *self.ecs.fetch::<Entity>(),
+                                WantsToDropItem { item: item_entity },
+                            )
+                            .expect("Unable to insert intent");
+                        newrunstate = RunState::Ticking;
+                    }
+                    gui::ItemMenuResult::NextPage => {
+                        newrunstate = RunState::ShowDropItem { page: page + 1 }
+                    }
+                    gui::ItemMenuResult::PreviousPage => {
+                        newrunstate = RunState::ShowDropItem { page: page - 1 }
+                    }
+                }
+            }
+            RunState::ShowRemoveItem { page } => {
+                let result = gui::remove_item_menu(self, ctx, page);
+                match result.0 {
+                    gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::ItemMenuResult::NoResponse => {}
+                    gui::ItemMenuResult::Selected => {
+                        let item_entity = result.1.unwrap();
+                        let mut intent = self.ecs.write_storage::<WantsToRemoveItem>();
+                        intent
+                            .insert(
+                                *self.ecs.fetch::<Entity>(),
+                                WantsToRemoveItem { item: item_entity },
+                            )
+                            .expect("Unable to insert intent");
+                        newrunstate = RunState::Ticking;
+                    }
+                    gui::ItemMenuResult::NextPage => {
+                        newrunstate = RunState::ShowRemoveItem { page: page + 1 }
+                    }
+                    gui::ItemMenuResult::PreviousPage => {
+                        newrunstate = RunState::ShowRemoveItem { page: page - 1 }
+                    }
+                }
+            }
+            RunState::ShowRemoveCurse { page } => {
+                let result = gui::remove_curse_menu(self, ctx, page);
+                match result.0 {
+                    gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::ItemMenuResult::NoResponse => {}
+                    gui::ItemMenuResult::Selected => {
+                        let item_entity = result.1.unwrap();
+                        self.ecs.write_storage::<CursedItem>().remove(item_entity);
+                        newrunstate = RunState::Ticking;
+                    }
+                    gui::ItemMenuResult::NextPage => {
+                        newrunstate = RunState::ShowRemoveCurse { page: page + 1 }
+                    }
+                    gui::ItemMenuResult::PreviousPage => {
+                        newrunstate = RunState::ShowRemoveCurse { page: page - 1 }
+                    }
+                }
+            }
+            RunState::ShowIdentify { page } => {
+                let result = gui::identify_menu(self, ctx, page);
+                match result.0 {
+                    gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::ItemMenuResult::NoResponse => {}
+                    gui::ItemMenuResult::Selected => {
+                        let item_entity = result.1.unwrap();
+                        if let Some(name) = self.ecs.read_storage::<Name>().get(item_entity) {
+                            let mut dm = self.ecs.fetch_mut::<MasterDungeonMap>();
+                            dm.identified_items.insert(name.name.clone());
+                        }
+                        newrunstate = RunState::Ticking;
+                    }
+                    gui::ItemMenuResult::NextPage => {
+                        newrunstate = RunState::ShowIdentify { page: page + 1 }
+                    }
+                    gui::ItemMenuResult::PreviousPage => {
+                        newrunstate = RunState::ShowIdentify { page: page - 1 }
+                    }
+                }
+            }
+            RunState::ShowTargeting { range, item } => {
+                let result = gui::ranged_target(self, ctx, range);
+                match result.0 {
+                    gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::ItemMenuResult::NoResponse => {}
+                    gui::ItemMenuResult::NextPage => {}
+                    gui::ItemMenuResult::PreviousPage => {}
+                    gui::ItemMenuResult::Selected => {
+                        if self.ecs.read_storage::<SpellTemplate>().get(item).is_some() {
+                            let mut intent = self.ecs.write_storage::<WantsToCastSpell>();
+                            intent
+                                .insert(
+                                    *self.ecs.fetch::<Entity>(),
+                                    WantsToCastSpell {
+                                        spell: item,
+                                        target: result.1,
+                                    },
+                                )
+                                .expect("Unable to insert intent");
+                            newrunstate = RunState::Ticking;
+                        } else {
+                            let mut intent = self.ecs.write_storage::<WantsToUseItem>();
+                            intent
+                                .insert(
+                                    *self.ecs.fetch::<Entity>(),
+                                    WantsToUseItem {
+                                        item,
+                                        target: result.1,
+                                    },
+                                )
+                                .expect("Unable to insert intent");
+                            newrunstate = RunState::Ticking;
+                        }
+                    }
+                }
+            }
+            RunState::ShowVendor { vendor, mode, page } => {
+                let result = gui::show_vendor_menu(self, ctx, vendor, mode, page);
+                match result.0 {
+                    gui::VendorResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::VendorResult::NoResponse => {}
+                    gui::VendorResult::Sell => {
+                        let item = result.1.unwrap();
+                        let price = self
+                            .ecs
+                            .read_storage::<Item>()
+                            .get(item)
+                            .unwrap()
+                            .base_value
+                            * 0.8;
+                        self.ecs
+                            .write_storage::<Pools>()
+                            .get_mut(*self.ecs.fetch::<Entity>())
+                            .unwrap()
+                            .gold += price;
+                        self.hand_over(item, vendor);
+                        self.ecs
+                            .read_resource::<SoundSystem>()
+                            .play_sound_effects(event_sounds("vendor_sell", None));
+                    }
+                    gui::VendorResult::Buy => {
+                        let item = result.1.unwrap();
+                        let price = self
+                            .ecs
+                            .read_storage::<Item>()
+                            .get(item)
+                            .unwrap()
+                            .base_value
+                            * 1.2;
+                        let player_entity = *self.ecs.fetch::<Entity>();
+                        self.identify_for_player(item);
+                        let mut pools = self.ecs.write_storage::<Pools>();
+                        let player_pools = pools.get_mut(player_entity).unwrap();
+                        if player_pools.gold >= price {
+                            player_pools.gold -= price;
+                            std::mem::drop(pools);
+                            self.hand_over(item, player_entity);
+                            self.ecs
+                                .fetch::<SoundSystem>()
+                                .play_sound_effects(event_sounds("vendor_buy", None));
+                        }
+                    }
+                    gui::VendorResult::Steal => {
+                        let item = result.1.unwrap();
+                        let price = self
+                            .ecs
+                            .read_storage::<Item>()
+                            .get(item)
+                            .unwrap()
+                            .base_value
+                            * 1.2;
+                        let player_entity = *self.ecs.fetch::<Entity>();
+                        let attributes = self.ecs.read_storage::<Attributes>();
+                        let player_attributes = attributes.get(player_entity).unwrap();
+                        // Calculate the value needed for success
+                        let target_value = match price {
+                            i if i < 50.0 => 10,
+                            i if i < 100.0 => 15,
+                            i if i < 300.0 => 20,
+                            _ => 25,
+                        };
+                        let natural_roll = crate::rng::combat::roll_dice(1, 20);
+                        let quickness_bonus = player_attributes.quickness.bonus;
+                        std::mem::drop(attributes);
+                        let stealth = natural_roll + quickness_bonus;
+                        if stealth > target_value {
+                            // Successful theft
+                            self.identify_for_player(item);
+                            self.hand_over(item, player_entity);
+                            self.ecs
+                                .fetch::<SoundSystem>()
+                                .play_sound_effects(event_sounds("theft_success", None));
+                        } else {
+                            // Failed to steal
+                            gamelog::Logger::new()
+                                .category(gamelog::LogCategory::Loot)
+                                .append("You fumble, and leave the")
+                                .item_name(&self.ecs.read_storage::<Name>().get(item).unwrap().name)
+                                .append("where it is.")
+                                .log();
+                            self.ecs
+                                .fetch::<SoundSystem>()
+                                .play_sound_effects(event_sounds("theft_failure", None));
+                        }
+                        // Anyone watching may have seen it, whether or not it worked
+                        if crime::witness_theft(&mut self.ecs, player_entity, item, stealth)
+                            || stealth <= target_value
+                        {
+                            // Exit the vendor menu
+                            newrunstate = RunState::AwaitingInput
+                        }
+                    }
+                    gui::VendorResult::BuyMode => {
+                        newrunstate = RunState::ShowVendor {
+                            vendor,
+                            mode: VendorMode::Buy,
+                            page: 0,
+                        }
+                    }
+                    gui::VendorResult::SellMode => {
+                        newrunstate = RunState::ShowVendor {
+                            vendor,
+                            mode: VendorMode::Sell,
+                            page: 0,
+                        }
+                    }
+                    gui::VendorResult::StealMode => {
+                        newrunstate = RunState::ShowVendor {
+                            vendor,
+                            mode: VendorMode::Steal,
+                            page: 0,
+                        }
+                    }
+                    gui::VendorResult::PreviousPage => {
+                        newrunstate = RunState::ShowVendor {
+                            vendor,
+                            mode: mode,
+                            page: page - 1,
+                        }
+                    }
+                    gui::VendorResult::NextPage => {
+                        newrunstate = RunState::ShowVendor {
+                            vendor,
+                            mode: mode,
+                            page: page + 1,
+                        }
+                    }
+                }
+            }
+            RunState::ShowFine { collector } => {
+                let result = gui::fine_menu(self, ctx, collector);
+                match result {
+                    gui::FineResult::NoResponse => {}
+                    gui::FineResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::FineResult::Pay => {
+                        crime::pay_fine(&mut self.ecs, collector);
+                        newrunstate = RunState::AwaitingInput;
+                    }
+                }
+            }
+            RunState::MainMenu { .. } => {
+                let mut result = gui::main_menu(self, ctx);
+                if let Some(playback) = &self.playback {
+                    // The menu's entries depend on what is on disk, so replays use the recorded
+                    // choice rather than the recorded keys
+                    result = match (playback.menu_selection(), result) {
+                        (Some(selected), _) => gui::MainMenuResult::Selected { selected },
+                        (None, gui::MainMenuResult::Selected { selected }) => {
+                            gui::MainMenuResult::NoSelection { selected }
+                        }
+                        (None, result) => result,
+                    };
+                }
+                match result {
+                    gui::MainMenuResult::NoSelection { selected } => {
+                        newrunstate = RunState::MainMenu {
+                            menu_selection: selected,
+                        }
+                    }
+                    gui::MainMenuResult::Selected { selected }
+                        if self.playback.is_some() && !self.replay_continues(selected) =>
+                    {
+                        self.finish_replay();
+                        newrunstate = RunState::MainMenu {
+                            menu_selection: selected,
+                        };
+                    }
+                    gui::MainMenuResult::Selected { selected } => {
+                        if let Some(recorder) = &mut self.recorder {
+                            recorder.record_menu(selected);
+                        }
+                        match selected {
+                            gui::MainMenuSelection::NewGame => {
+                                // Resuming keeps the run as it was, ironman or not
+                                if !self.run_in_progress {
+                                    self.ecs.insert(saveload_system::RunInfo::new(false));
+                                    self.record_next_run = true;
+                                }
+                                newrunstate = RunState::PreRun;
+                            }
+                            gui::MainMenuSelection::NewSeededGame => {
+                                self.seed_input.clear();
+                                newrunstate = RunState::SeedEntry;
+                            }
+                            gui::MainMenuSelection::NewIronmanGame => {
+                                self.ecs.insert(saveload_system::RunInfo::new(true));
+                                self.record_next_run = true;
+                                newrunstate = RunState::PreRun;
+                            }
+                            gui::MainMenuSelection::ResumeIronman => {
+                                let result = saveload_system::load_ironman(&mut self.ecs);
+                                newrunstate = self.finish_load(result);
+                            }
+                            gui::MainMenuSelection::Options => {
+                                newrunstate = RunState::OptionsMenu {
+                                    menu_selection: gui::OptionsMenuSelection::ToggleFps,
+                                }
+                            }
+                            gui::MainMenuSelection::Continue => {
+                                let result = saveload_system::load_latest_autosave(&mut self.ecs);
+                                newrunstate = self.finish_load(result);
+                            }
+                            gui::MainMenuSelection::LoadGame => {
+                                newrunstate = RunState::SaveSlots {
+                                    mode: gui::SaveSlotMode::Load,
+                                    selection: 0,
+                                };
+                            }
+                            gui::MainMenuSelection::SaveGame if self.is_ironman() => {
+                                match saveload_system::save_ironman(
+                                    &mut self.ecs,
+                                    self.game_options.save_format,
+                                ) {
+                                    Ok(()) => {
+                                        self.stop_recording();
+                                        ::std::process::exit(0);
+                                    }
+                                    Err(e) => {
+                                        self.save_error = Some(e);
+                                        newrunstate = RunState::SaveError {
+                                            mode: gui::SaveSlotMode::Save,
+                                        };
+                                    }
+                                }
+                            }
+                            gui::MainMenuSelection::SaveGame => {
+                                newrunstate = RunState::SaveSlots {
+                                    mode: gui::SaveSlotMode::Save,
+                                    selection: 0,
+                                };
+                            }
+                            gui::MainMenuSelection::Quit => {
+                                // Ironman runs can't be abandoned by quitting
+                                if self.is_ironman() && self.run_in_progress {
+                                    if let Err(e) = saveload_system::save_ironman(
+                                        &mut self.ecs,
+                                        self.game_options.save_format,
+                                    ) {
+                                        self.save_error = Some(e);
+                                        newrunstate = RunState::SaveError {
+                                            mode: gui::SaveSlotMode::Save,
+                                        };
+                                    } else {
+                                        self.stop_recording();
+                                        ::std::process::exit(0);
+                                    }
+                                } else {
+                                    self.stop_recording();
+                                    ::std::process::exit(0);
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            RunState::SaveSlots { mode, .. } => {
+                let result = gui::save_slot_menu(self, ctx);
+                match result {
+                    gui::SaveSlotResult::NoSelection { selected } => {
+                        newrunstate = RunState::SaveSlots {
+                            mode,
+                            selection: selected,
+                        }
+                    }
+                    gui::SaveSlotResult::Selected { selected } => match mode {
+                        // Replays never touch the player's saves
+                        gui::SaveSlotMode::Save if self.playback.is_some() => {
+                            newrunstate = RunState::MainMenu {
+                                menu_selection: gui::MainMenuSelection::Quit,
+                            }
+                        }
+                        gui::SaveSlotMode::Load => {
+                            let result = saveload_system::load_game(&mut self.ecs, selected);
+                            newrunstate = self.finish_load(result);
+                        }
+                        gui::SaveSlotMode::Save => {
+                            match saveload_system::save_game(
+                                &mut self.ecs,
+                                selected,
+                                self.game_options.save_format,
+                            ) {
+                                Ok(()) => {
+                                    newrunstate = RunState::MainMenu {
+                                        menu_selection: gui::MainMenuSelection::Quit,
+                                    }
+                                }
+                                Err(e) => {
+                                    self.save_error = Some(e);
+                                    newrunstate = RunState::SaveError { mode };
+                                }
+                            }
+                        }
+                    },
+                    gui::SaveSlotResult::Delete { selected } if self.playback.is_some() => {
+                        newrunstate = RunState::SaveSlots {
+                            mode,
+                            selection: selected,
+                        };
+                    }
+                    gui::SaveSlotResult::Delete { selected } => {
+                        let result = saveload_system::delete_save(selected);
+                        self.save_listing = saveload_system::SaveListing::read();
+                        if let Err(e) = result {
+                            self.save_error = Some(e);
+                            newrunstate = RunState::SaveError { mode };
+                        } else if mode == gui::SaveSlotMode::Load
+                            && !self.save_listing.any_slot_saved()
+                        {
+                            newrunstate = RunState::MainMenu {
+                                menu_selection: gui::MainMenuSelection::NewGame,
+                            };
+                        } else {
+                            newrunstate = RunState::SaveSlots {
+                                mode,
+                                selection: selected,
+                            };
+                        }
+                    }
+                    gui::SaveSlotResult::Cancel => {
+                        let menu_selection = match mode {
+                            gui::SaveSlotMode::Load => gui::MainMenuSelection::LoadGame,
+                            gui::SaveSlotMode::Save => gui::MainMenuSelection::SaveGame,
+                        };
+                        newrunstate = RunState::MainMenu { menu_selection };
+                    }
+                }
+            }
+            RunState::SaveError { mode } => {
+                let result = gui::save_error_dialog(self, ctx, mode);
+                match result {
+                    gui::SaveErrorResult::NoResponse => {}
+                    gui::SaveErrorResult::Dismissed => {
+                        self.save_error = None;
+                        let menu_selection = match mode {
+                            gui::SaveSlotMode::Load if saveload_system::does_save_exist() => {
+                                gui::MainMenuSelection::LoadGame
+                            }
+                            gui::SaveSlotMode::Load => gui::MainMenuSelection::NewGame,
+                            gui::SaveSlotMode::Save => gui::MainMenuSelection::SaveGame,
+                        };
+                        newrunstate = RunState::MainMenu { menu_selection };
+                    }
+                }
+            }
+            RunState::SeedEntry => {
+                let result = gui::seed_entry_menu(self, ctx);
+                match result {
+                    gui::SeedEntryResult::NoResponse => {}
+                    gui::SeedEntryResult::Cancel => {
+                        newrunstate = RunState::MainMenu {
+                            menu_selection: gui::MainMenuSelection::NewSeededGame,
+                        }
+                    }
+                    gui::SeedEntryResult::Selected { seed } => {
+                        self.start_run(seed);
+                        self.ecs.insert(saveload_system::RunInfo::new(false));
+                        self.record_next_run = true;
+                        self.mapgen_next_state = Some(RunState::PreRun);
+                        newrunstate = RunState::MapGeneration;
+                    }
+                }
+            }
+            RunState::OptionsMenu { .. } => {
+                let result = gui::options_menu(self, ctx);
+                match result {
+                    gui::OptionsMenuResult::NoSelection { selected } => {
+                        newrunstate = RunState::OptionsMenu {
+                            menu_selection: selected,
+                        }
+                    }
+                    gui::OptionsMenuResult::Selected { selected } => {
+                        match selected {
+                            gui::OptionsMenuSelection::ToggleFps => {
+                                self.game_options.show_fps = !self.game_options.show_fps;
+                                newrunstate = RunState::OptionsMenu {
+                                    menu_selection: selected,
+                                }
+                            }
+                            gui::OptionsMenuSelection::ToggleMapVisualizer => {
+                                self.game_options.show_map_visualizer =
+                                    !self.game_options.show_map_visualizer;
+                                newrunstate = RunState::OptionsMenu {
+                                    menu_selection: selected,
+                                }
+                            }
+                            gui::OptionsMenuSelection::ToggleCheatMenu => {
+                                self.game_options.show_cheat_menu =
+                                    !self.game_options.show_cheat_menu;
+                                newrunstate = RunState::OptionsMenu {
+                                    menu_selection: selected,
+                                }
+                            }
+                            gui::OptionsMenuSelection::ToggleAutoPickup => {
+                                self.game_options.auto_pickup = !self.game_options.auto_pickup;
+                                newrunstate = RunState::OptionsMenu {
+                                    menu_selection: selected,
+                                }
+                            }
+                            gui::OptionsMenuSelection::ToggleSaveFormat => {
+                                self.game_options.save_format = match self.game_options.save_format
+                                {
+                                    saveload_system::SaveFormatKind::Json => {
+                                        saveload_system::SaveFormatKind::Binary
+                                    }
+                                    saveload_system::SaveFormatKind::Binary => {
+                                        saveload_system::SaveFormatKind::Json
+                                    }
+                                };
+                                newrunstate = RunState::OptionsMenu {
+                                    menu_selection: selected,
+                                }
+                            }
+                            gui::OptionsMenuSelection::BackgroundVolume { change } => {
+                                let volume_change;
+                                match change {
+                                    gui::VolumeChange::None => {
+                                        volume_change = 0.0;
+                                    }
+                                    gui::VolumeChange::Increase => {
+                                        volume_change = options::VOLUME_STEP;
+                                    }
+                                    gui::VolumeChange::Decrease => {
+                                        volume_change = -options::VOLUME_STEP;
+                                    }
+                                }
+                                self.ecs
+                                    .fetch::<SoundSystem>()
+                                    .change_background_volume(volume_change);
+                                newrunstate = RunState::OptionsMenu {
+                                    menu_selection: gui::OptionsMenuSelection::BackgroundVolume {
+                                        change: gui::VolumeChange::None,
+                                    },
+                                }
+                            }
+                            gui::OptionsMenuSelection::EffectsVolume { change } => {
+                                let volume_change;
+                                match change {
+                                    gui::VolumeChange::None => {
+                                        volume_change = 0.0;
+                                    }
+                                    gui::VolumeChange::Increase => {
+                                        volume_change = options::VOLUME_STEP;
+                                    }
+                                    gui::VolumeChange::Decrease => {
+                                        volume_change = -options::VOLUME_STEP;
+                                    }
+                                }
+                                self.ecs
+                                    .fetch::<SoundSystem>()
+                                    .change_effect_volume(volume_change);
+                                newrunstate = RunState::OptionsMenu {
+                                    menu_selection: gui::OptionsMenuSelection::EffectsVolume {
+                                        change: gui::VolumeChange::None,
+                                    },
+                                }
+                            }
+                            gui::OptionsMenuSelection::KeyBindings => {
+                                self.key_bindings_message = None;
+                                newrunstate = RunState::KeyBindings {
+                                    selection: 0,
+                                    capturing: false,
+                                }
+                            }
+                            gui::OptionsMenuSelection::Quit => {
+                                newrunstate = RunState::MainMenu {
+                                    menu_selection: gui::MainMenuSelection::Options,
+                                }
+                            }
+                        }
+                        // Changes are kept as they are made, so nothing is lost on a crash
+                        self.save_options();
+                    }
+                }
+            }
+            RunState::KeyBindings {
+                selection,
+                capturing,
+            } => {
+                let action = keymap::Action::ALL[selection];
+                match gui::key_bindings_menu(self, ctx, selection, capturing) {
+                    gui::KeyBindingsResult::NoResponse {
+                        selection,
+                        capturing,
+                    } => {
+                        newrunstate = RunState::KeyBindings {
+                            selection,
+                            capturing,
+                        }
+                    }
+                    gui::KeyBindingsResult::Bind { selection, key } => {
+                        self.key_bindings_message = match self.game_options.keys.bind(action, key) {
+                            Ok(()) => None,
+                            Err(other) => Some(format!(
+                                "{} is already used for \"{}\"",
+                                keymap::key_press_name(key).unwrap_or_default(),
+                                other.description()
+                            )),
+                        };
+                        self.apply_keymap();
+                        newrunstate = RunState::KeyBindings {
+                            selection,
+                            capturing: false,
+                        }
+                    }
+                    gui::KeyBindingsResult::Clear { selection } => {
+                        self.key_bindings_message = if self.game_options.keys.clear(action) {
+                            None
+                        } else {
+                            Some(format!(
+                                "\"{}\" needs a key; add another before clearing this one",
+                                action.description()
+                            ))
+                        };
+                        self.apply_keymap();
+                        newrunstate = RunState::KeyBindings {
+                            selection,
+                            capturing: false,
+                        }
+                    }
+                    gui::KeyBindingsResult::Reset { selection } => {
+                        self.game_options.keys.reset(action);
+                        self.key_bindings_message = None;
+                        self.apply_keymap();
+                        newrunstate = RunState::KeyBindings {
+                            selection,
+                            capturing: false,
+                        }
+                    }
+                    gui::KeyBindingsResult::Back => {
+                        newrunstate = RunState::OptionsMenu {
+                            menu_selection: gui::OptionsMenuSelection::KeyBindings,
+                        }
+                    }
+                }
+            }
+            RunState::Looking { x, y, index } => match gui::look_mode(self, ctx, x, y, index) {
+                gui::LookResult::NoResponse { x, y, index } => {
+                    newrunstate = RunState::Looking { x, y, index }
+                }
+                gui::LookResult::Cancel => newrunstate = RunState::AwaitingInput,
+            },
+            RunState::MessageLog {
+                scroll,
+                category,
+                searching,
+            } => match gui::message_log_menu(self, ctx, scroll, category, searching) {
+                gui::MessageLogResult::NoResponse {
+                    scroll,
+                    category,
+                    searching,
+                } => {
+                    newrunstate = RunState::MessageLog {
+                        scroll,
+                        category,
+                        searching,
+                    }
+                }
+                gui::MessageLogResult::Close => newrunstate = RunState::AwaitingInput,
+            },
+            RunState::GameOver => {
+                let result = gui::game_over(ctx);
+                match result {
+                    gui::GameOverResult::NoSelection => {}
+                    gui::GameOverResult::QuitToMenu => {
+                        self.game_over_cleanup();
+                        newrunstate = RunState::MapGeneration;
+                        self.mapgen_next_state = Some(RunState::MainMenu {
+                            menu_selection: gui::MainMenuSelection::NewGame,
+                        });
+                    }
+                }
+            }
+        }
+
+        self.commit_runstate(newrunstate);
+
+        rltk::render_draw_buffer(ctx).expect("Failed to draw buffer");
+        if self.game_options.show_fps {
+            ctx.print(1, 59, &format!("FPS: {}", ctx.fps));
+        }
+    }
+}
+
+impl State {
+    /// Steps the states that play out without the player, returning the next state.
+    fn advance(&mut self, runstate: RunState) -> RunState {
+        let mut newrunstate = runstate;
+        match runstate {
+            RunState::PreRun => {
+                self.run_in_progress = true;
+                if self.record_next_run {
+                    self.record_next_run = false;
+                    self.start_recording();
+                }
+                self.run_systems();
+                self.ecs.maintain();
+                newrunstate = RunState::AwaitingInput;
+            }
+            RunState::Ticking => {
+                let mut should_change_target = false;
+                while newrunstate == RunState::Ticking {
+                    self.run_systems();
+                    self.ecs.maintain();
+                    match *self.ecs.fetch::<RunState>() {
+                        RunState::AwaitingInput => {
+                            newrunstate = RunState::AwaitingInput;
+                            should_change_target = true;
+                        }
+                        RunState::MagicMapReveal { .. } => {
+                            newrunstate = RunState::MagicMapReveal { row: 0 }
+                        }
+                        RunState::TownPortal => newrunstate = RunState::TownPortal,
+                        RunState::TeleportingToOtherLevel { x, y, depth } => {
+                            newrunstate = RunState::TeleportingToOtherLevel { x, y, depth }
+                        }
+                        RunState::ShowRemoveCurse { page } => {
+                            newrunstate = RunState::ShowRemoveCurse { page }
+                        }
+                        RunState::ShowIdentify { page } => {
+                            newrunstate = RunState::ShowIdentify { page }
+                        }
+                        _ => newrunstate = RunState::Ticking,
+                    }
+                }
+                if should_change_target {
+                    player::end_turn_targeting(&mut self.ecs);
+                    self.music.end_turn(&self.ecs);
+                    if self.travel.is_some() {
+                        newrunstate = RunState::Travelling;
+                    }
+                } else {
+                    // Level changes and menus end any walk
+                    self.travel = None;
+                }
+            }
+            RunState::NextLevel => {
+                self.goto_level(1);
+                self.mapgen_next_state = Some(RunState::PreRun);
+                newrunstate = RunState::MapGeneration;
+            }
+            RunState::PreviousLevel => {
+                self.goto_level(-1);
+                self.mapgen_next_state = Some(RunState::PreRun);
+                newrunstate = RunState::MapGeneration;
+            }
+            RunState::TownPortal => {
+                self.autosave();
+
+                // Spawn the portal
+                spawner::spawn_town_portal(&mut self.ecs);
+
+                // Transition
+                let map_depth = self.ecs.fetch::<Map>().depth;
+                let destination_offset = 0 - (map_depth - 1);
+                self.goto_level(destination_offset);
+                self.mapgen_next_state = Some(RunState::PreRun);
+                newrunstate = RunState::MapGeneration;
+            }
+            RunState::TeleportingToOtherLevel { x, y, depth } => {
+                self.goto_level(depth - 1);
+                let player_entity = self.ecs.fetch::<Entity>();
+                if let Some(pos) = self.ecs.write_storage::<Position>().get_mut(*player_entity) {
+                    pos.x = x;
+                    pos.y = y;
+                }
+                let mut ppos = self.ecs.fetch_mut::<rltk::Point>();
+                ppos.x = x;
+                ppos.y = y;
+                self.mapgen_next_state = Some(RunState::PreRun);
+                newrunstate = RunState::MapGeneration;
+            }
+            RunState::MagicMapReveal { row } => {
+                let mut map = self.ecs.fetch_mut::<Map>();
+                for x in 0..map.width {
+                    let idx = map.xy_idx(x as i32, row);
+                    map.revealed_tiles[idx] = true;
+                }
+                if row == map.height - 1 {
+                    newrunstate = RunState::Ticking;
+                } else {
+                    newrunstate = RunState::MagicMapReveal { row: row + 1 };
+                }
+            }
+            _ => {}
+        }
+        newrunstate
+    }
+
+    /// Moves an item into someone else's backpack, as when trading with a vendor.
+    fn hand_over(&mut self, item: Entity, to: Entity) {
+        let old_owner = self
+            .ecs
+            .read_storage::<InBackpack>()
+            .get(item)
+            .map(|carried| carried.owner);
+        self.ecs
+            .write_storage::<InBackpack>()
+            .insert(item, InBackpack { owner: to })
+            .expect("Unable to insert backpack entry");
+        let mut dirty = self.ecs.write_storage::<EquipmentChanged>();
+        for owner in old_owner.iter().chain(std::iter::once(&to)) {
+            dirty
+                .insert(*owner, EquipmentChanged {})
+                .expect("Unable to insert");
+        }
+    }
+
+    /// Lets the player know what an item really is, once it is theirs.
+    fn identify_for_player(&mut self, item: Entity) {
+        let name = match self.ecs.read_storage::<Name>().get(item) {
+            Some(name) => name.name.clone(),
+            None => return,
+        };
+        let player_entity = *self.ecs.fetch::<Entity>();
+        self.ecs
+            .write_storage::<IdentifiedItem>()
+            .insert(player_entity, IdentifiedItem { name })
+            .expect("Unable to insert");
+    }
+
+    /// Handles the player's key press, returning the next state.
+    fn player_turn(&mut self, input: KeyPress) -> RunState {
+        self.autosave_if_due();
+        let newrunstate = player_input(self, input);
+        // A walk counts its turns as it takes them
+        if newrunstate != RunState::AwaitingInput && newrunstate != RunState::Travelling {
+            crate::gamelog::record_event("Turn", 1);
+        }
+        newrunstate
+    }
+
+    fn autosave_if_due(&mut self) {
+        let turn = crate::gamelog::get_event_count("Turn");
+        if turn > 0
+            && turn % saveload_system::AUTOSAVE_INTERVAL == 0
+            && turn != self.last_autosave_turn
+        {
+            self.autosave();
+        }
+    }
+
+    /// Starts walking to the map tile that was clicked on.
+    fn click_to_travel(&mut self, ctx: &mut BTerm) -> RunState {
+        let destination = match camera::mouse_to_map(&self.ecs, ctx) {
+            Some(destination) => destination,
+            None => return RunState::AwaitingInput,
+        };
+        self.travel = travel::Travel::to(&self.ecs, destination);
+        if self.travel.is_some() {
+            RunState::Travelling
+        } else {
+            RunState::AwaitingInput
+        }
+    }
+
+    /// Takes the next step of the walk the player is on, returning the next state.
+    fn travel_turn(&mut self) -> RunState {
+        let mut travel = match self.travel.take() {
+            Some(travel) => travel,
+            None => return RunState::AwaitingInput,
+        };
+        let step = match travel.next_step(&self.ecs) {
+            Some(step) => step,
+            None => return RunState::AwaitingInput,
+        };
+
+        self.autosave_if_due();
+        let newrunstate = match step {
+            travel::Step::Move(delta_x, delta_y) => {
+                player::try_move_player(delta_x, delta_y, &mut self.ecs)
+            }
+            travel::Step::PickUp => {
+                player::get_item(&mut self.ecs);
+                RunState::Ticking
+            }
+        };
+        if newrunstate != RunState::AwaitingInput {
+            crate::gamelog::record_event("Turn", 1);
+        }
+        if newrunstate == RunState::Ticking && travel.stepped(&self.ecs) {
+            self.travel = Some(travel);
+        }
+        newrunstate
+    }
+
+    /// Stores the state for the next frame, and clears out anything that died this frame.
+    fn commit_runstate(&mut self, newrunstate: RunState) {
+        // Vendors top up their stock as the player comes to trade
+        if let RunState::ShowVendor { vendor, .. } = newrunstate {
+            if !matches!(*self.ecs.fetch::<RunState>(), RunState::ShowVendor { .. }) {
+                spawner::restock_vendor_if_due(&mut self.ecs, vendor);
+            }
+        }
+        // Menus list the saves on disk as they open, rather than reading them every frame
+        let opening_save_menu = match (*self.ecs.fetch::<RunState>(), newrunstate) {
+            (RunState::MainMenu { .. }, RunState::MainMenu { .. }) => false,
+            (RunState::SaveSlots { .. }, RunState::SaveSlots { .. }) => false,
+            (_, RunState::MainMenu { .. }) | (_, RunState::SaveSlots { .. }) => true,
+            _ => false,
+        };
+        if opening_save_menu {
+            self.save_listing = saveload_system::SaveListing::read();
+        }
+        {
+            let mut runwriter = self.ecs.write_resource::<RunState>();
+            *runwriter = newrunstate;
+        }
+        damage_system::delete_the_dead(&mut self.ecs);
+
+        // The player just died: bury the run
+        if newrunstate != RunState::GameOver && *self.ecs.fetch::<RunState>() == RunState::GameOver
+        {
+            systems::sound_system::play_stinger(&self.ecs, "death");
+            if self.touches_disk() {
+                if let Err(e) = saveload_system::record_death(&self.ecs) {
+                    gamelog::Logger::new()
+                        .category(gamelog::LogCategory::System)
+                        .color(rltk::RED)
+                        .append(format!("Unable to record your death: {}", e))
+                        .log();
+                }
+                self.stop_recording();
+            }
+        }
+    }
+
+    /// Replays and headless runs leave the player's saves and graveyard alone.
+    fn touches_disk(&self) -> bool {
+        self.playback.is_none() && !self.headless
+    }
+
+    fn goto_level(&mut self, offset: i32) {
+        freeze_level_entities(&mut self.ecs);
+
+        // Build a new map and place the player
+        let current_depth = self.ecs.fetch::<Map>().depth;
+        self.generate_world_map(current_depth + offset, offset);
+        if current_depth + offset == 1 {
+            spawner::restock_vendors(&mut self.ecs);
+        }
+
+        // Notify the player
+        gamelog::Logger::new()
+            .category(gamelog::LogCategory::System)
+            .append("You change level.")
+            .log();
+        self.ecs
+            .fetch::<SoundSystem>()
+            .play_sound_effects(event_sounds("level_change", None));
+
+        self.autosave();
+    }
+
+    /// Puts the player's key bindings into effect and keeps them.
+    fn apply_keymap(&mut self) {
+        self.ecs.insert(self.game_options.keys.clone());
+        self.save_options();
+    }
+
+    fn save_options(&mut self) {
+        {
+            let sound_system = self.ecs.fetch::<SoundSystem>();
+            self.game_options.background_volume = sound_system.get_background_volume();
+            self.game_options.effects_volume = sound_system.get_effects_volume();
+        }
+        if !self.touches_disk() {
+            return;
+        }
+        if let Err(e) = options::save_options(&self.game_options) {
+            gamelog::Logger::new()
+                .category(gamelog::LogCategory::System)
+                .color(rltk::RED)
+                .append(format!("Unable to save the options: {}", e))
+                .log();
+        }
+    }
+
+    fn is_ironman(&self) -> bool {
+        self.ecs.fetch::<saveload_system::RunInfo>().ironman
+    }
+
+    fn autosave(&mut self) {
+        self.last_autosave_turn = gamelog::get_event_count("Turn");
+        if !self.touches_disk() {
+            return;
+        }
+        self.save_recording();
+
+        // Ironman runs keep a single save, so there is nothing older to fall back to
+        let result = if self.is_ironman() {
+            saveload_system::save_ironman(&mut self.ecs, self.game_options.save_format)
+        } else {
+            saveload_system::autosave(&mut self.ecs, self.game_options.save_format)
+        };
+        if let Err(e) = result {
+            gamelog::Logger::new()
+                .category(gamelog::LogCategory::System)
+                .color(rltk::RED)
+                .append(format!("Autosave failed: {}", e))
+                .log();
+        }
+    }
+
+    fn finish_load(&mut self, result: Result<(), saveload_system::SaveError>) -> RunState {
+        match result {
+            Ok(()) => {
+                // A loaded world didn't come from a seed, so it can't be replayed
+                self.stop_recording();
+                self.travel = None;
+                self.run_in_progress = true;
+                self.music.enter_level(&self.ecs);
+                RunState::AwaitingInput
+            }
+            Err(e) => {
+                // A half-loaded world can't be resumed, so start over
+                let player_entity = *self.ecs.fetch::<Entity>();
+                if !self.ecs.is_alive(player_entity) {
+                    self.game_over_cleanup();
+                }
+                self.save_error = Some(e);
+                RunState::SaveError {
+                    mode: gui::SaveSlotMode::Load,
+                }
+            }
+        }
+    }
+
+    fn game_over_cleanup(&mut self) {
+        self.start_run(crate::rng::random_seed());
+    }
+
+    /// Replaces the world with a fresh player in a new dungeon built from `seed`.
+    fn start_run(&mut self, seed: u64) {
+        // Start over from an empty world rather than deleting everything, so that entity ids
+        // (and with them, the order systems visit entities in) only depend on the seed
+        let assets = self.ecs.remove::<rex_assets::RexAssets>();
+        let sound_system = self.ecs.remove::<SoundSystem>();
+        self.ecs = World::new();
+        init_world(&mut self.ecs, seed);
+        if let Some(assets) = assets {
+            self.ecs.insert(assets);
+        }
+        if let Some(sound_system) = sound_system {
+            self.ecs.insert(sound_system);
+        }
+        self.ecs.insert(self.game_options.keys.clone());
+        self.travel = None;
+        self.run_in_progress = false;
+
+        // The log and the event counts cover the whole run, so they only start over with a new one
+        gamelog::clear_log();
+        gamelog::clear_events();
+        effects::EFFECT_QUEUE.lock().unwrap().clear();
+        gamelog::Logger::new()
+            .category(gamelog::LogCategory::System)
+            .append("Welcome to")
+            .color(rltk::CYAN)
+            .append("Rusty Roguelike")
+            .log();
+
+        // Build a new map and place the player
+        self.generate_world_map(1, 0);
+    }
+
+    fn start_recording(&mut self) {
+        self.stop_recording();
+        let run = *self.ecs.fetch::<saveload_system::RunInfo>();
+        let seed = self.ecs.fetch::<MasterDungeonMap>().seed;
+        self.recorder = Some(replay::Recorder::new(
+            run,
+            seed,
+            self.game_options.show_map_visualizer,
+            self.game_options.show_cheat_menu,
+            self.game_options.auto_pickup,
+            self.game_options.keys.clone(),
+        ));
+    }
+
+    fn save_recording(&mut self) {
+        if let Some(recorder) = &self.recorder {
+            if let Err(e) = recorder.save() {
+                gamelog::Logger::new()
+                    .category(gamelog::LogCategory::System)
+                    .color(rltk::RED)
+                    .append(format!("Unable to save the replay: {}", e))
+                    .log();
+            }
+        }
+    }
+
+    fn stop_recording(&mut self) {
+        self.save_recording();
+        self.recorder = None;
+    }
+
+    /// Menu choices that stay inside the recorded run. Anything else ends the replay there.
+    fn replay_continues(&self, selected: gui::MainMenuSelection) -> bool {
+        match selected {
+            gui::MainMenuSelection::NewGame | gui::MainMenuSelection::Options => true,
+            gui::MainMenuSelection::SaveGame => !self.is_ironman(),
+            _ => false,
+        }
+    }
+
+    fn finish_replay(&mut self) {
+        self.playback = None;
+        gamelog::Logger::new()
+            .category(gamelog::LogCategory::System)
+            .color(rltk::CYAN)
+            .append("The replay has finished.")
+            .log();
+    }
+
+    fn generate_world_map(&mut self, new_depth: i32, offset: i32) {
+        self.mapgen_index = 0;
+        self.mapgen_timer = 0.0;
+        self.mapgen_history.clear();
+        let map_building_info = map::level_transition(
+            &mut self.ecs,
+            new_depth,
+            offset,
+            self.game_options.show_map_visualizer,
+        );
+        if let Some(history) = map_building_info {
+            self.mapgen_history = history;
+        } else {
+            map::thaw_level_entities(&mut self.ecs);
+        }
+
+        self.music.enter_level(&self.ecs);
+    }
+}
+
+fn init_world(ecs: &mut World, seed: u64) {
+    provide_all_components!(register, ecs);
+    ecs.register::<SimpleMarker<SerializeMe>>();
+    ecs.insert(SimpleMarkerAllocator::<SerializeMe>::new());
+
+    raws::load_raws();
+
+    ecs.insert(map::MasterDungeonMap::new(seed));
+    ecs.insert(Map::new(1, 64, 64, "New Map"));
+    ecs.insert(Point::new(0, 0));
+    let player_entity = spawner::player(ecs, 0, 0);
+    ecs.insert(player_entity);
+    ecs.insert(RunState::MapGeneration {});
+    ecs.insert(systems::particle_system::ParticleBuilder::new());
+    ecs.insert(saveload_system::RunInfo::new(false));
+}
+
+/// Runs the game as the command line asks: in a window, replaying a recording, or headless
+/// from a script.
+pub fn run() -> rltk::BError {
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(index) = args.iter().position(|arg| arg == "--headless") {
+        let path = args
+            .get(index + 1)
+            .ok_or("--headless needs a script file")?;
+        let seed = match args.iter().position(|arg| arg == "--seed") {
+            Some(index) => rng::parse_seed(args.get(index + 1).ok_or("--seed needs a seed")?),
+            None => rng::random_seed(),
+        };
+        headless::run(std::path::Path::new(path), seed)?;
+        return Ok(());
+    }
+
+    // Load the replay up front, so a bad file is reported before a window opens
+    let playback = match args.iter().position(|arg| arg == "--replay") {
+        Some(index) => {
+            let path = args.get(index + 1).ok_or("--replay needs a replay file")?;
+            Some(replay::Playback::load(std::path::Path::new(path))?)
+        }
+        None => None,
+    };
+
+    use rltk::BTermBuilder;
+    let mut context = BTermBuilder::simple(80, 60)
+        .unwrap()
+        .with_title("Roguelike Tutorial")
+        .with_font("vga8x16.png", 8, 16)
+        .with_sparse_console(80, 30, "vga8x16.png")
+        .with_vsync(false)
+        .with_fps_cap(60.0)
+        .build()?;
+    context.with_post_scanlines(true);
+    // The output has to outlive the sound system, or nothing is heard
+    let (_output, sound_system) = systems::sound_system::open_audio();
+    let mut gs = State::new();
+    gs.game_options = options::load_options();
+    sound_system.set_background_volume(gs.game_options.background_volume);
+    sound_system.set_effects_volume(gs.game_options.effects_volume);
+    gs.ecs.insert(rex_assets::RexAssets::new());
+    gs.ecs.insert(sound_system);
+
+    if let Some(playback) = playback {
+        let replay = playback.replay();
+        gs.game_options.show_map_visualizer = replay.show_map_visualizer;
+        gs.game_options.show_cheat_menu = replay.show_cheat_menu;
+        gs.game_options.auto_pickup = replay.auto_pickup;
+        gs.game_options.keys = replay.keys.clone();
+        let (seed, ironman) = (replay.seed, replay.ironman);
+        gs.start_run(seed);
+        gs.ecs.insert(saveload_system::RunInfo::new(ironman));
+
+        // Recordings start just after the run's first PreRun
+        gs.run_systems();
+        gs.ecs.maintain();
+        gs.ecs.insert(RunState::AwaitingInput);
+        gs.run_in_progress = true;
+        gs.playback = Some(playback);
+    } else {
+        gs.start_run(rng::random_seed());
+        // Saves from before there were slots show up in the first free one
+        match saveload_system::import_legacy_save() {
+            Ok(None) => {}
+            Ok(Some(_)) => {
+                gs.mapgen_next_state = Some(RunState::MainMenu {
+                    menu_selection: gui::MainMenuSelection::LoadGame,
+                });
+            }
+            Err(e) => {
+                gs.save_error = Some(e);
+                gs.mapgen_next_state = Some(RunState::SaveError {
+                    mode: gui::SaveSlotMode::Load,
+                });
+            }
+        }
+    }
+
+    rltk::main_loop(context, gs)
+}
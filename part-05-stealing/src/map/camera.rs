@@ -19,6 +19,33 @@ pub fn get_screen_bounds(ecs: &World, _ctx: &mut rltk::BTerm) -> (i32, i32, i32,
     (min_x, max_x, min_y, max_y)
 }
 
+/// Where the map tile at `pos` is drawn on screen. The map sits one cell in from the border.
+pub fn map_to_screen(ecs: &World, ctx: &mut rltk::BTerm, pos: rltk::Point) -> rltk::Point {
+    let (min_x, _max_x, min_y, _max_y) = get_screen_bounds(ecs, ctx);
+    rltk::Point::new(pos.x - min_x + 1, pos.y - min_y + 1)
+}
+
+/// The map tile drawn at `pos` on screen; the reverse of `map_to_screen`.
+pub fn screen_to_map(ecs: &World, ctx: &mut rltk::BTerm, pos: rltk::Point) -> rltk::Point {
+    let (min_x, _max_x, min_y, _max_y) = get_screen_bounds(ecs, ctx);
+    rltk::Point::new(pos.x + min_x - 1, pos.y + min_y - 1)
+}
+
+/// The map tile under the mouse, or `None` when the mouse isn't over the map.
+pub fn mouse_to_map(ecs: &World, ctx: &mut rltk::BTerm) -> Option<rltk::Point> {
+    let (min_x, max_x, min_y, max_y) = get_screen_bounds(ecs, ctx);
+    let pos = screen_to_map(ecs, ctx, ctx.mouse_point());
+    let map = ecs.fetch::<Map>();
+    if pos.x < i32::max(min_x, 0)
+        || pos.x >= i32::min(max_x, map.width)
+        || pos.y < i32::max(min_y, 0)
+        || pos.y >= i32::min(max_y, map.height)
+    {
+        return None;
+    }
+    Some(pos)
+}
+
 const SHOW_BOUNDARIES: bool = false;
 
 pub fn render_camera(ecs: &World, ctx: &mut rltk::BTerm) {
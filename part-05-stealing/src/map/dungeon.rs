@@ -8,6 +8,8 @@ use std::collections::{HashMap, HashSet};
 
 #[derive(Default, Serialize, Deserialize, Clone)]
 pub struct MasterDungeonMap {
+    #[serde(default)]
+    pub seed: u64,
     maps: HashMap<i32, Map>,
     pub identified_items: HashSet<String>,
     pub scroll_mappings: HashMap<String, String>,
@@ -15,14 +17,16 @@ pub struct MasterDungeonMap {
 }
 
 impl MasterDungeonMap {
-    pub fn new() -> MasterDungeonMap {
+    pub fn new(seed: u64) -> MasterDungeonMap {
         let mut dm = MasterDungeonMap {
+            seed,
             maps: HashMap::new(),
             identified_items: HashSet::new(),
             scroll_mappings: HashMap::new(),
             potion_mappings: HashMap::new(),
         };
 
+        crate::rng::reseed_all(seed);
         for scroll_tag in crate::raws::get_scroll_tags().iter() {
             let masked_name = make_scroll_name();
             dm.scroll_mappings
@@ -54,12 +58,12 @@ impl MasterDungeonMap {
 }
 
 fn make_scroll_name() -> String {
-    let length = 4 + crate::rng::roll_dice(1, 4);
+    let length = 4 + crate::rng::map_gen::roll_dice(1, 4);
     let mut name = "Scroll of ".to_string();
 
     for i in 0..length {
         if i % 2 == 0 {
-            name += match crate::rng::roll_dice(1, 5) {
+            name += match crate::rng::map_gen::roll_dice(1, 5) {
                 1 => "a",
                 2 => "e",
                 3 => "i",
@@ -67,7 +71,7 @@ fn make_scroll_name() -> String {
                 _ => "u",
             }
         } else {
-            name += match crate::rng::roll_dice(1, 21) {
+            name += match crate::rng::map_gen::roll_dice(1, 21) {
                 1 => "b",
                 2 => "c",
                 3 => "d",
@@ -112,10 +116,11 @@ const POTION_ADJECTIVES: &[&str] = &[
 fn make_potion_name(used_names: &mut HashSet<String>) -> String {
     loop {
         let mut name: String = POTION_ADJECTIVES
-            [crate::rng::roll_dice(1, POTION_ADJECTIVES.len() as i32) as usize - 1]
+            [crate::rng::map_gen::roll_dice(1, POTION_ADJECTIVES.len() as i32) as usize - 1]
             .to_string();
         name += " ";
-        name += POTION_COLORS[crate::rng::roll_dice(1, POTION_COLORS.len() as i32) as usize - 1];
+        name += POTION_COLORS
+            [crate::rng::map_gen::roll_dice(1, POTION_COLORS.len() as i32) as usize - 1];
         name += " Potion";
 
         if !used_names.contains(&name) {
@@ -126,6 +131,10 @@ fn make_potion_name(used_names: &mut HashSet<String>) -> String {
 }
 
 fn transition_to_new_map(ecs: &mut World, new_depth: i32, show_visualizer: bool) -> Vec<Map> {
+    // Each level only depends on the run seed and its depth, whatever order they are visited in
+    let seed = ecs.fetch::<MasterDungeonMap>().seed;
+    crate::rng::reseed_level(seed, new_depth);
+
     let mut builder = level_builder(new_depth, 80, 50, show_visualizer);
     builder.build_map();
     if new_depth > 1 {
@@ -1,5 +1,5 @@
 mod tiletype;
-pub use tiletype::{tile_cost, tile_opaque, tile_walkable, TileType};
+pub use tiletype::{tile_cost, tile_name, tile_opaque, tile_walkable, TileType};
 
 use crate::rltk;
 use serde::{Deserialize, Serialize};
@@ -47,3 +47,22 @@ pub fn tile_cost(tt: TileType) -> f32 {
         _ => 1.0,
     }
 }
+
+/// What the tile is called when the player looks at it.
+pub fn tile_name(tt: TileType) -> &'static str {
+    match tt {
+        TileType::Wall => "Wall",
+        TileType::Stalactite => "Stalactite",
+        TileType::Stalagmite => "Stalagmite",
+        TileType::Floor => "Floor",
+        TileType::DownStairs => "Stairs down",
+        TileType::Road => "Road",
+        TileType::Grass => "Grass",
+        TileType::ShallowWater => "Shallow water",
+        TileType::DeepWater => "Deep water",
+        TileType::WoodFloor => "Wooden floor",
+        TileType::Bridge => "Bridge",
+        TileType::Gravel => "Gravel",
+        TileType::UpStairs => "Stairs up",
+    }
+}
@@ -72,8 +72,8 @@ impl PlazaMapBuilder {
         let mut voronoi_seeds: Vec<(usize, rltk::Point)> = Vec::new();
 
         while voronoi_seeds.len() < 32 {
-            let vx = crate::rng::roll_dice(1, build_data.map.width - 1);
-            let vy = crate::rng::roll_dice(1, build_data.map.height - 1);
+            let vx = crate::rng::map_gen::roll_dice(1, build_data.map.width - 1);
+            let vy = crate::rng::map_gen::roll_dice(1, build_data.map.height - 1);
             let vidx = build_data.map.xy_idx(vx, vy);
             let candidate = (vidx, rltk::Point::new(vx, vy));
             if !voronoi_seeds.contains(&candidate) {
@@ -120,7 +120,7 @@ impl PlazaMapBuilder {
                     self.fill_zone(build_data, &voronoi_membership, *zone, TileType::Wall)
                 }
                 _ => {
-                    let roll = crate::rng::roll_dice(1, 6);
+                    let roll = crate::rng::map_gen::roll_dice(1, 6);
                     match roll {
                         1 => self.fill_zone(
                             build_data,
@@ -210,7 +210,7 @@ impl PlazaMapBuilder {
             .enumerate()
             .filter(|(_, tile_zone)| **tile_zone == zone)
             .for_each(|(idx, _)| {
-                build_data.map.tiles[idx] = match crate::rng::roll_dice(1, 10) {
+                build_data.map.tiles[idx] = match crate::rng::map_gen::roll_dice(1, 10) {
                     1 => TileType::Stalactite,
                     2 => TileType::Stalagmite,
                     _ => TileType::Grass,
@@ -243,7 +243,7 @@ impl PlazaMapBuilder {
             for x in center.x - 2..=center.x + 2 {
                 let idx = build_data.map.xy_idx(x, y);
                 build_data.map.tiles[idx] = TileType::Road;
-                if crate::rng::roll_dice(1, 6) > 2 {
+                if crate::rng::map_gen::roll_dice(1, 6) > 2 {
                     build_data.map.bloodstains.insert(idx);
                 }
             }
@@ -256,7 +256,7 @@ impl PlazaMapBuilder {
         ));
 
         // And chairs for spectators, and the spectators themselves
-        let available_enemies = match crate::rng::roll_dice(1, 3) {
+        let available_enemies = match crate::rng::map_gen::roll_dice(1, 3) {
             1 => vec!["Arbat Dark Elf", "Arbat Dark Elf Leader", "Arbat Orc Slave"],
             2 => vec!["Barbo Dark Elf", "Barbo Goblin Archer"],
             _ => vec!["Cirro Dark Elf", "Cirro Dark Priestess", "Cirro Spider"],
@@ -264,10 +264,11 @@ impl PlazaMapBuilder {
 
         zone_tiles.iter().for_each(|idx| {
             if build_data.map.tiles[*idx] == TileType::Grass {
-                match crate::rng::roll_dice(1, 10) {
+                match crate::rng::map_gen::roll_dice(1, 10) {
                     1 => build_data.spawn_list.push((*idx, "Chair".to_string())),
                     2 => {
-                        let to_spawn = crate::rng::range(0, available_enemies.len() as i32);
+                        let to_spawn =
+                            crate::rng::map_gen::range(0, available_enemies.len() as i32);
                         build_data
                             .spawn_list
                             .push((*idx, available_enemies[to_spawn as usize].to_string()));
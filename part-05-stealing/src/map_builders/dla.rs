@@ -120,15 +120,17 @@ impl DLABuilder {
         while floor_tile_count < desired_floor_tiles {
             match self.algorithm {
                 DLAAlgorithm::WalkInwards => {
-                    let mut digger_x = crate::rng::roll_dice(1, build_data.map.width - 3) + 1;
-                    let mut digger_y = crate::rng::roll_dice(1, build_data.map.height - 3) + 1;
+                    let mut digger_x =
+                        crate::rng::map_gen::roll_dice(1, build_data.map.width - 3) + 1;
+                    let mut digger_y =
+                        crate::rng::map_gen::roll_dice(1, build_data.map.height - 3) + 1;
                     let mut prev_x = digger_x;
                     let mut prev_y = digger_y;
                     let mut digger_idx = build_data.map.xy_idx(digger_x, digger_y);
                     while build_data.map.tiles[digger_idx] == TileType::Wall {
                         prev_x = digger_x;
                         prev_y = digger_y;
-                        let stagger_direction = crate::rng::roll_dice(1, 4);
+                        let stagger_direction = crate::rng::map_gen::roll_dice(1, 4);
                         match stagger_direction {
                             1 => {
                                 if digger_x > 2 {
@@ -167,7 +169,7 @@ impl DLABuilder {
                     let mut digger_y = starting_position.y;
                     let mut digger_idx = build_data.map.xy_idx(digger_x, digger_y);
                     while build_data.map.tiles[digger_idx] == TileType::Floor {
-                        let stagger_direction = crate::rng::roll_dice(1, 4);
+                        let stagger_direction = crate::rng::map_gen::roll_dice(1, 4);
                         match stagger_direction {
                             1 => {
                                 if digger_x > 2 {
@@ -202,8 +204,10 @@ impl DLABuilder {
                 }
 
                 DLAAlgorithm::CentralAttractor => {
-                    let mut digger_x = crate::rng::roll_dice(1, build_data.map.width - 3) + 1;
-                    let mut digger_y = crate::rng::roll_dice(1, build_data.map.height - 3) + 1;
+                    let mut digger_x =
+                        crate::rng::map_gen::roll_dice(1, build_data.map.width - 3) + 1;
+                    let mut digger_y =
+                        crate::rng::map_gen::roll_dice(1, build_data.map.height - 3) + 1;
                     let mut prev_x = digger_x;
                     let mut prev_y = digger_y;
                     let mut digger_idx = build_data.map.xy_idx(digger_x, digger_y);
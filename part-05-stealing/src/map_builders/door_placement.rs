@@ -73,7 +73,7 @@ impl DoorPlacement {
             for (i, tile) in tiles.iter().enumerate() {
                 if *tile == TileType::Floor
                     && self.door_possible(build_data, i)
-                    && crate::rng::roll_dice(1, 3) == 1
+                    && crate::rng::map_gen::roll_dice(1, 3) == 1
                 {
                     build_data.spawn_list.push((i, "Door".to_string()));
                 }
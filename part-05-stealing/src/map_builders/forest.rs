@@ -101,7 +101,7 @@ impl YellowBrickRoad {
         build_data.take_snapshot();
 
         // Place exit
-        let exit_dir = crate::rng::roll_dice(1, 2);
+        let exit_dir = crate::rng::map_gen::roll_dice(1, 2);
         let (seed_x, seed_y, stream_startx, stream_starty) = if exit_dir == 1 {
             (build_data.map.width - 1, 1, 0, build_data.height - 1)
         } else {
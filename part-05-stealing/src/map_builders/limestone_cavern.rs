@@ -96,9 +96,9 @@ impl CaveDecorator {
         let old_map = build_data.map.clone();
         for (idx, tt) in build_data.map.tiles.iter_mut().enumerate() {
             // Gravel Spawning
-            if *tt == TileType::Floor && crate::rng::roll_dice(1, 6) == 1 {
+            if *tt == TileType::Floor && crate::rng::map_gen::roll_dice(1, 6) == 1 {
                 *tt = TileType::Gravel;
-            } else if *tt == TileType::Floor && crate::rng::roll_dice(1, 10) == 1 {
+            } else if *tt == TileType::Floor && crate::rng::map_gen::roll_dice(1, 10) == 1 {
                 // Spawn passable pools
                 *tt = TileType::ShallowWater;
             } else if *tt == TileType::Wall {
@@ -123,7 +123,7 @@ impl CaveDecorator {
                 if neighbors == 2 {
                     *tt = TileType::DeepWater;
                 } else if neighbors == 1 {
-                    let roll = crate::rng::roll_dice(1, 4);
+                    let roll = crate::rng::map_gen::roll_dice(1, 4);
                     match roll {
                         1 => *tt = TileType::Stalactite,
                         2 => *tt = TileType::Stalagmite,
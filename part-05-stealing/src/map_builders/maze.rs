@@ -135,7 +135,8 @@ impl Grid {
                 return Some(neighbors[0]);
             } else {
                 return Some(
-                    neighbors[(crate::rng::roll_dice(1, neighbors.len() as i32) - 1) as usize],
+                    neighbors
+                        [(crate::rng::map_gen::roll_dice(1, neighbors.len() as i32) - 1) as usize],
                 );
             }
         }
@@ -170,7 +170,7 @@ pub trait MetaMapBuilder {
 
 fn random_start_position() -> (XStart, YStart) {
     let x;
-    let xroll = crate::rng::roll_dice(1, 3);
+    let xroll = crate::rng::map_gen::roll_dice(1, 3);
     match xroll {
         1 => x = XStart::LEFT,
         2 => x = XStart::CENTER,
@@ -178,7 +178,7 @@ fn random_start_position() -> (XStart, YStart) {
     }
 
     let y;
-    let yroll = crate::rng::roll_dice(1, 3);
+    let yroll = crate::rng::map_gen::roll_dice(1, 3);
     match yroll {
         1 => y = YStart::BOTTOM,
         2 => y = YStart::CENTER,
@@ -189,7 +189,7 @@ fn random_start_position() -> (XStart, YStart) {
 }
 
 fn random_room_builder(builder: &mut BuilderChain) {
-    let build_roll = crate::rng::roll_dice(1, 3);
+    let build_roll = crate::rng::map_gen::roll_dice(1, 3);
     match build_roll {
         1 => builder.start_with(SimpleMapBuilder::new()),
         2 => builder.start_with(BspDungeonBuilder::new()),
@@ -199,7 +199,7 @@ fn random_room_builder(builder: &mut BuilderChain) {
     // BSP Interior still makes holes in the walls
     if build_roll != 3 {
         // Sort by one of the 5 available algorithms
-        let sort_roll = crate::rng::roll_dice(1, 5);
+        let sort_roll = crate::rng::map_gen::roll_dice(1, 5);
         match sort_roll {
             1 => builder.with(RoomSorter::new(RoomSort::LEFTMOST)),
             2 => builder.with(RoomSorter::new(RoomSort::RIGHTMOST)),
@@ -210,7 +210,7 @@ fn random_room_builder(builder: &mut BuilderChain) {
 
         builder.with(RoomDrawer::new());
 
-        let corridor_roll = crate::rng::roll_dice(1, 4);
+        let corridor_roll = crate::rng::map_gen::roll_dice(1, 4);
         match corridor_roll {
             1 => builder.with(DoglegCorridors::new()),
             2 => builder.with(NearestCorridors::new()),
@@ -218,12 +218,12 @@ fn random_room_builder(builder: &mut BuilderChain) {
             _ => builder.with(BspCorridors::new()),
         }
 
-        let cspawn_roll = crate::rng::roll_dice(1, 2);
+        let cspawn_roll = crate::rng::map_gen::roll_dice(1, 2);
         if cspawn_roll == 1 {
             builder.with(CorridorSpawner::new());
         }
 
-        let modifier_roll = crate::rng::roll_dice(1, 6);
+        let modifier_roll = crate::rng::map_gen::roll_dice(1, 6);
         match modifier_roll {
             1 => builder.with(RoomExploder::new()),
             2 => builder.with(RoomCornerRounder::new()),
@@ -231,7 +231,7 @@ fn random_room_builder(builder: &mut BuilderChain) {
         }
     }
 
-    let start_roll = crate::rng::roll_dice(1, 2);
+    let start_roll = crate::rng::map_gen::roll_dice(1, 2);
     match start_roll {
         1 => builder.with(RoomBasedStartingPosition::new()),
         _ => {
@@ -240,13 +240,13 @@ fn random_room_builder(builder: &mut BuilderChain) {
         }
     }
 
-    let exit_roll = crate::rng::roll_dice(1, 2);
+    let exit_roll = crate::rng::map_gen::roll_dice(1, 2);
     match exit_roll {
         1 => builder.with(RoomBasedStairs::new()),
         _ => builder.with(DistantExit::new()),
     }
 
-    let spawn_roll = crate::rng::roll_dice(1, 2);
+    let spawn_roll = crate::rng::map_gen::roll_dice(1, 2);
     match spawn_roll {
         1 => builder.with(RoomBasedSpawner::new()),
         _ => builder.with(VoronoiSpawning::new()),
@@ -254,7 +254,7 @@ fn random_room_builder(builder: &mut BuilderChain) {
 }
 
 fn random_shape_builder(builder: &mut BuilderChain) {
-    let builder_roll = crate::rng::roll_dice(1, 16);
+    let builder_roll = crate::rng::map_gen::roll_dice(1, 16);
     match builder_roll {
         1 => builder.start_with(CellularAutomataBuilder::new()),
         2 => builder.start_with(DrunkardsWalkBuilder::open_area()),
@@ -294,13 +294,13 @@ pub fn random_builder(
     show_visualizer: bool,
 ) -> BuilderChain {
     let mut builder = BuilderChain::new(new_depth, width, height, "New Map", show_visualizer);
-    let type_roll = crate::rng::roll_dice(1, 2);
+    let type_roll = crate::rng::map_gen::roll_dice(1, 2);
     match type_roll {
         1 => random_room_builder(&mut builder),
         _ => random_shape_builder(&mut builder),
     }
 
-    if crate::rng::roll_dice(1, 3) == 1 {
+    if crate::rng::map_gen::roll_dice(1, 3) == 1 {
         builder.with(WaveformCollapseBuilder::new());
 
         // Now set the start to a random starting area
@@ -312,7 +312,7 @@ pub fn random_builder(
         builder.with(DistantExit::new());
     }
 
-    if crate::rng::roll_dice(1, 20) == 1 {
+    if crate::rng::map_gen::roll_dice(1, 20) == 1 {
         builder.with(PrefabBuilder::sectional(
             prefab_builder::prefab_sections::UNDERGROUND_FORT,
         ));
@@ -24,7 +24,7 @@ impl RoomExploder {
 
         for room in rooms.iter() {
             let start = room.center();
-            let n_diggers = crate::rng::roll_dice(1, 20) - 5;
+            let n_diggers = crate::rng::map_gen::roll_dice(1, 20) - 5;
             if n_diggers > 0 {
                 for _i in 0..n_diggers {
                     let mut drunk_x = start.0;
@@ -41,7 +41,7 @@ impl RoomExploder {
                         paint(&mut build_data.map, Symmetry::None, 1, drunk_x, drunk_y);
                         build_data.map.tiles[drunk_idx] = TileType::DownStairs;
 
-                        let stagger_direction = crate::rng::roll_dice(1, 4);
+                        let stagger_direction = crate::rng::map_gen::roll_dice(1, 4);
                         match stagger_direction {
                             1 => {
                                 if drunk_x > 2 {
@@ -27,12 +27,14 @@ impl BspCorridors {
         for i in 0..rooms.len() - 1 {
             let room = rooms[i];
             let next_room = rooms[i + 1];
-            let start_x = room.x1 + (crate::rng::roll_dice(1, i32::abs(room.x1 - room.x2)) - 1);
-            let start_y = room.y1 + (crate::rng::roll_dice(1, i32::abs(room.y1 - room.y2)) - 1);
+            let start_x =
+                room.x1 + (crate::rng::map_gen::roll_dice(1, i32::abs(room.x1 - room.x2)) - 1);
+            let start_y =
+                room.y1 + (crate::rng::map_gen::roll_dice(1, i32::abs(room.y1 - room.y2)) - 1);
             let end_x = next_room.x1
-                + (crate::rng::roll_dice(1, i32::abs(next_room.x1 - next_room.x2)) - 1);
+                + (crate::rng::map_gen::roll_dice(1, i32::abs(next_room.x1 - next_room.x2)) - 1);
             let end_y = next_room.y1
-                + (crate::rng::roll_dice(1, i32::abs(next_room.y1 - next_room.y2)) - 1);
+                + (crate::rng::map_gen::roll_dice(1, i32::abs(next_room.y1 - next_room.y2)) - 1);
             let corridor = draw_corridor(&mut build_data.map, start_x, start_y, end_x, end_y);
             corridors.push(corridor);
             build_data.take_snapshot();
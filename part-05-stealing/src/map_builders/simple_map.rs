@@ -22,10 +22,10 @@ impl SimpleMapBuilder {
         let mut rooms: Vec<Rect> = Vec::new();
 
         for _i in 0..MAX_ROOMS {
-            let w = crate::rng::range(MIN_SIZE, MAX_SIZE);
-            let h = crate::rng::range(MIN_SIZE, MAX_SIZE);
-            let x = crate::rng::roll_dice(1, build_data.map.width - w - 1) - 1;
-            let y = crate::rng::roll_dice(1, build_data.map.height - h - 1) - 1;
+            let w = crate::rng::map_gen::range(MIN_SIZE, MAX_SIZE);
+            let h = crate::rng::map_gen::range(MIN_SIZE, MAX_SIZE);
+            let x = crate::rng::map_gen::roll_dice(1, build_data.map.width - w - 1) - 1;
+            let y = crate::rng::map_gen::roll_dice(1, build_data.map.height - h - 1) - 1;
             let new_room = Rect::new(x, y, w, h);
             let mut ok = true;
             for other_room in rooms.iter() {
@@ -1,6 +1,6 @@
 use super::{BuilderChain, BuilderMap, InitialMapBuilder, Position, TileType};
 use crate::rltk;
-use std::collections::HashSet;
+use std::collections::BTreeSet;
 
 pub fn town_builder(
     new_depth: i32,
@@ -90,10 +90,10 @@ impl TownBuilder {
     }
 
     fn water_and_piers(&mut self, build_data: &mut BuilderMap) {
-        let mut n = (crate::rng::roll_dice(1, 65535) as f32) / 65535f32;
+        let mut n = (crate::rng::map_gen::roll_dice(1, 65535) as f32) / 65535f32;
         let mut water_width: Vec<i32> = Vec::new();
         for y in 0..build_data.height {
-            let n_water = (f32::sin(n) * 10.0) as i32 + 14 + crate::rng::roll_dice(1, 6);
+            let n_water = (f32::sin(n) * 10.0) as i32 + 14 + crate::rng::map_gen::roll_dice(1, 6);
             water_width.push(n_water);
             n += 0.1;
             for x in 0..n_water {
@@ -108,9 +108,9 @@ impl TownBuilder {
         build_data.take_snapshot();
 
         // Add piers
-        for _i in 0..crate::rng::roll_dice(1, 4) + 6 {
-            let y = crate::rng::roll_dice(1, build_data.height) - 1;
-            for x in 2 + crate::rng::roll_dice(1, 6)..water_width[y as usize] + 4 {
+        for _i in 0..crate::rng::map_gen::roll_dice(1, 4) + 6 {
+            let y = crate::rng::map_gen::roll_dice(1, build_data.height) - 1;
+            for x in 2 + crate::rng::map_gen::roll_dice(1, 6)..water_width[y as usize] + 4 {
                 let idx = build_data.map.xy_idx(x, y);
                 build_data.map.tiles[idx] = TileType::Bridge;
             }
@@ -118,9 +118,9 @@ impl TownBuilder {
         build_data.take_snapshot();
     }
 
-    fn town_walls(&mut self, build_data: &mut BuilderMap) -> (HashSet<usize>, i32) {
-        let mut available_building_tiles: HashSet<usize> = HashSet::new();
-        let wall_gap_y = crate::rng::roll_dice(1, build_data.height - 9) + 5;
+    fn town_walls(&mut self, build_data: &mut BuilderMap) -> (BTreeSet<usize>, i32) {
+        let mut available_building_tiles: BTreeSet<usize> = BTreeSet::new();
+        let wall_gap_y = crate::rng::map_gen::roll_dice(1, build_data.height - 9) + 5;
         for y in 1..build_data.height - 2 {
             if !(y > wall_gap_y - 4 && y < wall_gap_y + 4) {
                 let idx = build_data.map.xy_idx(30, y);
@@ -158,15 +158,15 @@ impl TownBuilder {
     fn buildings(
         &mut self,
         build_data: &mut BuilderMap,
-        available_building_tiles: &mut HashSet<usize>,
+        available_building_tiles: &mut BTreeSet<usize>,
     ) -> Vec<(i32, i32, i32, i32)> {
         let mut buildings: Vec<(i32, i32, i32, i32)> = Vec::new();
         let mut n_buildings = 0;
         while n_buildings < 12 {
-            let bx = crate::rng::roll_dice(1, build_data.map.width - 32) + 30;
-            let by = crate::rng::roll_dice(1, build_data.map.height) - 2;
-            let bw = crate::rng::roll_dice(1, 8) + 4;
-            let bh = crate::rng::roll_dice(1, 8) + 4;
+            let bx = crate::rng::map_gen::roll_dice(1, build_data.map.width - 32) + 30;
+            let by = crate::rng::map_gen::roll_dice(1, build_data.map.height) - 2;
+            let bw = crate::rng::map_gen::roll_dice(1, 8) + 4;
+            let bh = crate::rng::map_gen::roll_dice(1, 8) + 4;
             let mut possible = true;
             for y in by..by + bh {
                 for x in bx..bx + bw {
@@ -238,7 +238,7 @@ impl TownBuilder {
     ) -> Vec<usize> {
         let mut doors = Vec::new();
         for building in buildings.iter() {
-            let door_x = building.0 + 1 + crate::rng::roll_dice(1, building.2 - 3);
+            let door_x = building.0 + 1 + crate::rng::map_gen::roll_dice(1, building.2 - 3);
             let cy = building.1 + (building.3 / 2);
             let idx = if cy > wall_gap_y {
                 // Door on the north wall
@@ -356,7 +356,7 @@ impl TownBuilder {
                 let idx = build_data.map.xy_idx(x, y);
                 if build_data.map.tiles[idx] == TileType::WoodFloor
                     && idx != player_idx
-                    && crate::rng::roll_dice(1, 3) == 1
+                    && crate::rng::map_gen::roll_dice(1, 3) == 1
                     && !to_place.is_empty()
                 {
                     let entity_tag = to_place[0];
@@ -454,7 +454,7 @@ impl TownBuilder {
                 let idx = build_data.map.xy_idx(x, y);
                 if build_data.map.tiles[idx] == TileType::WoodFloor
                     && idx != 0
-                    && crate::rng::roll_dice(1, 2) == 1
+                    && crate::rng::map_gen::roll_dice(1, 2) == 1
                 {
                     build_data.spawn_list.push((idx, "Rat".to_string()));
                 }
@@ -464,8 +464,8 @@ impl TownBuilder {
 
     fn spawn_dockers(&mut self, build_data: &mut BuilderMap) {
         for (idx, tt) in build_data.map.tiles.iter().enumerate() {
-            if *tt == TileType::Bridge && crate::rng::roll_dice(1, 6) == 1 {
-                let roll = crate::rng::roll_dice(1, 3);
+            if *tt == TileType::Bridge && crate::rng::map_gen::roll_dice(1, 6) == 1 {
+                let roll = crate::rng::map_gen::roll_dice(1, 3);
                 match roll {
                     1 => build_data.spawn_list.push((idx, "Dock Worker".to_string())),
                     2 => build_data
@@ -480,11 +480,11 @@ impl TownBuilder {
     fn spawn_townsfolk(
         &mut self,
         build_data: &mut BuilderMap,
-        available_building_tiles: &mut HashSet<usize>,
+        available_building_tiles: &mut BTreeSet<usize>,
     ) {
         for idx in available_building_tiles.iter() {
-            if crate::rng::roll_dice(1, 10) == 1 {
-                let roll = crate::rng::roll_dice(1, 4);
+            if crate::rng::map_gen::roll_dice(1, 10) == 1 {
+                let roll = crate::rng::map_gen::roll_dice(1, 4);
                 match roll {
                     1 => build_data.spawn_list.push((*idx, "Peasant".to_string())),
                     2 => build_data.spawn_list.push((*idx, "Drunk".to_string())),
@@ -500,10 +500,10 @@ impl TownBuilder {
     fn spawn_town_guards(
         &mut self,
         build_data: &mut BuilderMap,
-        available_building_tiles: &mut HashSet<usize>,
+        available_building_tiles: &mut BTreeSet<usize>,
     ) {
         for idx in available_building_tiles.iter() {
-            if crate::rng::roll_dice(1, 200) == 1 {
+            if crate::rng::map_gen::roll_dice(1, 200) == 1 {
                 build_data.spawn_list.push((*idx, "Town Guard".to_string()));
             }
         }
@@ -52,8 +52,8 @@ impl VoronoiCellBuilder {
         let mut voronoi_seeds: Vec<(usize, rltk::Point)> = Vec::new();
 
         while voronoi_seeds.len() < self.n_seeds {
-            let vx = crate::rng::roll_dice(1, build_data.map.width - 1);
-            let vy = crate::rng::roll_dice(1, build_data.map.height - 1);
+            let vx = crate::rng::map_gen::roll_dice(1, build_data.map.width - 1);
+            let vy = crate::rng::map_gen::roll_dice(1, build_data.map.height - 1);
             let vidx = build_data.map.xy_idx(vx, vy);
             let candidate = (vidx, rltk::Point::new(vx, vy));
             if !voronoi_seeds.contains(&candidate) {
@@ -1,6 +1,6 @@
 use super::{spawner, BuilderMap, MetaMapBuilder, TileType};
 use crate::rltk;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 pub struct VoronoiSpawning {}
 
@@ -18,8 +18,8 @@ impl VoronoiSpawning {
 
     #[allow(clippy::map_entry)]
     fn build(&mut self, build_data: &mut BuilderMap) {
-        let mut noise_areas: HashMap<i32, Vec<usize>> = HashMap::new();
-        let mut noise = rltk::FastNoise::seeded(crate::rng::roll_dice(1, 65536) as u64);
+        let mut noise_areas: BTreeMap<i32, Vec<usize>> = BTreeMap::new();
+        let mut noise = rltk::FastNoise::seeded(crate::rng::map_gen::roll_dice(1, 65536) as u64);
         noise.set_noise_type(rltk::NoiseType::Cellular);
         noise.set_frequency(0.08);
         noise.set_cellular_distance_function(rltk::CellularDistanceFunction::Manhattan);
@@ -69,8 +69,9 @@ pub fn build_patterns(
             "Pre de-duplication, there are {} patterns",
             patterns.len()
         ));
-        let set: HashSet<Vec<TileType>> = patterns.drain(..).collect(); // dedup
-        patterns.extend(set.into_iter());
+        // Keep the first copy of each pattern, so the order doesn't depend on hashing
+        let mut seen: HashSet<Vec<TileType>> = HashSet::new();
+        patterns.retain(|pattern| seen.insert(pattern.clone()));
         rltk::console::log(format!("There are {} patterns", patterns.len()));
     }
 
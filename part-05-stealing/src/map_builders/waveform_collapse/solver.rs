@@ -1,6 +1,6 @@
 use super::{Map, MapChunk};
 use crate::rltk;
-use std::collections::HashSet;
+use std::collections::BTreeSet;
 
 pub struct Solver {
     constraints: Vec<MapChunk>,
@@ -104,7 +104,7 @@ impl Solver {
 
         // Pick a random chunk we haven't dealt with yet and get its index, remove from remaining list
         let remaining_index = if !neighbors_exist {
-            (crate::rng::roll_dice(1, self.remaining.len() as i32) - 1) as usize
+            (crate::rng::map_gen::roll_dice(1, self.remaining.len() as i32) - 1) as usize
         } else {
             0usize
         };
@@ -164,7 +164,7 @@ impl Solver {
         if neighbors == 0 {
             // There is nothing nearby, so we can have anything!
             let new_chunk_idx =
-                (crate::rng::roll_dice(1, self.constraints.len() as i32) - 1) as usize;
+                (crate::rng::map_gen::roll_dice(1, self.constraints.len() as i32) - 1) as usize;
             self.chunks[chunk_index] = Some(new_chunk_idx);
             let left_x = chunk_x as i32 * self.chunk_size as i32;
             let right_x = (chunk_x as i32 + 1) * self.chunk_size as i32;
@@ -182,7 +182,7 @@ impl Solver {
             }
         } else {
             // There are neighbors, so we try to be compatible with them
-            let mut options_to_check: HashSet<usize> = HashSet::new();
+            let mut options_to_check: BTreeSet<usize> = BTreeSet::new();
             for o in options.iter() {
                 for i in o.iter() {
                     options_to_check.insert(*i);
@@ -210,7 +210,7 @@ impl Solver {
                 let new_chunk_idx = if possible_options.len() == 1 {
                     0
                 } else {
-                    crate::rng::roll_dice(1, possible_options.len() as i32) - 1
+                    crate::rng::map_gen::roll_dice(1, possible_options.len() as i32) - 1
                 };
 
                 self.chunks[chunk_index] = Some(possible_options[new_chunk_idx as usize]);
@@ -0,0 +1,110 @@
+use crate::keymap::Keymap;
+use crate::saveload_system::SaveFormatKind;
+use crate::systems::sound_system::clamp_volume;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+
+const OPTIONS_FILE: &str = "options.json";
+const CONFIG_DIRECTORY_NAME: &str = "rusty-roguelike";
+/// How far one press of Left or Right moves a volume in the options menu.
+pub const VOLUME_STEP: f32 = 0.1;
+
+/// Settings that outlive a run, kept in the player's config directory. Missing fields take
+/// their defaults, so older files keep working as options are added.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct GameOptions {
+    pub show_fps: bool,
+    pub show_map_visualizer: bool,
+    pub show_cheat_menu: bool,
+    /// Whether exploring picks up the items it finds.
+    pub auto_pickup: bool,
+    pub save_format: SaveFormatKind,
+    pub background_volume: f32,
+    pub effects_volume: f32,
+    pub keys: Keymap,
+}
+
+impl Default for GameOptions {
+    fn default() -> Self {
+        GameOptions {
+            show_fps: true,
+            show_map_visualizer: false,
+            show_cheat_menu: true,
+            auto_pickup: false,
+            save_format: SaveFormatKind::Binary,
+            background_volume: 1.0,
+            effects_volume: 1.0,
+            keys: Keymap::default(),
+        }
+    }
+}
+
+impl GameOptions {
+    /// Brings hand edited values back into range.
+    fn validate(mut self) -> GameOptions {
+        self.background_volume = clamp_volume(self.background_volume);
+        self.effects_volume = clamp_volume(self.effects_volume);
+        self.keys.restore_essential();
+        self
+    }
+}
+
+/// Follows each platform's convention for where config lives, falling back to the working
+/// directory when there's nowhere better.
+fn config_directory() -> PathBuf {
+    let base = if cfg!(windows) {
+        env::var_os("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        env::var_os("HOME").map(|home| PathBuf::from(home).join("Library/Application Support"))
+    } else {
+        env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+    };
+    base.map_or_else(
+        || PathBuf::from("."),
+        |base| base.join(CONFIG_DIRECTORY_NAME),
+    )
+}
+
+pub fn options_path() -> PathBuf {
+    config_directory().join(OPTIONS_FILE)
+}
+
+/// Reads the options file. A missing file gives the defaults; so does a damaged one, which is
+/// moved aside so the player can see what went wrong.
+pub fn load_options() -> GameOptions {
+    let path = options_path();
+    let data = match fs::read_to_string(&path) {
+        Ok(data) => data,
+        Err(_) => return GameOptions::default(),
+    };
+    match serde_json::from_str::<GameOptions>(&data) {
+        Ok(options) => options.validate(),
+        Err(e) => {
+            eprintln!(
+                "{} is damaged ({}), using the default options",
+                path.display(),
+                e
+            );
+            let _ = fs::rename(&path, path.with_extension("json.bad"));
+            GameOptions::default()
+        }
+    }
+}
+
+pub fn save_options(options: &GameOptions) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let path = options_path();
+    if let Some(directory) = path.parent() {
+        fs::create_dir_all(directory)?;
+    }
+    // Write beside the file and rename over it, so a failed write leaves the old options intact
+    let temp_path = path.with_extension("tmp");
+    fs::write(&temp_path, serde_json::to_vec_pretty(options)?)?;
+    fs::rename(&temp_path, &path)?;
+    Ok(())
+}
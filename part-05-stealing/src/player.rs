@@ -1,10 +1,11 @@
 use crate::{gui, Equipped, Name, Target, WantsToCastSpell, WantsToShoot, Weapon, Player};
 
 use super::{
-    raws::Reaction, Attributes, BlocksTile, BlocksVisibility, Door, EntityMoved, Faction, Item,
-    Map, Pools, Position, Renderable, RunState, State, TileType, Vendor, VendorMode, Viewshed,
-    WantsToMelee, WantsToPickupItem,
+    raws::Reaction, Attributes, BlocksTile, BlocksVisibility, CrimeRecord, Door, EntityMoved,
+    Faction, Item, Map, Pools, Position, Renderable, RunState, State, TileType, Vendor, VendorMode,
+    Viewshed, WantsToMelee, WantsToPickupItem,
 };
+use crate::keymap::{Action, KeyContext, Keymap};
 use crate::rltk;
 use specs::prelude::*;
 use std::cmp::{max, min};
@@ -23,8 +24,10 @@ pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) -> RunState
     let mut blocks_movement = ecs.write_storage::<BlocksTile>();
     let mut renderables = ecs.write_storage::<Renderable>();
     let factions = ecs.read_storage::<Faction>();
+    let crime_records = ecs.read_storage::<CrimeRecord>();
     let mut result = RunState::AwaitingInput;
     let vendors = ecs.read_storage::<Vendor>();
+    let names = ecs.read_storage::<Name>();
 
     let player_entity = ecs.fetch::<Entity>();
     let mut swap_entities: Vec<(Entity, i32, i32)> = Vec::new();
@@ -47,14 +50,33 @@ pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) -> RunState
             destination_idx,
             |potential_target| {
                 let target_reaction = if let Some(faction) = factions.get(potential_target) {
-                    crate::raws::faction_reaction(
+                    crate::crime::reaction(
                         &faction.name,
+                        *player_entity,
                         &player_faction.name,
-                        &crate::raws::RAWS.lock().unwrap(),
+                        &crime_records,
+                        map.depth,
                     )
                 } else {
                     Reaction::Ignore
                 };
+                let collects_fines = names
+                    .get(potential_target)
+                    .is_some_and(|name| crate::crime::collects_fines(&name.name));
+                // Guards who are after the player fight them instead; a priest still takes the fine
+                if collects_fines
+                    && target_reaction != Reaction::Attack
+                    && crate::crime::fine_owed(
+                        factions.get(*player_entity),
+                        crime_records.get(*player_entity),
+                        map.depth,
+                    )
+                    .is_some()
+                {
+                    return Some(RunState::ShowFine {
+                        collector: potential_target,
+                    });
+                }
                 if let Some(_vendor) = vendors.get(potential_target) {
                     if target_reaction == Reaction::Ignore {
                         return Some(RunState::ShowVendor {
@@ -98,6 +120,15 @@ pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) -> RunState
                 let door = doors.get_mut(potential_target);
                 if let Some(door) = door {
                     door.open = true;
+                    crate::effects::add_effect(
+                        None,
+                        crate::effects::EffectType::Sound {
+                            event: "door_open".to_string(),
+                        },
+                        crate::effects::Targets::Single {
+                            target: potential_target,
+                        },
+                    );
                     blocks_visibility.remove(potential_target);
                     blocks_movement.remove(potential_target);
                     let glyph = renderables.get_mut(potential_target).unwrap();
@@ -155,6 +186,7 @@ pub fn try_next_level(ecs: &mut World) -> bool {
         true
     } else {
         crate::gamelog::Logger::new()
+            .category(crate::gamelog::LogCategory::System)
             .append("There is no way down from here.")
             .log();
         false
@@ -169,13 +201,14 @@ pub fn try_previous_level(ecs: &mut World) -> bool {
         true
     } else {
         crate::gamelog::Logger::new()
+            .category(crate::gamelog::LogCategory::System)
             .append("There is no way up from here.")
             .log();
         false
     }
 }
 
-fn get_item(ecs: &mut World) {
+pub fn get_item(ecs: &mut World) {
     let player_pos = ecs.fetch::<rltk::Point>();
     let player_entity = ecs.fetch::<Entity>();
     let entities = ecs.entities();
@@ -191,6 +224,7 @@ fn get_item(ecs: &mut World) {
 
     match target_item {
         None => crate::gamelog::Logger::new()
+            .category(crate::gamelog::LogCategory::Loot)
             .append("There is nothing here to pick up.")
             .log(),
         Some(item) => {
@@ -224,11 +258,7 @@ fn skip_turn(ecs: &mut World) -> RunState {
             match faction {
                 None => {}
                 Some(faction) => {
-                    let reaction = crate::raws::faction_reaction(
-                        &faction.name,
-                        "Player",
-                        &crate::raws::RAWS.lock().unwrap(),
-                    );
+                    let reaction = crate::crime::reaction_to_player(ecs, &faction.name);
                     if reaction == Reaction::Attack {
                         can_heal = false;
                     }
@@ -241,7 +271,7 @@ fn skip_turn(ecs: &mut World) -> RunState {
         let mut health_components = ecs.write_storage::<Pools>();
         let pools = health_components.get_mut(*player_entity).unwrap();
         pools.hit_points.current = i32::min(pools.hit_points.current + 1, pools.hit_points.max);
-        if crate::rng::roll_dice(1, 6) == 1 {
+        if crate::rng::combat::roll_dice(1, 6) == 1 {
             pools.mana.current = i32::min(pools.mana.current + 1, pools.mana.max);
         }
     }
@@ -274,12 +304,7 @@ fn get_player_target_list(ecs: &mut World) -> Vec<(f32, Entity)> {
                                 && factions.get(possible_target).is_some()
                             {
                                 let faction = factions.get(possible_target).unwrap();
-                                let player_faction = factions.get(*player_entity).unwrap();
-                                let reaction = crate::raws::faction_reaction(
-                                    &faction.name,
-                                    &player_faction.name,
-                                    &crate::raws::RAWS.lock().unwrap(),
-                                );
+                                let reaction = crate::crime::reaction_to_player(ecs, &faction.name);
                                 if reaction == Reaction::Attack {
                                     possible_targets.push((distance_to_target, possible_target));
                                 }
@@ -343,6 +368,7 @@ fn fire_on_target(ecs: &mut World) -> RunState {
         let names = ecs.read_storage::<Name>();
         if let Some(name) = names.get(target) {
             crate::gamelog::Logger::new()
+                .category(crate::gamelog::LogCategory::Combat)
                 .append("You fire at")
                 .color(rltk::CYAN)
                 .append(&name.name)
@@ -354,7 +380,10 @@ fn fire_on_target(ecs: &mut World) -> RunState {
 
         RunState::Ticking
     } else {
-        crate::gamelog::Logger::new().append("You don't have a target selected!");
+        crate::gamelog::Logger::new()
+            .category(crate::gamelog::LogCategory::Combat)
+            .append("You don't have a target selected!")
+            .log();
         RunState::AwaitingInput
     }
 }
@@ -448,6 +477,7 @@ fn use_spell_hotkey(gs: &mut State, key: i32) -> RunState {
             }
         } else {
             crate::gamelog::Logger::new()
+                .category(crate::gamelog::LogCategory::Combat)
                 .append("You don't have enough mana to cast that!")
                 .log();
         }
@@ -456,122 +486,158 @@ fn use_spell_hotkey(gs: &mut State, key: i32) -> RunState {
     RunState::Ticking
 }
 
-pub fn player_input(gs: &mut State, ctx: &mut rltk::BTerm) -> RunState {
-    // Hotkeys
-    if ctx.shift && ctx.key.is_some() {
-        let key: Option<i32> = match ctx.key.unwrap() {
-            rltk::VirtualKeyCode::Key1 => Some(1),
-            rltk::VirtualKeyCode::Key2 => Some(2),
-            rltk::VirtualKeyCode::Key3 => Some(3),
-            rltk::VirtualKeyCode::Key4 => Some(4),
-            rltk::VirtualKeyCode::Key5 => Some(5),
-            rltk::VirtualKeyCode::Key6 => Some(6),
-            rltk::VirtualKeyCode::Key7 => Some(7),
-            rltk::VirtualKeyCode::Key8 => Some(8),
-            rltk::VirtualKeyCode::Key9 => Some(9),
-            _ => None,
-        };
-        if let Some(key) = key {
-            return use_consumable_hotkey(gs, key - 1);
-        }
-    }
-    if ctx.control && ctx.key.is_some() {
-        let key: Option<i32> = match ctx.key.unwrap() {
-            rltk::VirtualKeyCode::Key1 => Some(1),
-            rltk::VirtualKeyCode::Key2 => Some(2),
-            rltk::VirtualKeyCode::Key3 => Some(3),
-            rltk::VirtualKeyCode::Key4 => Some(4),
-            rltk::VirtualKeyCode::Key5 => Some(5),
-            rltk::VirtualKeyCode::Key6 => Some(6),
-            rltk::VirtualKeyCode::Key7 => Some(7),
-            rltk::VirtualKeyCode::Key8 => Some(8),
-            rltk::VirtualKeyCode::Key9 => Some(9),
-            _ => None,
-        };
-        if let Some(key) = key {
-            return use_spell_hotkey(gs, key - 1);
+/// The keys `player_input` reacts to, read from the terminal or from a script.
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub struct KeyPress {
+    pub key: Option<rltk::VirtualKeyCode>,
+    pub shift: bool,
+    pub control: bool,
+}
+
+impl KeyPress {
+    pub fn from_ctx(ctx: &rltk::BTerm) -> KeyPress {
+        KeyPress {
+            key: ctx.key,
+            shift: ctx.shift,
+            control: ctx.control,
         }
     }
+}
 
-    // Player movement
-    match ctx.key {
-        None => return RunState::AwaitingInput, // Nothing happened
-        Some(key) => match key {
-            rltk::VirtualKeyCode::Left
-            | rltk::VirtualKeyCode::Numpad4
-            | rltk::VirtualKeyCode::H => return try_move_player(-1, 0, &mut gs.ecs),
-
-            rltk::VirtualKeyCode::Right
-            | rltk::VirtualKeyCode::Numpad6
-            | rltk::VirtualKeyCode::L => return try_move_player(1, 0, &mut gs.ecs),
-
-            rltk::VirtualKeyCode::Up | rltk::VirtualKeyCode::Numpad8 | rltk::VirtualKeyCode::K => {
-                return try_move_player(0, -1, &mut gs.ecs)
-            }
-
-            rltk::VirtualKeyCode::Down
-            | rltk::VirtualKeyCode::Numpad2
-            | rltk::VirtualKeyCode::J => return try_move_player(0, 1, &mut gs.ecs),
-
-            // Diagonals
-            rltk::VirtualKeyCode::Numpad9 | rltk::VirtualKeyCode::U => {
-                return try_move_player(1, -1, &mut gs.ecs)
-            }
+/// Sets the player off on a walk, if there is one to take.
+fn start_travel(gs: &mut State, travel: Option<crate::travel::Travel>) -> RunState {
+    gs.travel = travel;
+    if gs.travel.is_some() {
+        RunState::Travelling
+    } else {
+        RunState::AwaitingInput
+    }
+}
 
-            rltk::VirtualKeyCode::Numpad7 | rltk::VirtualKeyCode::Y => {
-                return try_move_player(-1, -1, &mut gs.ecs)
-            }
+pub fn player_input(gs: &mut State, input: KeyPress) -> RunState {
+    let (action, run_direction) = {
+        let keymap = gs.ecs.fetch::<Keymap>();
+        (
+            keymap.action(KeyContext::Game, input),
+            keymap.run_direction(input),
+        )
+    };
+    let action = match (action, run_direction) {
+        (Some(action), _) => action,
+        // Shift and a movement key runs
+        (None, Some(direction)) => {
+            let run = crate::travel::Travel::run(&gs.ecs, direction);
+            return start_travel(gs, run);
+        }
+        (None, None) => return RunState::AwaitingInput, // Nothing happened
+    };
 
-            rltk::VirtualKeyCode::Numpad3 | rltk::VirtualKeyCode::N => {
-                return try_move_player(1, 1, &mut gs.ecs)
+    match action {
+        // Player movement
+        Action::MoveWest => return try_move_player(-1, 0, &mut gs.ecs),
+        Action::MoveEast => return try_move_player(1, 0, &mut gs.ecs),
+        Action::MoveNorth => return try_move_player(0, -1, &mut gs.ecs),
+        Action::MoveSouth => return try_move_player(0, 1, &mut gs.ecs),
+
+        // Diagonals
+        Action::MoveNorthEast => return try_move_player(1, -1, &mut gs.ecs),
+        Action::MoveNorthWest => return try_move_player(-1, -1, &mut gs.ecs),
+        Action::MoveSouthEast => return try_move_player(1, 1, &mut gs.ecs),
+        Action::MoveSouthWest => return try_move_player(-1, 1, &mut gs.ecs),
+
+        // Skip Turn
+        Action::Wait => return skip_turn(&mut gs.ecs),
+
+        // Level changes
+        Action::Descend => {
+            if try_next_level(&mut gs.ecs) {
+                return RunState::NextLevel;
             }
-
-            rltk::VirtualKeyCode::Numpad1 | rltk::VirtualKeyCode::B => {
-                return try_move_player(-1, 1, &mut gs.ecs)
+        }
+        Action::Ascend => {
+            if try_previous_level(&mut gs.ecs) {
+                return RunState::PreviousLevel;
             }
+        }
 
-            // Skip Turn
-            rltk::VirtualKeyCode::Numpad5 | rltk::VirtualKeyCode::Space => {
-                return skip_turn(&mut gs.ecs)
-            }
+        // Picking up items
+        Action::PickUp => get_item(&mut gs.ecs),
+        Action::Inventory => return RunState::ShowInventory { page: 0 },
+        Action::Drop => return RunState::ShowDropItem { page: 0 },
+        Action::RemoveItem => return RunState::ShowRemoveItem { page: 0 },
 
-            // Level changes
-            rltk::VirtualKeyCode::Period => {
-                if try_next_level(&mut gs.ecs) {
-                    return RunState::NextLevel;
-                }
-            }
-            rltk::VirtualKeyCode::Comma => {
-                if try_previous_level(&mut gs.ecs) {
-                    return RunState::PreviousLevel;
-                }
+        // Main Menu
+        Action::GameMenu => {
+            return RunState::MainMenu {
+                menu_selection: gui::MainMenuSelection::SaveGame,
             }
+        }
 
-            // Picking up items
-            rltk::VirtualKeyCode::G => get_item(&mut gs.ecs),
-            rltk::VirtualKeyCode::I => return RunState::ShowInventory { page: 0 },
-            rltk::VirtualKeyCode::D => return RunState::ShowDropItem { page: 0 },
-            rltk::VirtualKeyCode::R => return RunState::ShowRemoveItem { page: 0 },
+        // Cheating!
+        Action::CheatMenu => return RunState::ShowCheatMenu,
 
-            // Main Menu
-            rltk::VirtualKeyCode::Escape => {
-                return RunState::MainMenu {
-                    menu_selection: gui::MainMenuSelection::SaveGame,
-                }
-            }
+        // Ranged
+        Action::CycleTarget => {
+            cycle_target(&mut gs.ecs);
+            return RunState::AwaitingInput;
+        }
+        Action::Fire => return fire_on_target(&mut gs.ecs),
+
+        // Looking around
+        Action::Look => {
+            let player_pos = *gs.ecs.fetch::<rltk::Point>();
+            return RunState::Looking {
+                x: player_pos.x,
+                y: player_pos.y,
+                index: 0,
+            };
+        }
 
-            // Cheating!
-            rltk::VirtualKeyCode::Backslash => return RunState::ShowCheatMenu,
+        // Exploring
+        Action::AutoExplore => {
+            let explore = crate::travel::Travel::explore(&gs.ecs, gs.game_options.auto_pickup);
+            return start_travel(gs, explore);
+        }
+        Action::TravelDownStairs => {
+            let travel = crate::travel::Travel::to_stairs(&gs.ecs, TileType::DownStairs);
+            return start_travel(gs, travel);
+        }
+        Action::TravelUpStairs => {
+            let travel = crate::travel::Travel::to_stairs(&gs.ecs, TileType::UpStairs);
+            return start_travel(gs, travel);
+        }
 
-            // Ranged
-            rltk::VirtualKeyCode::V => {
-                cycle_target(&mut gs.ecs);
-                return RunState::AwaitingInput;
-            }
-            rltk::VirtualKeyCode::F => return fire_on_target(&mut gs.ecs),
+        // Message history
+        Action::MessageLog => {
+            gs.log_search.clear();
+            return RunState::MessageLog {
+                scroll: 0,
+                category: None,
+                searching: false,
+            };
+        }
 
-            _ => return RunState::AwaitingInput,
-        },
+        // Hotkeys
+        Action::UseItem1 => return use_consumable_hotkey(gs, 0),
+        Action::UseItem2 => return use_consumable_hotkey(gs, 1),
+        Action::UseItem3 => return use_consumable_hotkey(gs, 2),
+        Action::UseItem4 => return use_consumable_hotkey(gs, 3),
+        Action::UseItem5 => return use_consumable_hotkey(gs, 4),
+        Action::UseItem6 => return use_consumable_hotkey(gs, 5),
+        Action::UseItem7 => return use_consumable_hotkey(gs, 6),
+        Action::UseItem8 => return use_consumable_hotkey(gs, 7),
+        Action::UseItem9 => return use_consumable_hotkey(gs, 8),
+        Action::CastSpell1 => return use_spell_hotkey(gs, 0),
+        Action::CastSpell2 => return use_spell_hotkey(gs, 1),
+        Action::CastSpell3 => return use_spell_hotkey(gs, 2),
+        Action::CastSpell4 => return use_spell_hotkey(gs, 3),
+        Action::CastSpell5 => return use_spell_hotkey(gs, 4),
+        Action::CastSpell6 => return use_spell_hotkey(gs, 5),
+        Action::CastSpell7 => return use_spell_hotkey(gs, 6),
+        Action::CastSpell8 => return use_spell_hotkey(gs, 7),
+        Action::CastSpell9 => return use_spell_hotkey(gs, 8),
+
+        _ => return RunState::AwaitingInput,
     }
     RunState::Ticking
 }
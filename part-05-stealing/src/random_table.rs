@@ -39,7 +39,7 @@ impl MasterTable {
     }
 
     pub fn roll(&self) -> String {
-        let roll = crate::rng::roll_dice(1, 4);
+        let roll = crate::rng::loot::roll_dice(1, 4);
         match roll {
             1 => self.items.roll(),
             2 => self.props.roll(),
@@ -75,7 +75,7 @@ impl RandomTable {
         if self.total_weight == 0 {
             return "None".to_string();
         }
-        let mut roll = crate::rng::roll_dice(1, self.total_weight) - 1;
+        let mut roll = crate::rng::loot::roll_dice(1, self.total_weight) - 1;
         let mut index: usize = 0;
 
         while roll > 0 {
@@ -25,6 +25,7 @@ pub struct Mob {
     pub vendor: Option<Vec<String>>,
     pub abilities: Option<Vec<MobAbility>>,
     pub on_death: Option<Vec<MobAbility>>,
+    pub sound_events: Option<HashMap<String, Vec<String>>>,
 }
 
 #[derive(Deserialize, Debug)]
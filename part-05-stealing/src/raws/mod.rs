@@ -14,12 +14,15 @@ mod spell_structs;
 pub use spell_structs::Spell;
 mod weapon_traits;
 pub use weapon_traits::*;
+mod music_structs;
+use music_structs::*;
 
 mod rawmaster;
 use crate::rltk;
 use crate::rltk::EMBED;
 pub use rawmaster::*;
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::sync::Mutex;
 
 rltk::embedded_resource!(RAW_FILE, "../../raws/spawns.json");
@@ -38,6 +41,10 @@ pub struct Raws {
     pub faction_table: Vec<FactionInfo>,
     pub spells: Vec<Spell>,
     pub weapon_traits: Vec<WeaponTrait>,
+    #[serde(default)]
+    pub music: MusicTable,
+    #[serde(default)]
+    pub sound_events: HashMap<String, Vec<String>>,
 }
 
 pub fn load_raws() {
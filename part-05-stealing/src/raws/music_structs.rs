@@ -0,0 +1,20 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+
+#[derive(Deserialize, Debug, Default)]
+pub struct MusicTable {
+    pub default: String,
+    #[serde(default)]
+    pub tracks: Vec<MusicTrack>,
+    pub combat: Option<String>,
+    #[serde(default)]
+    pub stingers: HashMap<String, String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct MusicTrack {
+    pub name: String,
+    pub file: String,
+    pub min_depth: i32,
+    pub max_depth: i32,
+}
@@ -1,4 +1,4 @@
-use super::{faction_structs::Reaction, Raws};
+use super::{faction_structs::Reaction, music_structs::MusicTable, Raws};
 use crate::components::*;
 use crate::random_table::{MasterTable, RandomTable};
 use crate::rltk;
@@ -63,6 +63,8 @@ impl RawMaster {
                 faction_table: Vec::new(),
                 spells: Vec::new(),
                 weapon_traits: Vec::new(),
+                music: MusicTable::default(),
+                sound_events: HashMap::new(),
             },
             item_index: HashMap::new(),
             mob_index: HashMap::new(),
@@ -776,7 +778,7 @@ pub fn spawn_named_mob(
             total_initiative_penalty: 0.0,
             gold: if let Some(gold) = &mob_template.gold {
                 let (n, d, b) = parse_dice_string(&gold);
-                (crate::rng::roll_dice(n, d) + b) as f32
+                (crate::rng::loot::roll_dice(n, d) + b) as f32
             } else {
                 0.0
             },
@@ -864,6 +866,7 @@ pub fn spawn_named_mob(
         if let Some(vendor) = &mob_template.vendor {
             eb = eb.with(Vendor {
                 categories: vendor.clone(),
+                last_restock: None,
             });
         }
 
@@ -1093,6 +1096,39 @@ pub fn get_spawn_table_for_depth(raws: &RawMaster, depth: i32) -> MasterTable {
     rt
 }
 
+pub fn get_music_for_depth(raws: &RawMaster, depth: i32) -> String {
+    raws.raws
+        .music
+        .tracks
+        .iter()
+        .find(|track| depth >= track.min_depth && depth <= track.max_depth)
+        .map_or_else(|| get_default_music(raws), |track| track.file.clone())
+}
+
+pub fn get_default_music(raws: &RawMaster) -> String {
+    raws.raws.music.default.clone()
+}
+
+pub fn get_combat_music(raws: &RawMaster) -> Option<String> {
+    raws.raws.music.combat.clone()
+}
+
+pub fn get_stinger(raws: &RawMaster, cue: &str) -> Option<String> {
+    raws.raws.music.stingers.get(cue).cloned()
+}
+
+/// The sounds that can play for `event`. A mob's own sounds replace the shared ones.
+pub fn get_event_sounds(raws: &RawMaster, event: &str, mob_name: Option<&str>) -> Vec<String> {
+    let mob_sounds = mob_name
+        .and_then(|name| raws.mob_index.get(name))
+        .and_then(|index| raws.raws.mobs[*index].sound_events.as_ref())
+        .and_then(|events| events.get(event));
+    mob_sounds
+        .or_else(|| raws.raws.sound_events.get(event))
+        .cloned()
+        .unwrap_or_default()
+}
+
 pub fn get_item_drop(raws: &RawMaster, table: &str) -> Option<String> {
     if raws.loot_index.contains_key(table) {
         let mut rt = RandomTable::new();
@@ -0,0 +1,214 @@
+use crate::gui::MainMenuSelection;
+use crate::key_names::{key_from_name, key_name};
+use crate::keymap::Keymap;
+use crate::rltk;
+use crate::saveload_system::{write_atomically, RunInfo};
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const REPLAY_DIRECTORY: &str = "./replays";
+const REPLAY_VERSION: u32 = 1;
+
+/// The input for one frame of a recorded run. Frames where nothing happened aren't stored.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ReplayFrame {
+    pub frame: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    #[serde(default)]
+    pub shift: bool,
+    #[serde(default)]
+    pub control: bool,
+    #[serde(default)]
+    pub mouse: (i32, i32),
+    #[serde(default)]
+    pub left_click: bool,
+    #[serde(default)]
+    pub right_click: bool,
+    /// Only kept while something runs on the clock (particles, the map visualizer).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub frame_time_ms: Option<f32>,
+    /// Main menu choices are stored by meaning, since the menu's entries depend on what is on
+    /// disk.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub menu: Option<MainMenuSelection>,
+}
+
+/// A run, from its first turn, as the seed it was built from and the input that played it.
+#[derive(Serialize, Deserialize)]
+pub struct Replay {
+    pub version: u32,
+    pub seed: u64,
+    pub ironman: bool,
+    pub show_map_visualizer: bool,
+    pub show_cheat_menu: bool,
+    #[serde(default)]
+    pub auto_pickup: bool,
+    /// The key bindings at the start of the run. Recordings from before keys could be rebound
+    /// were made with the defaults.
+    #[serde(default)]
+    pub keys: Keymap,
+    pub frames: Vec<ReplayFrame>,
+}
+
+pub struct Recorder {
+    path: PathBuf,
+    replay: Replay,
+    frame: u64,
+}
+
+impl Recorder {
+    pub fn new(
+        run: RunInfo,
+        seed: u64,
+        show_map_visualizer: bool,
+        show_cheat_menu: bool,
+        auto_pickup: bool,
+        keys: Keymap,
+    ) -> Recorder {
+        Recorder {
+            path: Path::new(REPLAY_DIRECTORY).join(format!("run_{:016x}.json", run.run_id)),
+            replay: Replay {
+                version: REPLAY_VERSION,
+                seed,
+                ironman: run.ironman,
+                show_map_visualizer,
+                show_cheat_menu,
+                auto_pickup,
+                keys,
+                frames: Vec::new(),
+            },
+            frame: 0,
+        }
+    }
+
+    /// Records the input for this frame. `timed` is set when the frame's length matters to the
+    /// game, so it is kept too. Right clicks aren't on `ctx`, so they are passed in.
+    pub fn record_frame(&mut self, ctx: &rltk::BTerm, right_click: bool, timed: bool) {
+        let key = ctx.key.and_then(key_name);
+        if key.is_some() || ctx.left_click || right_click || timed {
+            self.replay.frames.push(ReplayFrame {
+                frame: self.frame,
+                key: key.map(str::to_string),
+                shift: ctx.shift,
+                control: ctx.control,
+                mouse: ctx.mouse_pos,
+                left_click: ctx.left_click,
+                right_click,
+                frame_time_ms: if timed { Some(ctx.frame_time_ms) } else { None },
+                menu: None,
+            });
+        }
+        self.frame += 1;
+    }
+
+    /// Records a main menu choice made during the frame that was just recorded.
+    pub fn record_menu(&mut self, selection: MainMenuSelection) {
+        let frame = self.frame.saturating_sub(1);
+        match self.replay.frames.last_mut() {
+            Some(recorded) if recorded.frame == frame => recorded.menu = Some(selection),
+            _ => self.replay.frames.push(ReplayFrame {
+                frame,
+                key: None,
+                shift: false,
+                control: false,
+                mouse: (0, 0),
+                left_click: false,
+                right_click: false,
+                frame_time_ms: None,
+                menu: Some(selection),
+            }),
+        }
+    }
+
+    pub fn save(&self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        fs::create_dir_all(REPLAY_DIRECTORY)?;
+        write_atomically(&self.path, &serde_json::to_vec(&self.replay)?)?;
+        Ok(())
+    }
+}
+
+pub struct Playback {
+    replay: Replay,
+    next: usize,
+    frame: u64,
+    menu: Option<MainMenuSelection>,
+    right_click: bool,
+}
+
+impl Playback {
+    pub fn load(path: &Path) -> Result<Playback, Box<dyn Error + Send + Sync>> {
+        let replay: Replay = serde_json::from_slice(&fs::read(path)?)?;
+        if replay.version != REPLAY_VERSION {
+            return Err(format!(
+                "{} was recorded with replay version {}, expected {}",
+                path.display(),
+                replay.version,
+                REPLAY_VERSION
+            )
+            .into());
+        }
+        Ok(Playback {
+            replay,
+            next: 0,
+            frame: 0,
+            menu: None,
+            right_click: false,
+        })
+    }
+
+    pub fn replay(&self) -> &Replay {
+        &self.replay
+    }
+
+    /// Replaces the live input on `ctx` with the recorded input for this frame.
+    pub fn apply_frame(&mut self, ctx: &mut rltk::BTerm) {
+        ctx.key = None;
+        ctx.shift = false;
+        ctx.control = false;
+        ctx.left_click = false;
+        self.menu = None;
+        self.right_click = false;
+
+        if let Some(recorded) = self.replay.frames.get(self.next) {
+            if recorded.frame == self.frame {
+                ctx.key = recorded.key.as_deref().and_then(key_from_name);
+                ctx.shift = recorded.shift;
+                ctx.control = recorded.control;
+                ctx.mouse_pos = recorded.mouse;
+                ctx.left_click = recorded.left_click;
+                self.right_click = recorded.right_click;
+                if let Some(frame_time_ms) = recorded.frame_time_ms {
+                    ctx.frame_time_ms = frame_time_ms;
+                }
+                self.menu = recorded.menu;
+                self.next += 1;
+            }
+        }
+        self.frame += 1;
+    }
+
+    /// The main menu choice recorded for the current frame.
+    pub fn menu_selection(&self) -> Option<MainMenuSelection> {
+        self.menu
+    }
+
+    /// Whether the right mouse button was clicked in the current frame.
+    pub fn right_click(&self) -> bool {
+        self.right_click
+    }
+
+    /// Jumps over frames where the recorded player was thinking. Only safe while the game is
+    /// waiting for input, when a frame with no input does nothing.
+    pub fn skip_idle(&mut self) {
+        if let Some(recorded) = self.replay.frames.get(self.next) {
+            self.frame = u64::max(self.frame, recorded.frame);
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.next >= self.replay.frames.len()
+    }
+}
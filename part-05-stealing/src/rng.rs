@@ -1,19 +1,118 @@
 use crate::rltk;
 use std::sync::Mutex;
 
+/// Each subsystem draws from its own stream, so extra rolls in one (a longer fight, say) don't
+/// change the results of another (the layout of the next level).
+#[derive(PartialEq, Copy, Clone)]
+pub enum Stream {
+    MapGen,
+    Loot,
+    Combat,
+    Ai,
+    Cosmetic,
+}
+
+const STREAMS: [Stream; 5] = [
+    Stream::MapGen,
+    Stream::Loot,
+    Stream::Combat,
+    Stream::Ai,
+    Stream::Cosmetic,
+];
+
 lazy_static! {
-    static ref RNG: Mutex<rltk::RandomNumberGenerator> =
-        Mutex::new(rltk::RandomNumberGenerator::new());
+    static ref RNG: Mutex<Vec<rltk::RandomNumberGenerator>> = Mutex::new(
+        STREAMS
+            .iter()
+            .map(|_| rltk::RandomNumberGenerator::new())
+            .collect()
+    );
+}
+
+pub fn reseed(stream: Stream, seed: u64) {
+    RNG.lock().unwrap()[stream as usize] = rltk::RandomNumberGenerator::seeded(seed);
+}
+
+/// Levels derive their seeds from their depth, so the run-wide seeds hang off a number no depth
+/// will reach.
+const RUN_STREAM: u64 = u64::MAX;
+
+/// Seeds every stream from one run seed.
+pub fn reseed_all(seed: u64) {
+    let run_seed = derive_seed(seed, RUN_STREAM);
+    for stream in STREAMS.iter() {
+        reseed(*stream, derive_seed(run_seed, *stream as u64));
+    }
+}
+
+/// Seeds the streams that build a level, so its layout and spawns only depend on the run seed
+/// and the depth.
+pub fn reseed_level(seed: u64, depth: i32) {
+    let level_seed = derive_seed(seed, depth as u64);
+    reseed(
+        Stream::MapGen,
+        derive_seed(level_seed, Stream::MapGen as u64),
+    );
+    reseed(Stream::Loot, derive_seed(level_seed, Stream::Loot as u64));
 }
 
-pub fn reseed(seed: u64) {
-    *RNG.lock().unwrap() = rltk::RandomNumberGenerator::seeded(seed);
+pub fn roll_dice(stream: Stream, n: i32, die_type: i32) -> i32 {
+    RNG.lock().unwrap()[stream as usize].roll_dice(n, die_type)
+}
+
+pub fn range(stream: Stream, min: i32, max: i32) -> i32 {
+    RNG.lock().unwrap()[stream as usize].range(min, max)
+}
+
+macro_rules! stream_shorthand {
+    ($($module:ident => $stream:ident),*) => {
+        $(
+            pub mod $module {
+                use super::Stream;
+
+                pub fn roll_dice(n: i32, die_type: i32) -> i32 {
+                    super::roll_dice(Stream::$stream, n, die_type)
+                }
+
+                pub fn range(min: i32, max: i32) -> i32 {
+                    super::range(Stream::$stream, min, max)
+                }
+            }
+        )*
+    };
+}
+
+// Shorthand, so callers can write `rng::combat::roll_dice(1, 20)`
+stream_shorthand!(
+    map_gen => MapGen,
+    loot => Loot,
+    combat => Combat,
+    ai => Ai,
+    cosmetic => Cosmetic
+);
+
+/// Picks a seed for a new run from fresh entropy, so it never follows from a previous seed.
+pub fn random_seed() -> u64 {
+    rltk::RandomNumberGenerator::new().next_u64()
 }
 
-pub fn roll_dice(n: i32, die_type: i32) -> i32 {
-    RNG.lock().unwrap().roll_dice(n, die_type)
+/// Mixes a run seed with a stream number (such as a depth), so that each stream gets an
+/// unrelated seed that only depends on the two inputs.
+pub fn derive_seed(seed: u64, stream: u64) -> u64 {
+    // SplitMix64 finalizer
+    let mut z = seed ^ stream.wrapping_add(1).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
 }
 
-pub fn range(min: i32, max: i32) -> i32 {
-    RNG.lock().unwrap().range(min, max)
+/// Turns whatever the player typed into a seed. Numbers are used as-is, so a seed read off the
+/// HUD reproduces the run; anything else is hashed.
+pub fn parse_seed(text: &str) -> u64 {
+    let text = text.trim();
+    text.parse().unwrap_or_else(|_| {
+        text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash: u64, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+        })
+    })
 }
@@ -0,0 +1,53 @@
+use super::{
+    existing_save_path, read_metadata, read_save_files, write_save_files, SaveError,
+    SaveFormatKind, SaveMetadata,
+};
+use specs::prelude::*;
+use std::time::SystemTime;
+
+/// Autosaves rotate through this many files, overwriting the oldest.
+const AUTOSAVE_RING: usize = 3;
+
+/// Take an autosave every this many turns.
+pub const AUTOSAVE_INTERVAL: i32 = 100;
+
+fn autosave_name(index: usize) -> String {
+    format!("autosave_{}", index)
+}
+
+fn modified_time(index: usize) -> Option<SystemTime> {
+    let path = existing_save_path(&autosave_name(index))?;
+    std::fs::metadata(path).ok()?.modified().ok()
+}
+
+fn newest_autosave() -> Option<usize> {
+    (0..AUTOSAVE_RING)
+        .filter_map(|index| modified_time(index).map(|time| (index, time)))
+        .max_by_key(|(_, time)| *time)
+        .map(|(index, _)| index)
+}
+
+fn next_autosave() -> usize {
+    (0..AUTOSAVE_RING)
+        .min_by_key(|index| modified_time(*index))
+        .unwrap_or(0)
+}
+
+/// Writes the world over the oldest file in the autosave ring.
+pub fn autosave(ecs: &mut World, format: SaveFormatKind) -> Result<(), SaveError> {
+    write_save_files(ecs, &autosave_name(next_autosave()), format)
+}
+
+pub fn does_autosave_exist() -> bool {
+    newest_autosave().is_some()
+}
+
+pub fn latest_autosave_metadata() -> Option<SaveMetadata> {
+    newest_autosave().and_then(|index| read_metadata(&autosave_name(index)))
+}
+
+pub fn load_latest_autosave(ecs: &mut World) -> Result<(), SaveError> {
+    let index =
+        newest_autosave().ok_or_else(|| SaveError::Corrupt("there is no autosave".to_string()))?;
+    read_save_files(ecs, &autosave_name(index))
+}
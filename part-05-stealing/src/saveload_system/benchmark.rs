@@ -0,0 +1,25 @@
+use crate::map;
+use specs::prelude::*;
+
+/// How many levels the fixture dungeon generates before it is saved.
+const FIXTURE_DEPTH: i32 = 12;
+/// The fixture is built from a fixed seed, so runs of the benchmark compare the same dungeon.
+const FIXTURE_SEED: u64 = 1;
+
+/// Builds the world `cargo bench` saves and loads: a dungeon walked down from the town, keeping
+/// every level in the dungeon map the way a long run would.
+pub fn fixture() -> World {
+    let mut ecs = World::new();
+    crate::init_world(&mut ecs, FIXTURE_SEED);
+    map::level_transition(&mut ecs, 1, 0, false);
+    for depth in 2..=FIXTURE_DEPTH {
+        map::freeze_level_entities(&mut ecs);
+        map::level_transition(&mut ecs, depth, 1, false);
+        crate::gamelog::Logger::new()
+            .category(crate::gamelog::LogCategory::System)
+            .append("You descend to depth")
+            .append(depth)
+            .log();
+    }
+    ecs
+}
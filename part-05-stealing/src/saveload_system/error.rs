@@ -0,0 +1,60 @@
+use std::fmt;
+
+#[derive(Debug)]
+pub enum SaveError {
+    Io(std::io::Error),
+    Serialization(serde_json::Error),
+    Encoding(String),
+    Corrupt(String),
+    Incompatible { version: u32 },
+    Tampered,
+    RunEnded,
+    Stale,
+    MissingResource(&'static str),
+    MissingHelper,
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SaveError::Io(e) => write!(f, "Unable to access the save file: {}", e),
+            SaveError::Serialization(e) => write!(f, "The save file is damaged: {}", e),
+            SaveError::Encoding(reason) => write!(f, "Unable to encode the save: {}", reason),
+            SaveError::Corrupt(reason) => write!(f, "The save file is damaged: {}", reason),
+            SaveError::Incompatible { version } => write!(
+                f,
+                "The save uses format version {}, which this game can't read",
+                version
+            ),
+            SaveError::Tampered => write!(f, "The save has been modified outside the game"),
+            SaveError::RunEnded => write!(f, "This ironman run has already ended"),
+            SaveError::Stale => write!(f, "This is an old copy of an ironman save"),
+            SaveError::MissingResource(name) => {
+                write!(f, "The game has no {} to save", name)
+            }
+            SaveError::MissingHelper => write!(f, "The save is missing its world data"),
+        }
+    }
+}
+
+impl std::error::Error for SaveError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SaveError::Io(e) => Some(e),
+            SaveError::Serialization(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for SaveError {
+    fn from(e: std::io::Error) -> Self {
+        SaveError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for SaveError {
+    fn from(e: serde_json::Error) -> Self {
+        SaveError::Serialization(e)
+    }
+}
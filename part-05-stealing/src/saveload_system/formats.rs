@@ -0,0 +1,82 @@
+use super::{migrations, SaveError};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::io::Write;
+
+/// Binary saves start with this tag, so they can be told apart from JSON on load.
+const BINARY_MAGIC: &[u8] = b"RRSAVE";
+
+/// Turns a save document into bytes on disk and back again.
+pub trait SaveFormat {
+    fn name(&self) -> &'static str;
+    fn encode(&self, document: &Value) -> Result<Vec<u8>, SaveError>;
+    fn decode(&self, data: &[u8]) -> Result<Value, SaveError>;
+}
+
+/// Human readable saves, useful for debugging.
+pub struct JsonFormat {}
+
+impl SaveFormat for JsonFormat {
+    fn name(&self) -> &'static str {
+        "JSON"
+    }
+
+    fn encode(&self, document: &Value) -> Result<Vec<u8>, SaveError> {
+        Ok(serde_json::to_vec(document)?)
+    }
+
+    fn decode(&self, data: &[u8]) -> Result<Value, SaveError> {
+        let text = std::str::from_utf8(data).map_err(|e| SaveError::Corrupt(e.to_string()))?;
+        migrations::parse_document(text)
+    }
+}
+
+/// MessagePack, compressed with gzip.
+pub struct BinaryFormat {}
+
+impl SaveFormat for BinaryFormat {
+    fn name(&self) -> &'static str {
+        "Binary"
+    }
+
+    fn encode(&self, document: &Value) -> Result<Vec<u8>, SaveError> {
+        let packed = rmp_serde::to_vec(document).map_err(|e| SaveError::Encoding(e.to_string()))?;
+        let mut encoder = GzEncoder::new(BINARY_MAGIC.to_vec(), Compression::default());
+        encoder.write_all(&packed)?;
+        Ok(encoder.finish()?)
+    }
+
+    fn decode(&self, data: &[u8]) -> Result<Value, SaveError> {
+        let compressed = data
+            .strip_prefix(BINARY_MAGIC)
+            .ok_or_else(|| SaveError::Corrupt("not a binary save".to_string()))?;
+        rmp_serde::from_read(GzDecoder::new(compressed))
+            .map_err(|e| SaveError::Corrupt(e.to_string()))
+    }
+}
+
+#[derive(PartialEq, Copy, Clone, Serialize, Deserialize)]
+pub enum SaveFormatKind {
+    Json,
+    Binary,
+}
+
+impl SaveFormatKind {
+    pub fn format(self) -> Box<dyn SaveFormat> {
+        match self {
+            SaveFormatKind::Json => Box::new(JsonFormat {}),
+            SaveFormatKind::Binary => Box::new(BinaryFormat {}),
+        }
+    }
+}
+
+pub fn detect_format(data: &[u8]) -> Box<dyn SaveFormat> {
+    if data.starts_with(BINARY_MAGIC) {
+        SaveFormatKind::Binary.format()
+    } else {
+        SaveFormatKind::Json.format()
+    }
+}
@@ -0,0 +1,198 @@
+use super::{
+    build_metadata, delete_save_files, existing_save_path, read_save_files, write_atomically,
+    write_save_files, SaveError, SaveFormatKind, SaveMetadata, SAVE_DIRECTORY,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use specs::prelude::*;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const IRONMAN_SAVE: &str = "ironman";
+
+/// Identifies the current run. Stored in every save, so a save can be traced back to its run.
+#[derive(Serialize, Deserialize, Clone, Copy, Default)]
+pub struct RunInfo {
+    pub run_id: u64,
+    pub ironman: bool,
+    /// Counts the ironman saves and loads of this run, as recorded in the run ledger.
+    #[serde(default)]
+    pub generation: u32,
+}
+
+impl RunInfo {
+    pub fn new(ironman: bool) -> RunInfo {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_nanos() as u64);
+        RunInfo {
+            run_id: nanos ^ crate::rng::random_seed(),
+            ironman,
+            generation: 0,
+        }
+    }
+}
+
+/// A record of a run that ended in death. Ironman saves from these runs will not load.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct GraveyardEntry {
+    pub run_id: u64,
+    pub ironman: bool,
+    pub summary: SaveMetadata,
+}
+
+fn graveyard_path() -> PathBuf {
+    Path::new(SAVE_DIRECTORY).join("graveyard.json")
+}
+
+pub fn load_graveyard() -> Vec<GraveyardEntry> {
+    fs::read_to_string(graveyard_path())
+        .ok()
+        .and_then(|data| serde_json::from_str(&data).ok())
+        .unwrap_or_default()
+}
+
+pub fn is_run_ended(run_id: u64) -> bool {
+    load_graveyard().iter().any(|entry| entry.run_id == run_id)
+}
+
+// The latest generation of each ironman run. A save from an earlier generation is a copy kept
+// aside from before the run moved on, and won't load.
+fn ledger_path() -> PathBuf {
+    Path::new(SAVE_DIRECTORY).join("runs.json")
+}
+
+fn load_ledger() -> HashMap<u64, u32> {
+    fs::read_to_string(ledger_path())
+        .ok()
+        .and_then(|data| serde_json::from_str(&data).ok())
+        .unwrap_or_default()
+}
+
+fn recorded_generation(run_id: u64) -> u32 {
+    load_ledger().get(&run_id).copied().unwrap_or(0)
+}
+
+/// Moves the current run on a generation, returning it to be recorded once the save is written.
+fn next_generation(ecs: &mut World) -> RunInfo {
+    let mut run = ecs.write_resource::<RunInfo>();
+    run.generation += 1;
+    *run
+}
+
+fn record_generation(run: RunInfo) -> Result<(), SaveError> {
+    let mut ledger = load_ledger();
+    ledger.insert(run.run_id, run.generation);
+    fs::create_dir_all(SAVE_DIRECTORY)?;
+    write_atomically(&ledger_path(), &serde_json::to_vec(&ledger)?)
+}
+
+/// Buries the current run. Ironman runs also lose their save, so they can't be resumed.
+pub fn record_death(ecs: &World) -> Result<(), SaveError> {
+    let run = *ecs.fetch::<RunInfo>();
+    let mut graveyard = load_graveyard();
+    graveyard.push(GraveyardEntry {
+        run_id: run.run_id,
+        ironman: run.ironman,
+        summary: build_metadata(ecs),
+    });
+
+    fs::create_dir_all(SAVE_DIRECTORY)?;
+    write_atomically(&graveyard_path(), &serde_json::to_vec(&graveyard)?)?;
+
+    if run.ironman {
+        delete_save_files(IRONMAN_SAVE)?;
+    }
+    Ok(())
+}
+
+/// FNV-1a over the serialized components. This doesn't stop a determined cheat, but it catches
+/// hand edited saves.
+pub fn checksum(components: &Value) -> Result<String, SaveError> {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in serde_json::to_vec(components)? {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    Ok(format!("{:016x}", hash))
+}
+
+pub fn does_ironman_save_exist() -> bool {
+    existing_save_path(IRONMAN_SAVE).is_some()
+}
+
+/// Writes the ironman save. Any earlier save of the run, or copy of one, is stale from here on.
+pub fn save_ironman(ecs: &mut World, format: SaveFormatKind) -> Result<(), SaveError> {
+    // Only recorded once written, so a failed save leaves the last one loadable
+    let run = next_generation(ecs);
+    write_save_files(ecs, IRONMAN_SAVE, format)?;
+    record_generation(run)
+}
+
+/// Loads the ironman run and removes its save; it will be written again on quit. Loading moves
+/// the run on a generation, so a copy of the save won't load a second time.
+pub fn load_ironman(ecs: &mut World) -> Result<(), SaveError> {
+    read_save_files(ecs, IRONMAN_SAVE)?;
+    record_generation(next_generation(ecs))?;
+    delete_save_files(IRONMAN_SAVE)
+}
+
+/// Stamps a save document with the current run and a checksum of its components.
+pub fn fingerprint(ecs: &World, document: &mut Map<String, Value>) -> Result<(), SaveError> {
+    let run = ecs
+        .try_fetch::<RunInfo>()
+        .map_or_else(RunInfo::default, |run| *run);
+    let components = document
+        .get("components")
+        .ok_or_else(|| SaveError::Corrupt("the save has no components".to_string()))?;
+    let checksum = checksum(components)?;
+    document.insert("run".to_string(), serde_json::to_value(run)?);
+    document.insert("checksum".to_string(), Value::from(checksum));
+    Ok(())
+}
+
+/// Checks a save document's fingerprint, returning the run it belongs to. Saves from before
+/// fingerprinting belong to an anonymous, non-ironman run.
+pub fn verify_fingerprint(document: &Value) -> Result<RunInfo, SaveError> {
+    let run: RunInfo = match document.get("run") {
+        Some(run) => serde_json::from_value(run.clone())?,
+        None => RunInfo::default(),
+    };
+
+    let intact = match (document.get("checksum"), document.get("components")) {
+        (Some(expected), Some(components)) => {
+            expected.as_str() == Some(checksum(components)?.as_str())
+        }
+        _ => !run.ironman,
+    };
+    if !intact {
+        return Err(SaveError::Tampered);
+    }
+
+    if run.ironman && is_run_ended(run.run_id) {
+        return Err(SaveError::RunEnded);
+    }
+    if run.ironman && run.generation < recorded_generation(run.run_id) {
+        return Err(SaveError::Stale);
+    }
+    Ok(run)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checksums_survive_a_round_trip_through_json() {
+        // Renderable colors are f32s, which serde_json only reads back exactly with float_roundtrip
+        let components = serde_json::to_value([0.933_333_3_f32, 0.1, 1.0 / 3.0]).unwrap();
+        let text = serde_json::to_string(&components).unwrap();
+        let read_back: Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(
+            checksum(&components).unwrap(),
+            checksum(&read_back).unwrap()
+        );
+    }
+}
@@ -0,0 +1,408 @@
+use super::SaveError;
+use serde_json::{Map, Value};
+
+/// The save format written by this build. Bump it whenever a change to `components.rs` would
+/// stop an older save from loading, and register a migration below that upgrades the old shape.
+pub const SAVE_VERSION: u32 = 4;
+
+pub struct Migration {
+    pub from_version: u32,
+    pub apply: fn(&mut Value) -> Result<(), String>,
+}
+
+/// Each step upgrades a document from `from_version` to `from_version + 1`.
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        from_version: 0,
+        apply: name_legacy_components,
+    },
+    Migration {
+        from_version: 1,
+        apply: categorize_log_entries,
+    },
+    Migration {
+        from_version: 2,
+        apply: add_vendor_restock,
+    },
+    Migration {
+        from_version: 3,
+        apply: add_crime_bounties,
+    },
+];
+
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum SaveCompatibility {
+    Current,
+    Upgradable { version: u32 },
+    Incompatible { version: u32 },
+}
+
+pub fn compatibility(version: u32) -> SaveCompatibility {
+    if version == SAVE_VERSION {
+        SaveCompatibility::Current
+    } else if version < SAVE_VERSION
+        && (version..SAVE_VERSION).all(|v| MIGRATIONS.iter().any(|m| m.from_version == v))
+    {
+        SaveCompatibility::Upgradable { version }
+    } else {
+        SaveCompatibility::Incompatible { version }
+    }
+}
+
+/// Reads a save into a versioned document. Saves written before the format was versioned are a
+/// headerless stream of component storages; they are wrapped up as version 0.
+pub fn parse_document(data: &str) -> Result<Value, SaveError> {
+    let mut values = Vec::new();
+    for value in serde_json::Deserializer::from_str(data).into_iter::<Value>() {
+        values.push(value?);
+    }
+    if values.is_empty() {
+        return Err(SaveError::Corrupt("the file is empty".to_string()));
+    }
+
+    if values.len() == 1 && values[0].get("version").is_some() {
+        return Ok(values.remove(0));
+    }
+
+    let mut document = Map::new();
+    document.insert("version".to_string(), Value::from(0));
+    document.insert("components".to_string(), Value::Array(values));
+    Ok(Value::Object(document))
+}
+
+pub fn document_version(document: &Value) -> u32 {
+    document
+        .get("version")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0) as u32
+}
+
+/// Applies every registered step until the document matches `SAVE_VERSION`.
+pub fn migrate(document: &mut Value) -> Result<(), SaveError> {
+    loop {
+        let version = document_version(document);
+        if version == SAVE_VERSION {
+            return Ok(());
+        }
+        let step = MIGRATIONS
+            .iter()
+            .find(|m| m.from_version == version)
+            .ok_or(SaveError::Incompatible { version })?;
+        (step.apply)(document).map_err(SaveError::Corrupt)?;
+        document["version"] = Value::from(version + 1);
+    }
+}
+
+/// The component order baked into version 0 saves, which stored storages positionally.
+const LEGACY_COMPONENTS: &[&str] = &[
+    "Position",
+    "Renderable",
+    "Player",
+    "Viewshed",
+    "Name",
+    "BlocksTile",
+    "WantsToMelee",
+    "Item",
+    "Consumable",
+    "Ranged",
+    "InflictsDamage",
+    "AreaOfEffect",
+    "Confusion",
+    "ProvidesHealing",
+    "InBackpack",
+    "WantsToPickupItem",
+    "WantsToUseItem",
+    "WantsToDropItem",
+    "SerializationHelper",
+    "Equippable",
+    "Equipped",
+    "Wearable",
+    "Weapon",
+    "WantsToRemoveItem",
+    "ParticleLifetime",
+    "MagicMapper",
+    "Hidden",
+    "EntryTrigger",
+    "EntityMoved",
+    "Door",
+    "BlocksVisibility",
+    "Quips",
+    "Attributes",
+    "Skills",
+    "Pools",
+    "NaturalAttackDefense",
+    "LootTable",
+    "OtherLevelPosition",
+    "DMSerializationHelper",
+    "LightSource",
+    "Initiative",
+    "MyTurn",
+    "Faction",
+    "WantsToApproach",
+    "WantsToFlee",
+    "MoveMode",
+    "Chasing",
+    "EquipmentChanged",
+    "Vendor",
+    "SingleActivation",
+    "TeleportTo",
+    "ApplyMove",
+    "ApplyTeleport",
+    "TownPortal",
+    "MagicItem",
+    "ObfuscatedName",
+    "IdentifiedItem",
+    "SpawnParticleBurst",
+    "SpawnParticleLine",
+    "CursedItem",
+    "ProvidesRemoveCurse",
+    "ProvidesIdentification",
+    "AttributeBonus",
+    "Duration",
+    "StatusEffect",
+    "KnownSpells",
+    "SpellTemplate",
+    "WantsToCastSpell",
+    "ProvidesMana",
+    "Slow",
+    "DamageOverTime",
+    "SpecialAbilities",
+    "TeachesSpell",
+    "TileSize",
+    "OnDeath",
+    "AlwaysTargetsSelf",
+    "Target",
+    "WantsToShoot",
+    "Sounds",
+];
+
+fn name_legacy_components(document: &mut Value) -> Result<(), String> {
+    let storages = match document.get_mut("components").map(Value::take) {
+        Some(Value::Array(storages)) => storages,
+        _ => return Err("Legacy save has no component list".to_string()),
+    };
+    if storages.len() != LEGACY_COMPONENTS.len() {
+        return Err(format!(
+            "Legacy save has {} component storages, expected {}",
+            storages.len(),
+            LEGACY_COMPONENTS.len()
+        ));
+    }
+
+    let mut components = Map::new();
+    for (name, storage) in LEGACY_COMPONENTS.iter().zip(storages) {
+        components.insert(name.to_string(), storage);
+    }
+    document["components"] = Value::Object(components);
+    Ok(())
+}
+
+/// Version 1 stored each log entry as a bare list of fragments. They become `System` entries,
+/// since there is no telling now what they were about.
+fn categorize_log_entries(document: &mut Value) -> Result<(), String> {
+    let storage = match document["components"].get_mut("DMSerializationHelper") {
+        Some(Value::Array(storage)) => storage,
+        _ => return Ok(()),
+    };
+    // Each stored entity carries its components as a list, one slot per component type
+    for entity in storage.iter_mut() {
+        let components = match entity.get_mut("components") {
+            Some(Value::Array(components)) => components,
+            _ => return Err("Dungeon helper has no components".to_string()),
+        };
+        for helper in components.iter_mut() {
+            if let Some(Value::Array(log)) = helper.get_mut("log") {
+                for entry in log.iter_mut() {
+                    let fragments = entry.take();
+                    let mut categorized = Map::new();
+                    categorized.insert("category".to_string(), Value::from("System"));
+                    categorized.insert("fragments".to_string(), fragments);
+                    *entry = Value::Object(categorized);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Version 2 vendors sold from an endless list rather than carrying stock. Marking them as never
+/// restocked has them stock up the first time the player trades with them.
+fn add_vendor_restock(document: &mut Value) -> Result<(), String> {
+    let storage = match document["components"].get_mut("Vendor") {
+        Some(Value::Array(storage)) => storage,
+        _ => return Ok(()),
+    };
+    for entity in storage.iter_mut() {
+        let components = match entity.get_mut("components") {
+            Some(Value::Array(components)) => components,
+            _ => return Err("Vendor has no components".to_string()),
+        };
+        for vendor in components.iter_mut() {
+            if let Some(vendor) = vendor.as_object_mut() {
+                vendor.insert("last_restock".to_string(), Value::Null);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// What each theft reported in a version 3 save adds to the bounty.
+const MIGRATED_THEFT_BOUNTY: f64 = 50.0;
+
+/// Version 3 crime records counted reported thefts but not what was taken, so each one is
+/// charged at a flat rate.
+fn add_crime_bounties(document: &mut Value) -> Result<(), String> {
+    let storage = match document["components"].get_mut("CrimeRecord") {
+        Some(Value::Array(storage)) => storage,
+        _ => return Ok(()),
+    };
+    for entity in storage.iter_mut() {
+        let components = match entity.get_mut("components") {
+            Some(Value::Array(components)) => components,
+            _ => return Err("Crime record has no components".to_string()),
+        };
+        for record in components.iter_mut() {
+            if let Some(Value::Object(towns)) = record.get_mut("towns") {
+                for crimes in towns.values_mut() {
+                    let thefts = crimes.get("thefts").and_then(Value::as_f64).unwrap_or(0.0);
+                    if let Some(crimes) = crimes.as_object_mut() {
+                        crimes.insert(
+                            "bounty".to_string(),
+                            Value::from(thefts * MIGRATED_THEFT_BOUNTY),
+                        );
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAVE_V0: &str = include_str!("fixtures/save_v0.json");
+    const SAVE_V3: &str = include_str!("fixtures/save_v3.json");
+
+    /// Each stored `name` component, skipping the entities that don't have one.
+    fn stored<'a>(document: &'a Value, name: &str) -> Vec<&'a Value> {
+        document["components"][name]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|entity| &entity["components"][0])
+            .filter(|component| !component.is_null())
+            .collect()
+    }
+
+    #[test]
+    fn headerless_saves_parse_as_version_0() {
+        let document = parse_document(SAVE_V0).unwrap();
+        assert_eq!(document_version(&document), 0);
+        assert_eq!(
+            document["components"].as_array().unwrap().len(),
+            LEGACY_COMPONENTS.len()
+        );
+    }
+
+    #[test]
+    fn legacy_saves_are_read_as_json() {
+        // The path `import_legacy_save` takes
+        let data = SAVE_V0.as_bytes();
+        let document = super::super::detect_format(data).decode(data).unwrap();
+        assert_eq!(document_version(&document), 0);
+    }
+
+    #[test]
+    fn versioned_saves_parse_as_they_are() {
+        let document = parse_document(SAVE_V3).unwrap();
+        assert_eq!(document_version(&document), 3);
+        assert!(document["components"].is_object());
+    }
+
+    #[test]
+    fn empty_saves_are_corrupt() {
+        assert!(matches!(parse_document(""), Err(SaveError::Corrupt(_))));
+        assert!(matches!(parse_document("  \n"), Err(SaveError::Corrupt(_))));
+    }
+
+    #[test]
+    fn compatibility_follows_the_registered_steps() {
+        assert_eq!(compatibility(SAVE_VERSION), SaveCompatibility::Current);
+        for version in 0..SAVE_VERSION {
+            assert_eq!(
+                compatibility(version),
+                SaveCompatibility::Upgradable { version }
+            );
+        }
+        let version = SAVE_VERSION + 1;
+        assert_eq!(
+            compatibility(version),
+            SaveCompatibility::Incompatible { version }
+        );
+    }
+
+    #[test]
+    fn version_0_saves_upgrade_to_the_current_version() {
+        let mut document = parse_document(SAVE_V0).unwrap();
+        migrate(&mut document).unwrap();
+        assert_eq!(document_version(&document), SAVE_VERSION);
+
+        // Storages are named by their position in the old component list
+        assert_eq!(stored(&document, "Name")[0]["name"], "Player");
+        assert_eq!(stored(&document, "Name")[1]["name"], "Shopkeeper");
+        assert_eq!(stored(&document, "Position")[0]["x"], 40);
+        assert_eq!(stored(&document, "Player").len(), 1);
+
+        let helper = stored(&document, "DMSerializationHelper")[0];
+        assert_eq!(helper["log"][0]["category"], "System");
+        assert_eq!(helper["log"][0]["fragments"][1]["text"], "Rusty Roguelike");
+        assert_eq!(helper["events"]["Turn"], 12);
+
+        assert!(stored(&document, "Vendor")[0]["last_restock"].is_null());
+    }
+
+    #[test]
+    fn version_3_saves_upgrade_to_the_current_version() {
+        let mut document = parse_document(SAVE_V3).unwrap();
+        migrate(&mut document).unwrap();
+        assert_eq!(document_version(&document), SAVE_VERSION);
+
+        // Later steps leave what earlier versions already had alone
+        let helper = stored(&document, "DMSerializationHelper")[0];
+        assert_eq!(helper["log"][0]["category"], "Combat");
+        assert_eq!(stored(&document, "Vendor")[0]["last_restock"], 7);
+
+        let crimes = &stored(&document, "CrimeRecord")[0]["towns"]["1"];
+        assert_eq!(crimes["thefts"], 2);
+        assert_eq!(crimes["bounty"], 2.0 * MIGRATED_THEFT_BOUNTY);
+    }
+
+    #[test]
+    fn current_saves_are_left_alone() {
+        let mut document = parse_document(SAVE_V3).unwrap();
+        migrate(&mut document).unwrap();
+        let upgraded = document.clone();
+        migrate(&mut document).unwrap();
+        assert_eq!(document, upgraded);
+    }
+
+    #[test]
+    fn newer_saves_are_incompatible() {
+        let mut document = parse_document(SAVE_V3).unwrap();
+        document["version"] = Value::from(SAVE_VERSION + 1);
+        let expected = SAVE_VERSION + 1;
+        assert!(matches!(
+            migrate(&mut document),
+            Err(SaveError::Incompatible { version }) if version == expected
+        ));
+    }
+
+    #[test]
+    fn legacy_saves_with_a_different_component_list_are_corrupt() {
+        let mut document = parse_document(SAVE_V0).unwrap();
+        document["components"].as_array_mut().unwrap().pop();
+        assert!(matches!(migrate(&mut document), Err(SaveError::Corrupt(_))));
+    }
+}
@@ -0,0 +1,446 @@
+use super::components::*;
+use crate::{provide_all_components, rltk};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use specs::prelude::*;
+use specs::saveload::{
+    DeserializeComponents, MarkedBuilder, SerializeComponents, SimpleMarker, SimpleMarkerAllocator,
+};
+use std::convert::Infallible;
+use std::fs;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+mod autosave;
+pub use autosave::{
+    autosave, does_autosave_exist, latest_autosave_metadata, load_latest_autosave,
+    AUTOSAVE_INTERVAL,
+};
+pub mod benchmark;
+mod error;
+mod ironman;
+pub use error::SaveError;
+pub use ironman::{
+    does_ironman_save_exist, load_graveyard, load_ironman, record_death, save_ironman,
+    GraveyardEntry, RunInfo,
+};
+mod formats;
+pub use formats::{detect_format, BinaryFormat, JsonFormat, SaveFormat, SaveFormatKind};
+mod migrations;
+pub use migrations::{compatibility, SaveCompatibility, SAVE_VERSION};
+
+pub const SAVE_DIRECTORY: &str = "./saves";
+pub const SAVE_SLOTS: usize = 5;
+/// Where the game kept its only save, before it had slots.
+pub const LEGACY_SAVE: &str = "./savegame.json";
+
+/// A small summary of a save, written next to it so the menus can preview a slot without
+/// deserializing the whole world.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SaveMetadata {
+    #[serde(default)]
+    pub version: u32,
+    pub character_level: i32,
+    pub depth: i32,
+    pub map_name: String,
+    pub turns: i32,
+    pub timestamp: u64,
+}
+
+// Each storage is stored under its component name, so reordering `provide_all_components!` or
+// adding a component doesn't shift the rest of the save.
+macro_rules! serialize_individually {
+    (
+        $ecs:expr,
+        $components:expr,
+        $(
+            $type:ty
+        ),*
+    ) => {
+        {
+            $(
+                let storage = SerializeComponents::<Infallible, SimpleMarker<SerializeMe>>::serialize(
+                    &( $ecs.read_storage::<$type>(), ),
+                    &$ecs.entities(),
+                    &$ecs.read_storage::<SimpleMarker<SerializeMe>>(),
+                    serde_json::value::Serializer,
+                )?;
+                $components.insert(stringify!($type).to_string(), storage);
+            )*
+        }
+    };
+}
+
+// Components missing from the save (because they were added later) load as empty storages.
+macro_rules! deserialize_individually {
+    (
+        $ecs:expr,
+        $components:expr,
+        $(
+            $type:ty
+        ),*
+    ) => {
+        {
+            $(
+                let storage = $components
+                    .remove(stringify!($type))
+                    .unwrap_or_else(|| Value::Array(Vec::new()));
+                DeserializeComponents::<Infallible, _>::deserialize(
+                    &mut ( &mut $ecs.write_storage::<$type>(), ),
+                    &mut $ecs.entities(),
+                    &mut $ecs.write_storage::<SimpleMarker<SerializeMe>>(),
+                    &mut $ecs.write_resource::<SimpleMarkerAllocator<SerializeMe>>(),
+                    storage,
+                )?;
+            )*
+        }
+    };
+}
+
+fn slot_name(slot: usize) -> String {
+    format!("slot_{}", slot)
+}
+
+fn save_path(name: &str) -> PathBuf {
+    Path::new(SAVE_DIRECTORY).join(format!("{}.sav", name))
+}
+
+// Saves written before the binary format existed were always JSON.
+fn legacy_save_path(name: &str) -> PathBuf {
+    Path::new(SAVE_DIRECTORY).join(format!("{}.json", name))
+}
+
+fn existing_save_path(name: &str) -> Option<PathBuf> {
+    [save_path(name), legacy_save_path(name)]
+        .into_iter()
+        .find(|path| path.exists())
+}
+
+fn metadata_path(name: &str) -> PathBuf {
+    Path::new(SAVE_DIRECTORY).join(format!("{}.meta.json", name))
+}
+
+fn build_metadata(ecs: &World) -> SaveMetadata {
+    let player_entity = ecs.fetch::<Entity>();
+    let map = ecs.fetch::<super::map::Map>();
+    let character_level = ecs
+        .read_storage::<Pools>()
+        .get(*player_entity)
+        .map_or(1, |pools| pools.level);
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+
+    SaveMetadata {
+        version: SAVE_VERSION,
+        character_level,
+        depth: map.depth,
+        map_name: map.name.clone(),
+        turns: crate::gamelog::get_event_count("Turn"),
+        timestamp,
+    }
+}
+
+/// Writes next to the destination and renames over it, so a failed write never clobbers the
+/// previous file.
+pub fn write_atomically(path: &Path, data: &[u8]) -> Result<(), SaveError> {
+    let temp_path = path.with_extension("tmp");
+    {
+        let mut writer = BufWriter::new(File::create(&temp_path)?);
+        writer.write_all(data)?;
+        writer.flush()?;
+        writer.get_ref().sync_all()?;
+    }
+    fs::rename(&temp_path, path)?;
+    Ok(())
+}
+
+fn serialize_world(ecs: &mut World) -> Result<Value, SaveError> {
+    let mut components = Map::new();
+    provide_all_components!(serialize_individually, ecs, components);
+    let mut document = Map::new();
+    document.insert("version".to_string(), Value::from(SAVE_VERSION));
+    document.insert("components".to_string(), Value::Object(components));
+    ironman::fingerprint(ecs, &mut document)?;
+    Ok(Value::Object(document))
+}
+
+/// Captures the world, map and log as a save document, ready to be encoded by a `SaveFormat`.
+pub fn snapshot(ecs: &mut World) -> Result<Value, SaveError> {
+    // Create helper
+    let mapcopy = ecs
+        .get_mut::<super::map::Map>()
+        .ok_or(SaveError::MissingResource("map"))?
+        .clone();
+    let dungeon_master = ecs
+        .get_mut::<super::map::MasterDungeonMap>()
+        .ok_or(SaveError::MissingResource("dungeon"))?
+        .clone();
+    let savehelper = ecs
+        .create_entity()
+        .with(SerializationHelper { map: mapcopy })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+    let savehelper2 = ecs
+        .create_entity()
+        .with(DMSerializationHelper {
+            map: dungeon_master,
+            log: crate::gamelog::clone_log(),
+            events: crate::gamelog::clone_events(),
+        })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+
+    let result = serialize_world(ecs);
+
+    // Clean up, whether or not serialization succeeded
+    ecs.delete_entity(savehelper)
+        .map_err(|_| SaveError::MissingHelper)?;
+    ecs.delete_entity(savehelper2)
+        .map_err(|_| SaveError::MissingHelper)?;
+
+    result
+}
+
+fn write_save_files(ecs: &mut World, name: &str, format: SaveFormatKind) -> Result<(), SaveError> {
+    let document = snapshot(ecs)?;
+    let data = format.format().encode(&document)?;
+
+    fs::create_dir_all(SAVE_DIRECTORY)?;
+    write_atomically(&save_path(name), &data)?;
+    if legacy_save_path(name).exists() {
+        fs::remove_file(legacy_save_path(name))?;
+    }
+
+    // Write the preview alongside the save
+    let metadata = serde_json::to_vec(&build_metadata(ecs))?;
+    write_atomically(&metadata_path(name), &metadata)
+}
+
+fn read_save_files(ecs: &mut World, name: &str) -> Result<(), SaveError> {
+    let path = existing_save_path(name)
+        .ok_or_else(|| SaveError::Corrupt("the save file is missing".to_string()))?;
+    let data = fs::read(path)?;
+    let document = detect_format(&data).decode(&data)?;
+    restore(ecs, document)
+}
+
+fn read_metadata(name: &str) -> Option<SaveMetadata> {
+    let path = existing_save_path(name)?;
+    fs::read_to_string(metadata_path(name))
+        .ok()
+        .and_then(|data| serde_json::from_str(&data).ok())
+        .or_else(|| metadata_from_save(&path))
+}
+
+/// Each stored `name` component, along with the marker of the entity it belongs to.
+fn stored_components<'a>(
+    components: &'a Value,
+    name: &str,
+) -> impl Iterator<Item = (&'a Value, &'a Value)> {
+    components[name]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|entity| {
+            entity["components"]
+                .get(0)
+                .filter(|component| !component.is_null())
+                .map(|component| (&entity["marker"], component))
+        })
+}
+
+/// Builds the preview from the save itself, for saves whose sidecar has gone missing. Saves
+/// too old or new to upgrade only get their version, so the menus can say so.
+fn metadata_from_save(path: &Path) -> Option<SaveMetadata> {
+    let data = fs::read(path).ok()?;
+    let mut document = detect_format(&data).decode(&data).ok()?;
+    let timestamp = fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |elapsed| elapsed.as_secs());
+    let mut metadata = SaveMetadata {
+        version: migrations::document_version(&document),
+        character_level: 0,
+        depth: 0,
+        map_name: "Unknown".to_string(),
+        turns: 0,
+        timestamp,
+    };
+    if migrations::migrate(&mut document).is_err() {
+        return Some(metadata);
+    }
+
+    let components = &document["components"];
+    if let Some((_, helper)) = stored_components(components, "SerializationHelper").next() {
+        metadata.depth = helper["map"]["depth"].as_i64().unwrap_or(0) as i32;
+        metadata.map_name = helper["map"]["name"]
+            .as_str()
+            .unwrap_or("Unknown")
+            .to_string();
+    }
+    if let Some((_, helper)) = stored_components(components, "DMSerializationHelper").next() {
+        metadata.turns = helper["events"]["Turn"].as_i64().unwrap_or(0) as i32;
+    }
+    if let Some((player, _)) = stored_components(components, "Player").next() {
+        metadata.character_level = stored_components(components, "Pools")
+            .find(|(marker, _)| *marker == player)
+            .and_then(|(_, pools)| pools["level"].as_i64())
+            .unwrap_or(0) as i32;
+    }
+    Some(metadata)
+}
+
+fn delete_save_files(name: &str) -> Result<(), SaveError> {
+    while let Some(path) = existing_save_path(name) {
+        std::fs::remove_file(path)?;
+    }
+    if metadata_path(name).exists() {
+        std::fs::remove_file(metadata_path(name))?;
+    }
+    Ok(())
+}
+
+pub fn save_game(ecs: &mut World, slot: usize, format: SaveFormatKind) -> Result<(), SaveError> {
+    write_save_files(ecs, &slot_name(slot), format)
+}
+
+pub fn load_game(ecs: &mut World, slot: usize) -> Result<(), SaveError> {
+    read_save_files(ecs, &slot_name(slot))
+}
+
+/// Replaces the world with the contents of a save document. If this fails after the world has
+/// been cleared, the player entity will no longer be alive and the caller must start a fresh game.
+pub fn restore(ecs: &mut World, mut document: Value) -> Result<(), SaveError> {
+    // Check and upgrade the document before touching the world
+    let run = ironman::verify_fingerprint(&document)?;
+    migrations::migrate(&mut document)?;
+    let mut components = match document["components"].take() {
+        Value::Object(components) => components,
+        _ => return Err(SaveError::Corrupt("the save has no components".to_string())),
+    };
+
+    {
+        // Delete everything
+        let mut to_delete = Vec::new();
+        for e in ecs.entities().join() {
+            to_delete.push(e);
+        }
+        for del in to_delete.iter() {
+            ecs.delete_entity(*del).expect("Deletion failed");
+        }
+    }
+
+    provide_all_components!(deserialize_individually, ecs, components);
+
+    let mut deleteme: Option<Entity> = None;
+    let mut deleteme2: Option<Entity> = None;
+    let mut found_player = false;
+    {
+        let entities = ecs.entities();
+        let helper = ecs.read_storage::<SerializationHelper>();
+        let helper2 = ecs.read_storage::<DMSerializationHelper>();
+        let player = ecs.read_storage::<Player>();
+        let position = ecs.read_storage::<Position>();
+        for (e, h) in (&entities, &helper).join() {
+            let mut worldmap = ecs.write_resource::<super::map::Map>();
+            *worldmap = h.map.clone();
+            crate::spatial::set_size((worldmap.height * worldmap.width) as usize);
+            deleteme = Some(e);
+        }
+        for (e, h) in (&entities, &helper2).join() {
+            let mut dungeonmaster = ecs.write_resource::<super::map::MasterDungeonMap>();
+            *dungeonmaster = h.map.clone();
+            deleteme2 = Some(e);
+            crate::gamelog::restore_log(&mut h.log.clone());
+            crate::gamelog::load_events(h.events.clone());
+        }
+        for (e, _p, pos) in (&entities, &player, &position).join() {
+            let mut ppos = ecs.write_resource::<rltk::Point>();
+            *ppos = rltk::Point::new(pos.x, pos.y);
+            let mut player_resource = ecs.write_resource::<Entity>();
+            *player_resource = e;
+            found_player = true;
+        }
+    }
+    if !found_player {
+        return Err(SaveError::Corrupt("the save has no player".to_string()));
+    }
+    ecs.delete_entity(deleteme.ok_or(SaveError::MissingHelper)?)
+        .map_err(|_| SaveError::MissingHelper)?;
+    ecs.delete_entity(deleteme2.ok_or(SaveError::MissingHelper)?)
+        .map_err(|_| SaveError::MissingHelper)?;
+    ecs.insert(run);
+
+    Ok(())
+}
+
+pub fn does_save_exist() -> bool {
+    (0..SAVE_SLOTS).any(does_slot_exist)
+}
+
+pub fn does_slot_exist(slot: usize) -> bool {
+    existing_save_path(&slot_name(slot)).is_some()
+}
+
+/// Reads the preview for a slot, from the save itself if its sidecar is missing. Returns None
+/// for empty slots, or if the save is unreadable.
+pub fn load_metadata(slot: usize) -> Option<SaveMetadata> {
+    read_metadata(&slot_name(slot))
+}
+
+/// What the menus show of the saves on disk. Read once as a menu opens, rather than on every
+/// frame it is drawn.
+#[derive(Clone, Default)]
+pub struct SaveListing {
+    pub slots: Vec<Option<SaveMetadata>>,
+    pub autosave_exists: bool,
+    pub autosave: Option<SaveMetadata>,
+    pub ironman_save_exists: bool,
+}
+
+impl SaveListing {
+    pub fn read() -> SaveListing {
+        SaveListing {
+            slots: (0..SAVE_SLOTS).map(load_metadata).collect(),
+            autosave_exists: does_autosave_exist(),
+            autosave: latest_autosave_metadata(),
+            ironman_save_exists: does_ironman_save_exist(),
+        }
+    }
+
+    pub fn any_slot_saved(&self) -> bool {
+        self.slots.iter().any(Option::is_some)
+    }
+}
+
+/// Moves a save left by a build without slots into the first free slot, and renames the original
+/// so it is only imported once. Returns the slot it went into, or None if there was nothing to
+/// import or no slot was free; in that case the save is left for a later start.
+pub fn import_legacy_save() -> Result<Option<usize>, SaveError> {
+    let path = Path::new(LEGACY_SAVE);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let slot = match (0..SAVE_SLOTS).find(|slot| !does_slot_exist(*slot)) {
+        Some(slot) => slot,
+        None => return Ok(None),
+    };
+
+    // Make sure it upgrades before giving it a slot
+    let data = fs::read(path)?;
+    let mut document = detect_format(&data).decode(&data)?;
+    migrations::migrate(&mut document)?;
+
+    fs::create_dir_all(SAVE_DIRECTORY)?;
+    write_atomically(&legacy_save_path(&slot_name(slot)), &data)?;
+    fs::rename(path, path.with_extension("json.imported"))?;
+    Ok(Some(slot))
+}
+
+pub fn delete_save(slot: usize) -> Result<(), SaveError> {
+    delete_save_files(&slot_name(slot))
+}
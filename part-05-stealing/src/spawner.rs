@@ -1,14 +1,15 @@
 use super::{
     random_table::MasterTable, raws::*, Attribute, AttributeBonus, Attributes, Duration,
-    EntryTrigger, EquipmentChanged, Faction, Initiative, KnownSpells, LightSource, Map,
-    MasterDungeonMap, Name, OtherLevelPosition, Player, Pool, Pools, Position, Rect, Renderable,
-    SerializeMe, SingleActivation, Skill, Skills, StatusEffect, TeleportTo, TileType, Viewshed,
+    EntryTrigger, EquipmentChanged, Faction, InBackpack, Initiative, Item, KnownSpells,
+    LightSource, Map, MasterDungeonMap, Name, OtherLevelPosition, Player, Pool, Pools, Position,
+    Rect, Renderable, SerializeMe, SingleActivation, Skill, Skills, StatusEffect, TeleportTo,
+    TileType, Vendor, Viewshed,
 };
 use crate::rltk;
 use crate::{attr_bonus, mana_at_level, player_hp_at_level};
 use specs::prelude::*;
 use specs::saveload::{MarkedBuilder, SimpleMarker};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 /// Spawns the player and returns his/her entity object.
 pub fn player(ecs: &mut World, player_x: i32, player_y: i32) -> Entity {
@@ -177,14 +178,14 @@ pub fn spawn_region(
     spawn_list: &mut Vec<(usize, String)>,
 ) {
     let spawn_table = room_table(map_depth);
-    let mut spawn_points: HashMap<usize, String> = HashMap::new();
+    let mut spawn_points: BTreeMap<usize, String> = BTreeMap::new();
     let mut areas: Vec<usize> = Vec::from(area);
 
     // Scope to keep the borrow checker happy
     {
         let num_spawns = i32::min(
             areas.len() as i32,
-            crate::rng::roll_dice(1, MAX_MONSTERS + 3) + (map_depth - 1) - 3,
+            crate::rng::map_gen::roll_dice(1, MAX_MONSTERS + 3) + (map_depth - 1) - 3,
         );
         if num_spawns == 0 {
             return;
@@ -194,7 +195,7 @@ pub fn spawn_region(
             let array_index = if areas.len() == 1 {
                 0usize
             } else {
-                (crate::rng::roll_dice(1, areas.len() as i32) - 1) as usize
+                (crate::rng::map_gen::roll_dice(1, areas.len() as i32) - 1) as usize
             };
 
             let map_idx = areas[array_index];
@@ -285,3 +286,114 @@ pub fn spawn_town_portal(ecs: &mut World) {
         })
         .build();
 }
+
+/// How many turns a vendor takes to restock.
+const VENDOR_RESTOCK_TURNS: i32 = 500;
+/// The most copies of each of their wares a vendor stocks.
+const VENDOR_STOCK_PER_WARE: i32 = 3;
+
+/// Tops up a vendor's stock if they have never stocked, or haven't for a while.
+pub fn restock_vendor_if_due(ecs: &mut World, vendor: Entity) {
+    let turn = crate::gamelog::current_turn();
+    let due = ecs.read_storage::<Vendor>().get(vendor).is_some_and(|v| {
+        v.last_restock
+            .is_none_or(|last| turn - last >= VENDOR_RESTOCK_TURNS)
+    });
+    if due {
+        restock_vendor(ecs, vendor);
+    }
+}
+
+/// Brings each ware in a vendor's categories back up to a few copies. Whatever the player sold
+/// them stays on the shelf too.
+fn restock_vendor(ecs: &mut World, vendor: Entity) {
+    let categories = match ecs.write_storage::<Vendor>().get_mut(vendor) {
+        Some(v) => {
+            v.last_restock = Some(crate::gamelog::current_turn());
+            v.categories.clone()
+        }
+        None => return,
+    };
+
+    let mut in_stock: HashMap<String, i32> = HashMap::new();
+    for (_item, carried, name) in (
+        &ecs.read_storage::<Item>(),
+        &ecs.read_storage::<InBackpack>(),
+        &ecs.read_storage::<Name>(),
+    )
+        .join()
+    {
+        if carried.owner == vendor {
+            *in_stock.entry(name.name.clone()).or_insert(0) += 1;
+        }
+    }
+
+    let raws = &RAWS.lock().unwrap();
+    for (ware, _price) in get_vendor_items(&categories, raws) {
+        let wanted = crate::rng::loot::roll_dice(1, VENDOR_STOCK_PER_WARE);
+        let have = in_stock.get(&ware).copied().unwrap_or(0);
+        for _ in have..wanted {
+            spawn_named_item(raws, ecs, &ware, SpawnType::Carried { by: vendor });
+        }
+    }
+}
+
+/// Restocks every vendor on the current level that is due, for when the player comes back to
+/// town.
+pub fn restock_vendors(ecs: &mut World) {
+    let vendors: Vec<Entity> = (
+        &ecs.entities(),
+        &ecs.read_storage::<Vendor>(),
+        &ecs.read_storage::<Position>(),
+    )
+        .join()
+        .map(|(entity, _, _)| entity)
+        .collect();
+    for vendor in vendors {
+        restock_vendor_if_due(ecs, vendor);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gamelog;
+
+    fn last_restock(ecs: &World, vendor: Entity) -> Option<i32> {
+        ecs.read_storage::<Vendor>()
+            .get(vendor)
+            .unwrap()
+            .last_restock
+    }
+
+    fn stock(ecs: &World, vendor: Entity) -> usize {
+        ecs.read_storage::<InBackpack>()
+            .join()
+            .filter(|carried| carried.owner == vendor)
+            .count()
+    }
+
+    #[test]
+    fn vendors_restock_once_the_interval_is_up() {
+        let mut ecs = World::new();
+        crate::init_world(&mut ecs, 1);
+        let vendor = ecs
+            .create_entity()
+            .with(Vendor {
+                categories: vec!["food".to_string()],
+                last_restock: Some(100),
+            })
+            .build();
+
+        gamelog::clear_events();
+        gamelog::record_event("Turn", 100 + VENDOR_RESTOCK_TURNS - 1);
+        restock_vendor_if_due(&mut ecs, vendor);
+        assert_eq!(last_restock(&ecs, vendor), Some(100));
+        assert_eq!(stock(&ecs, vendor), 0);
+
+        gamelog::record_event("Turn", 1);
+        restock_vendor_if_due(&mut ecs, vendor);
+        assert_eq!(last_restock(&ecs, vendor), Some(100 + VENDOR_RESTOCK_TURNS));
+        assert!(stock(&ecs, vendor) > 0);
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{raws::Reaction, Faction, Map, MyTurn, Position, TileSize, WantsToMelee};
+use crate::{raws::Reaction, CrimeRecord, Faction, Map, MyTurn, Position, TileSize, WantsToMelee};
 use specs::prelude::*;
 
 pub struct AdjacentAI {}
@@ -14,10 +14,21 @@ impl<'a> System<'a> for AdjacentAI {
         Entities<'a>,
         ReadExpect<'a, Entity>,
         ReadStorage<'a, TileSize>,
+        ReadStorage<'a, CrimeRecord>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (mut turns, factions, positions, map, mut want_melee, entities, player, sizes) = data;
+        let (
+            mut turns,
+            factions,
+            positions,
+            map,
+            mut want_melee,
+            entities,
+            player,
+            sizes,
+            crime_records,
+        ) = data;
 
         let mut turn_done: Vec<Entity> = Vec::new();
         for (entity, _turn, my_faction, pos) in (&entities, &turns, &factions, &positions).join() {
@@ -42,6 +53,7 @@ impl<'a> System<'a> for AdjacentAI {
                                     target_idx,
                                     &map,
                                     &factions,
+                                    &crime_records,
                                     &my_faction.name,
                                     &mut reactions,
                                 );
@@ -50,16 +62,31 @@ impl<'a> System<'a> for AdjacentAI {
                 } else {
                     // Add possible reactions to adjacents for each direction
                     if pos.x > 0 {
-                        evaluate(idx - 1, &map, &factions, &my_faction.name, &mut reactions);
+                        evaluate(
+                            idx - 1,
+                            &map,
+                            &factions,
+                            &crime_records,
+                            &my_faction.name,
+                            &mut reactions,
+                        );
                     }
                     if pos.x < w - 1 {
-                        evaluate(idx + 1, &map, &factions, &my_faction.name, &mut reactions);
+                        evaluate(
+                            idx + 1,
+                            &map,
+                            &factions,
+                            &crime_records,
+                            &my_faction.name,
+                            &mut reactions,
+                        );
                     }
                     if pos.y > 0 {
                         evaluate(
                             idx - w as usize,
                             &map,
                             &factions,
+                            &crime_records,
                             &my_faction.name,
                             &mut reactions,
                         );
@@ -69,6 +96,7 @@ impl<'a> System<'a> for AdjacentAI {
                             idx + w as usize,
                             &map,
                             &factions,
+                            &crime_records,
                             &my_faction.name,
                             &mut reactions,
                         );
@@ -78,6 +106,7 @@ impl<'a> System<'a> for AdjacentAI {
                             (idx - w as usize) - 1,
                             &map,
                             &factions,
+                            &crime_records,
                             &my_faction.name,
                             &mut reactions,
                         );
@@ -87,6 +116,7 @@ impl<'a> System<'a> for AdjacentAI {
                             (idx - w as usize) + 1,
                             &map,
                             &factions,
+                            &crime_records,
                             &my_faction.name,
                             &mut reactions,
                         );
@@ -96,6 +126,7 @@ impl<'a> System<'a> for AdjacentAI {
                             (idx + w as usize) - 1,
                             &map,
                             &factions,
+                            &crime_records,
                             &my_faction.name,
                             &mut reactions,
                         );
@@ -105,6 +136,7 @@ impl<'a> System<'a> for AdjacentAI {
                             (idx + w as usize) + 1,
                             &map,
                             &factions,
+                            &crime_records,
                             &my_faction.name,
                             &mut reactions,
                         );
@@ -136,8 +168,9 @@ impl<'a> System<'a> for AdjacentAI {
 
 fn evaluate(
     idx: usize,
-    _map: &Map,
+    map: &Map,
     factions: &ReadStorage<Faction>,
+    crime_records: &ReadStorage<CrimeRecord>,
     my_faction: &str,
     reactions: &mut Vec<(Entity, Reaction)>,
 ) {
@@ -145,10 +178,12 @@ fn evaluate(
         if let Some(faction) = factions.get(other_entity) {
             reactions.push((
                 other_entity,
-                crate::raws::faction_reaction(
+                crate::crime::reaction(
                     my_faction,
+                    other_entity,
                     &faction.name,
-                    &crate::raws::RAWS.lock().unwrap(),
+                    crime_records,
+                    map.depth,
                 ),
             ));
         }
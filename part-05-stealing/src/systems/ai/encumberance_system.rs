@@ -138,6 +138,7 @@ impl<'a> System<'a> for EncumbranceSystem {
                         pool.total_initiative_penalty += 4.0;
                         if *entity == *player {
                             crate::gamelog::Logger::new()
+                                .category(crate::gamelog::LogCategory::Loot)
                                 .color(rltk::ORANGE)
                                 .append(
                                     "You are overburdened, and suffering an initiative penalty.",
@@ -56,7 +56,7 @@ impl<'a> System<'a> for InitiativeSystem {
                 let mut myturn = true;
 
                 // Re-roll
-                initiative.current = 6 + crate::rng::roll_dice(1, 6);
+                initiative.current = 6 + crate::rng::combat::roll_dice(1, 6);
 
                 // Give a bonus for quickness
                 if let Some(attr) = attributes.get(entity) {
@@ -100,7 +100,10 @@ impl<'a> System<'a> for InitiativeSystem {
                     if let Some(dot) = dots.get(effect_entity) {
                         add_effect(
                             None,
-                            EffectType::Damage { amount: dot.damage },
+                            EffectType::Damage {
+                                amount: dot.damage,
+                                sound: "damage_over_time".to_string(),
+                            },
                             Targets::Single {
                                 target: status.target,
                             },
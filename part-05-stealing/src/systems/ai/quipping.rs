@@ -20,15 +20,16 @@ impl<'a> System<'a> for QuipSystem {
         for (quip, name, viewshed, _turn) in (&mut quips, &names, &viewsheds, &turns).join() {
             if !quip.available.is_empty()
                 && viewshed.visible_tiles.contains(&player_pos)
-                && crate::rng::roll_dice(1, 6) == 1
+                && crate::rng::cosmetic::roll_dice(1, 6) == 1
             {
                 let quip_index = if quip.available.len() == 1 {
                     0
                 } else {
-                    (crate::rng::roll_dice(1, quip.available.len() as i32) - 1) as usize
+                    (crate::rng::cosmetic::roll_dice(1, quip.available.len() as i32) - 1) as usize
                 };
 
                 crate::gamelog::Logger::new()
+                    .category(crate::gamelog::LogCategory::Dialogue)
                     .npc_name(&name.name)
                     .append("says")
                     .npc_name(&quip.available[quip_index])
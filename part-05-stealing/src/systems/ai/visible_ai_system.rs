@@ -1,7 +1,8 @@
 use crate::rltk;
 use crate::{
-    raws::Reaction, Chasing, Equipped, Faction, Map, MyTurn, Name, Position, SpecialAbilities,
-    SpellTemplate, Viewshed, WantsToApproach, WantsToCastSpell, WantsToFlee, WantsToShoot, Weapon,
+    raws::Reaction, Chasing, CrimeRecord, Equipped, Faction, Map, MyTurn, Name, Position,
+    SpecialAbilities, SpellTemplate, Viewshed, WantsToApproach, WantsToCastSpell, WantsToFlee,
+    WantsToShoot, Weapon,
 };
 use specs::prelude::*;
 
@@ -27,6 +28,7 @@ impl<'a> System<'a> for VisibleAI {
         ReadStorage<'a, Weapon>,
         ReadStorage<'a, Equipped>,
         WriteStorage<'a, WantsToShoot>,
+        ReadStorage<'a, CrimeRecord>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -48,6 +50,7 @@ impl<'a> System<'a> for VisibleAI {
             weapons,
             equipped,
             mut wants_shoot,
+            crime_records,
         ) = data;
 
         for (entity, _turn, my_faction, pos, viewshed) in
@@ -60,7 +63,14 @@ impl<'a> System<'a> for VisibleAI {
                 for visible_tile in viewshed.visible_tiles.iter() {
                     let idx = map.xy_idx(visible_tile.x, visible_tile.y);
                     if my_idx != idx {
-                        evaluate(idx, &factions, &my_faction.name, &mut reactions);
+                        evaluate(
+                            idx,
+                            &factions,
+                            &crime_records,
+                            map.depth,
+                            &my_faction.name,
+                            &mut reactions,
+                        );
                     }
                 }
 
@@ -79,7 +89,7 @@ impl<'a> System<'a> for VisibleAI {
                                 for ability in abilities.abilities.iter() {
                                     if range >= ability.min_range
                                         && range <= ability.range
-                                        && crate::rng::roll_dice(1, 100)
+                                        && crate::rng::combat::roll_dice(1, 100)
                                             <= (ability.chance * 100.0) as i32
                                     {
                                         use crate::raws::find_spell_entity_by_name;
@@ -159,6 +169,8 @@ impl<'a> System<'a> for VisibleAI {
 fn evaluate(
     idx: usize,
     factions: &ReadStorage<Faction>,
+    crime_records: &ReadStorage<CrimeRecord>,
+    depth: i32,
     my_faction: &str,
     reactions: &mut Vec<(usize, Reaction, Entity)>,
 ) {
@@ -166,10 +178,12 @@ fn evaluate(
         if let Some(faction) = factions.get(other_entity) {
             reactions.push((
                 idx,
-                crate::raws::faction_reaction(
+                crate::crime::reaction(
                     my_faction,
+                    other_entity,
                     &faction.name,
-                    &crate::raws::RAWS.lock().unwrap(),
+                    crime_records,
+                    depth,
                 ),
                 other_entity,
             ));
@@ -36,7 +36,9 @@ pub struct MultiThreadedDispatcher {
 impl<'a> UnifiedDispatcher for MultiThreadedDispatcher {
     fn run_now(&mut self, ecs: *mut World) {
         unsafe {
-            self.dispatcher.dispatch(&mut *ecs);
+            // Systems that share an RNG stream must roll in a fixed order, or seeded runs and
+            // replays drift apart, so they are run in sequence
+            self.dispatcher.dispatch_seq(&*ecs);
             crate::effects::run_effects_queue(&mut *ecs);
         }
     }
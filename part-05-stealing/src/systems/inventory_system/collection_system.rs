@@ -1,3 +1,5 @@
+use crate::effects::{add_effect, EffectType, Targets};
+
 use super::{
     EquipmentChanged, InBackpack, MagicItem, MasterDungeonMap, Name, ObfuscatedName, Position,
     WantsToPickupItem,
@@ -46,9 +48,19 @@ impl<'a> System<'a> for ItemCollectionSystem {
             dirty
                 .insert(pickup.collected_by, EquipmentChanged {})
                 .expect("Unable to insert");
+            add_effect(
+                Some(pickup.collected_by),
+                EffectType::Sound {
+                    event: "pickup".to_string(),
+                },
+                Targets::Single {
+                    target: pickup.collected_by,
+                },
+            );
 
             if pickup.collected_by == *player_entity {
                 crate::gamelog::Logger::new()
+                    .category(crate::gamelog::LogCategory::Loot)
                     .append("You pick up the")
                     .item_name(super::obfuscate_name(
                         pickup.item,
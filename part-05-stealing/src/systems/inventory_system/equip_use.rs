@@ -1,4 +1,4 @@
-use crate::systems::sound_system::SoundSystem;
+use crate::effects::{add_effect, EffectType, Targets};
 
 use super::{
     CursedItem, EquipmentChanged, Equippable, Equipped, IdentifiedItem, InBackpack, Name,
@@ -12,7 +12,6 @@ impl<'a> System<'a> for ItemEquipOnUse {
     #[allow(clippy::type_complexity)]
     type SystemData = (
         ReadExpect<'a, Entity>,
-        ReadExpect<'a, SoundSystem>,
         Entities<'a>,
         WriteStorage<'a, WantsToUseItem>,
         ReadStorage<'a, Name>,
@@ -28,7 +27,6 @@ impl<'a> System<'a> for ItemEquipOnUse {
     fn run(&mut self, data: Self::SystemData) {
         let (
             player_entity,
-            sound_system,
             entities,
             mut wants_use,
             names,
@@ -53,6 +51,7 @@ impl<'a> System<'a> for ItemEquipOnUse {
                     if already_equipped.owner == target && already_equipped.slot == target_slot {
                         if cursed.get(item_entity).is_some() {
                             crate::gamelog::Logger::new()
+                                .category(crate::gamelog::LogCategory::Loot)
                                 .append("You cannot unequip")
                                 .item_name(&name.name)
                                 .append("- it is cursed!")
@@ -62,6 +61,7 @@ impl<'a> System<'a> for ItemEquipOnUse {
                             to_unequip.push(item_entity);
                             if target == *player_entity {
                                 crate::gamelog::Logger::new()
+                                    .category(crate::gamelog::LogCategory::Loot)
                                     .append("You unequip")
                                     .item_name(&name.name)
                                     .log();
@@ -103,6 +103,7 @@ impl<'a> System<'a> for ItemEquipOnUse {
                     backpack.remove(useitem.item);
                     if target == *player_entity {
                         crate::gamelog::Logger::new()
+                            .category(crate::gamelog::LogCategory::Loot)
                             .append("You equip")
                             .item_name(&names.get(useitem.item).unwrap().name)
                             .log();
@@ -112,7 +113,13 @@ impl<'a> System<'a> for ItemEquipOnUse {
                         .insert(target, EquipmentChanged {})
                         .expect("Unable to insert");
 
-                    sound_system.play_sound_effects(vec![String::from("equip.wav")]);
+                    add_effect(
+                        Some(target),
+                        EffectType::Sound {
+                            event: "equip".to_string(),
+                        },
+                        Targets::Single { target },
+                    );
                 }
 
                 // Done with item
@@ -70,7 +70,7 @@ impl<'a> System<'a> for MeleeCombatSystem {
                         let attack_index = if nat.attacks.len() == 1 {
                             0
                         } else {
-                            crate::rng::roll_dice(1, nat.attacks.len() as i32) as usize - 1
+                            crate::rng::combat::roll_dice(1, nat.attacks.len() as i32) as usize - 1
                         };
                         weapon_info.hit_bonus = nat.attacks[attack_index].hit_bonus;
                         weapon_info.damage_n_dice = nat.attacks[attack_index].damage_n_dice;
@@ -89,7 +89,7 @@ impl<'a> System<'a> for MeleeCombatSystem {
                     }
                 }
 
-                let natural_roll = crate::rng::roll_dice(1, 20);
+                let natural_roll = crate::rng::combat::roll_dice(1, 20);
                 let attribute_hit_bonus = if weapon_info.attribute == WeaponAttribute::Might {
                     attacker_attributes.might.bonus
                 } else {
@@ -118,7 +118,7 @@ impl<'a> System<'a> for MeleeCombatSystem {
 
                 if natural_roll != 1 && (natural_roll == 20 || modified_hit_roll > armor_class) {
                     // Target hit! Until we support weapons, we're going with 1d4
-                    let base_damage = crate::rng::roll_dice(
+                    let base_damage = crate::rng::combat::roll_dice(
                         weapon_info.damage_n_dice,
                         weapon_info.damage_die_type,
                     );
@@ -136,12 +136,16 @@ impl<'a> System<'a> for MeleeCombatSystem {
                     );
                     add_effect(
                         Some(entity),
-                        EffectType::Damage { amount: damage },
+                        EffectType::Damage {
+                            amount: damage,
+                            sound: "melee_hit".to_string(),
+                        },
                         Targets::Single {
                             target: wants_melee.target,
                         },
                     );
                     crate::gamelog::Logger::new()
+                        .category(crate::gamelog::LogCategory::Combat)
                         .npc_name(&name.name)
                         .append("hits")
                         .npc_name(&target_name.name)
@@ -152,7 +156,7 @@ impl<'a> System<'a> for MeleeCombatSystem {
 
                     // Proc effects
                     if let Some(chance) = &weapon_info.proc_chance {
-                        let roll = crate::rng::roll_dice(1, 100);
+                        let roll = crate::rng::combat::roll_dice(1, 100);
                         if roll <= (chance * 100.0) as i32 {
                             let mut effect_target = Targets::Single {
                                 target: wants_melee.target,
@@ -173,7 +177,17 @@ impl<'a> System<'a> for MeleeCombatSystem {
                     }
                 } else if natural_roll == 1 {
                     // Natural 1 miss
+                    add_effect(
+                        Some(entity),
+                        EffectType::Sound {
+                            event: "melee_miss".to_string(),
+                        },
+                        Targets::Single {
+                            target: wants_melee.target,
+                        },
+                    );
                     crate::gamelog::Logger::new()
+                        .category(crate::gamelog::LogCategory::Combat)
                         .npc_name(&name.name)
                         .append("considers attacking")
                         .npc_name(&target_name.name)
@@ -193,7 +207,17 @@ impl<'a> System<'a> for MeleeCombatSystem {
                     );
                 } else {
                     // Miss
+                    add_effect(
+                        Some(entity),
+                        EffectType::Sound {
+                            event: "melee_miss".to_string(),
+                        },
+                        Targets::Single {
+                            target: wants_melee.target,
+                        },
+                    );
                     crate::gamelog::Logger::new()
+                        .category(crate::gamelog::LogCategory::Combat)
                         .npc_name(&name.name)
                         .append("attacks")
                         .color(rltk::CYAN)
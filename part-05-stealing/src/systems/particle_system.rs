@@ -43,7 +43,7 @@ impl ParticleBuilder {
     }
 }
 
-pub fn update_particles(ecs: &mut World, ctx: &rltk::BTerm) {
+pub fn update_particles(ecs: &mut World, frame_time_ms: f32) {
     let mut dead_particles: Vec<Entity> = Vec::new();
     {
         // Age out particles
@@ -51,7 +51,7 @@ pub fn update_particles(ecs: &mut World, ctx: &rltk::BTerm) {
         let entities = ecs.entities();
         for (entity, mut particle) in (&entities, &mut particles).join() {
             if let Some(animation) = &mut particle.animation {
-                animation.timer += ctx.frame_time_ms;
+                animation.timer += frame_time_ms;
                 if animation.timer > animation.step_time
                     && animation.current_step < animation.path.len() - 2
                 {
@@ -64,7 +64,7 @@ pub fn update_particles(ecs: &mut World, ctx: &rltk::BTerm) {
                 }
             }
 
-            particle.lifetime_ms -= ctx.frame_time_ms;
+            particle.lifetime_ms -= frame_time_ms;
             if particle.lifetime_ms < 0.0 {
                 dead_particles.push(entity);
             }
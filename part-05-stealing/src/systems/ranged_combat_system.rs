@@ -96,7 +96,7 @@ impl<'a> System<'a> for RangedCombatSystem {
                         let attack_index = if nat.attacks.len() == 1 {
                             0
                         } else {
-                            crate::rng::roll_dice(1, nat.attacks.len() as i32) as usize - 1
+                            crate::rng::combat::roll_dice(1, nat.attacks.len() as i32) as usize - 1
                         };
                         weapon_info.hit_bonus = nat.attacks[attack_index].hit_bonus;
                         weapon_info.damage_n_dice = nat.attacks[attack_index].damage_n_dice;
@@ -113,7 +113,7 @@ impl<'a> System<'a> for RangedCombatSystem {
                     }
                 }
 
-                let natural_roll = crate::rng::roll_dice(1, 20);
+                let natural_roll = crate::rng::combat::roll_dice(1, 20);
                 let attribute_hit_bonus = if weapon_info.attribute == WeaponAttribute::Might {
                     attacker_attributes.might.bonus
                 } else {
@@ -145,7 +145,7 @@ impl<'a> System<'a> for RangedCombatSystem {
                 //println!("Armor class: {}", armor_class);
                 if natural_roll != 1 && (natural_roll == 20 || modified_hit_roll > armor_class) {
                     // Target hit! Until we support weapons, we're going with 1d4
-                    let base_damage = crate::rng::roll_dice(
+                    let base_damage = crate::rng::combat::roll_dice(
                         weapon_info.damage_n_dice,
                         weapon_info.damage_die_type,
                     );
@@ -164,12 +164,16 @@ impl<'a> System<'a> for RangedCombatSystem {
                     );*/
                     add_effect(
                         Some(entity),
-                        EffectType::Damage { amount: damage },
+                        EffectType::Damage {
+                            amount: damage,
+                            sound: "ranged_hit".to_string(),
+                        },
                         Targets::Single {
                             target: wants_shoot.target,
                         },
                     );
                     crate::gamelog::Logger::new()
+                        .category(crate::gamelog::LogCategory::Combat)
                         .npc_name(&name.name)
                         .append("hits")
                         .npc_name(&target_name.name)
@@ -180,7 +184,7 @@ impl<'a> System<'a> for RangedCombatSystem {
 
                     // Proc effects
                     if let Some(chance) = &weapon_info.proc_chance {
-                        let roll = crate::rng::roll_dice(1, 100);
+                        let roll = crate::rng::combat::roll_dice(1, 100);
                         if roll <= (chance * 100.0) as i32 {
                             let effect_target;
                             if let Some(proc_target) = weapon_info.proc_target {
@@ -207,7 +211,17 @@ impl<'a> System<'a> for RangedCombatSystem {
                     }
                 } else if natural_roll == 1 {
                     // Natural 1 miss
+                    add_effect(
+                        Some(entity),
+                        EffectType::Sound {
+                            event: "ranged_miss".to_string(),
+                        },
+                        Targets::Single {
+                            target: wants_shoot.target,
+                        },
+                    );
                     crate::gamelog::Logger::new()
+                        .category(crate::gamelog::LogCategory::Combat)
                         .npc_name(&name.name)
                         .append("considers attacking")
                         .npc_name(&target_name.name)
@@ -227,7 +241,17 @@ impl<'a> System<'a> for RangedCombatSystem {
                     );
                 } else {
                     // Miss
+                    add_effect(
+                        Some(entity),
+                        EffectType::Sound {
+                            event: "ranged_miss".to_string(),
+                        },
+                        Targets::Single {
+                            target: wants_shoot.target,
+                        },
+                    );
                     crate::gamelog::Logger::new()
+                        .category(crate::gamelog::LogCategory::Combat)
                         .npc_name(&name.name)
                         .append("attacks")
                         .npc_name(&target_name.name)
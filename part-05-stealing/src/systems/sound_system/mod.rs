@@ -0,0 +1,263 @@
+use crate::components::{Name, Position};
+use crate::map::Map;
+use crate::raws::{get_default_music, get_event_sounds, RAWS};
+use crate::rltk::{self, BaseMap, Point};
+use specs::prelude::*;
+
+mod music;
+pub use music::{play_stinger, MusicDirector};
+mod recording_backend;
+pub use recording_backend::RecordingBackend;
+#[cfg(feature = "audio")]
+mod rodio_backend;
+#[cfg(feature = "audio")]
+pub use rodio_backend::RodioBackend;
+mod silent_backend;
+pub use silent_backend::SilentBackend;
+
+/// Sounds further than this many tiles from the player can't be heard.
+const HEARING_RANGE: f32 = 24.0;
+/// How much of a sound makes it through the walls between it and the player.
+const MUFFLED: f32 = 0.35;
+/// Sounds quieter than this aren't worth playing.
+const INAUDIBLE: f32 = 0.05;
+/// Volumes above 1 amplify; past this they just distort.
+const MAX_VOLUME: f32 = 2.0;
+
+/// Keeps a volume between silent and `MAX_VOLUME`. Anything that isn't a number goes back to
+/// full volume.
+pub fn clamp_volume(volume: f32) -> f32 {
+    if volume.is_finite() {
+        volume.clamp(0.0, MAX_VOLUME)
+    } else {
+        1.0
+    }
+}
+
+/// Somewhere for the game's sounds to go.
+pub trait AudioBackend: Send + Sync {
+    /// Plays a sound effect. `pan` runs from -1 (left) to 1 (right).
+    fn play(&self, file_name: &str, volume: f32, pan: f32);
+    fn background_volume(&self) -> f32;
+    fn effects_volume(&self) -> f32;
+    fn set_background_volume(&self, volume: f32);
+    fn set_effects_volume(&self, volume: f32);
+
+    /// Fades from the current music track into `file_name`, which loops until replaced.
+    /// Returns false if the track can't be opened, leaving the current one playing.
+    fn play_music(&self, file_name: &str) -> bool;
+    /// Plays a short musical cue over the music, which dips while it plays.
+    fn play_stinger(&self, file_name: &str);
+
+    /// Moves fades along by a frame.
+    fn update(&self, _frame_time_ms: f32) {}
+
+    /// The latest sound effects and stingers played, for backends that keep track.
+    fn played(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// The music track playing, for backends that keep track.
+    fn music(&self) -> Option<String> {
+        None
+    }
+}
+
+pub struct SoundSystem {
+    backend: Box<dyn AudioBackend>,
+}
+
+/// Holds the audio device open; nothing is heard once it is dropped.
+#[cfg(feature = "audio")]
+pub type AudioOutput = rodio::OutputStream;
+#[cfg(not(feature = "audio"))]
+pub type AudioOutput = ();
+
+/// Opens the audio device, or gives a silent sound system if there isn't one.
+#[cfg(feature = "audio")]
+pub fn open_audio() -> (Option<AudioOutput>, SoundSystem) {
+    match rodio::OutputStream::try_default() {
+        Ok((stream, stream_handle)) => (Some(stream), SoundSystem::new(&stream_handle)),
+        Err(e) => {
+            eprintln!("No audio device ({}), continuing without sound", e);
+            (None, SoundSystem::silent())
+        }
+    }
+}
+
+/// Builds without audio support always run silent.
+#[cfg(not(feature = "audio"))]
+pub fn open_audio() -> (Option<AudioOutput>, SoundSystem) {
+    (None, SoundSystem::silent())
+}
+
+impl SoundSystem {
+    /// Plays through the audio device, or stays silent if the device won't open.
+    #[cfg(feature = "audio")]
+    pub fn new(stream_handle: &rodio::OutputStreamHandle) -> SoundSystem {
+        match RodioBackend::new(stream_handle) {
+            Ok(backend) => SoundSystem::with_backend(backend),
+            Err(e) => {
+                eprintln!("Unable to play sound ({}), continuing without it", e);
+                SoundSystem::silent()
+            }
+        }
+    }
+
+    /// A sound system for when there is no audio device, which plays nothing.
+    pub fn silent() -> SoundSystem {
+        SoundSystem::with_backend(SilentBackend::new())
+    }
+
+    /// A sound system with no audio device behind it, which only notes what it was asked to play.
+    pub fn recording() -> SoundSystem {
+        SoundSystem::with_backend(RecordingBackend::new())
+    }
+
+    pub fn with_backend(backend: impl AudioBackend + 'static) -> SoundSystem {
+        SoundSystem {
+            backend: Box::new(backend),
+        }
+    }
+
+    /// Plays sounds that don't come from anywhere on the map, such as the shop till.
+    pub fn play_sound_effects(&self, file_names: Vec<String>) {
+        for file_name in file_names {
+            self.backend.play(&file_name, 1.0, 0.0);
+        }
+    }
+
+    /// Plays sounds made at `origin`, as heard by a player standing at `listener`.
+    pub fn play_sound_effects_at(
+        &self,
+        file_names: Vec<String>,
+        origin: Point,
+        listener: Point,
+        map: &Map,
+    ) {
+        if let Some((volume, pan)) = place_sound(origin, listener, map) {
+            for file_name in file_names {
+                self.backend.play(&file_name, volume, pan);
+            }
+        }
+    }
+
+    /// Plays `file_name`, or the default track if that can't be opened.
+    pub fn play_music(&self, file_name: &str) {
+        if !self.backend.play_music(file_name) {
+            let default = get_default_music(&RAWS.lock().unwrap());
+            if default != file_name {
+                self.backend.play_music(&default);
+            }
+        }
+    }
+
+    pub fn play_stinger(&self, file_name: &str) {
+        self.backend.play_stinger(file_name);
+    }
+
+    pub fn update(&self, frame_time_ms: f32) {
+        self.backend.update(frame_time_ms);
+    }
+
+    pub fn played_sound_effects(&self) -> Vec<String> {
+        self.backend.played()
+    }
+
+    pub fn current_music(&self) -> Option<String> {
+        self.backend.music()
+    }
+
+    pub fn get_background_volume(&self) -> f32 {
+        self.backend.background_volume()
+    }
+
+    pub fn get_effects_volume(&self) -> f32 {
+        self.backend.effects_volume()
+    }
+
+    pub fn set_background_volume(&self, volume: f32) {
+        self.backend.set_background_volume(clamp_volume(volume));
+    }
+
+    pub fn set_effects_volume(&self, volume: f32) {
+        self.backend.set_effects_volume(clamp_volume(volume));
+    }
+
+    pub fn change_background_volume(&self, offset: f32) {
+        self.set_background_volume(self.get_background_volume() + offset);
+    }
+
+    pub fn change_effect_volume(&self, offset: f32) {
+        self.set_effects_volume(self.get_effects_volume() + offset);
+    }
+}
+
+/// Works out how loud a sound is and where it is heard from, or `None` if it is out of earshot.
+fn place_sound(origin: Point, listener: Point, map: &Map) -> Option<(f32, f32)> {
+    let distance = rltk::DistanceAlg::Pythagoras.distance2d(origin, listener);
+    let mut volume = 1.0 - distance / HEARING_RANGE;
+    if is_muffled(origin, listener, map) {
+        volume *= MUFFLED;
+    }
+    if volume < INAUDIBLE {
+        return None;
+    }
+    let pan = ((origin.x - listener.x) as f32 / (HEARING_RANGE / 2.0)).clamp(-1.0, 1.0);
+    Some((volume, pan))
+}
+
+/// Whether anything opaque stands between the sound and the player.
+fn is_muffled(origin: Point, listener: Point, map: &Map) -> bool {
+    let line = rltk::line2d(rltk::LineAlg::Bresenham, listener, origin);
+    line.iter()
+        .filter(|pt| **pt != listener && **pt != origin)
+        .any(|pt| {
+            pt.x < 0
+                || pt.x >= map.width
+                || pt.y < 0
+                || pt.y >= map.height
+                || map.is_opaque(map.xy_idx(pt.x, pt.y))
+        })
+}
+
+/// Picks one of the sounds raws give for `event`, using the source mob's own sounds if it has
+/// any. Events with nothing to play give an empty list.
+pub fn event_sounds(event: &str, source_name: Option<&str>) -> Vec<String> {
+    let candidates = get_event_sounds(&RAWS.lock().unwrap(), event, source_name);
+    if candidates.is_empty() {
+        return Vec::new();
+    }
+    let pick = crate::rng::cosmetic::range(0, candidates.len() as i32) as usize;
+    vec![candidates[pick].clone()]
+}
+
+/// Plays the sound `maker` makes for `event`, heard from wherever `location` is. An archer's
+/// arrow, for example, is the archer's sound but lands on the target.
+pub fn play_event(ecs: &World, event: &str, maker: Entity, location: Entity) {
+    let maker_name = ecs
+        .read_storage::<Name>()
+        .get(maker)
+        .map(|name| name.name.clone());
+    play_sound_effects_from(ecs, event_sounds(event, maker_name.as_deref()), location);
+}
+
+pub fn play_event_from(ecs: &World, event: &str, source: Entity) {
+    play_event(ecs, event, source, source);
+}
+
+/// Plays sounds made by `source`, wherever it is. Sounds from things with no position (such as
+/// an item in a backpack) are heard as if they were on the player.
+pub fn play_sound_effects_from(ecs: &World, file_names: Vec<String>, source: Entity) {
+    let listener = *ecs.fetch::<Point>();
+    let origin = ecs
+        .read_storage::<Position>()
+        .get(source)
+        .map_or(listener, |pos| Point::new(pos.x, pos.y));
+    ecs.fetch::<SoundSystem>().play_sound_effects_at(
+        file_names,
+        origin,
+        listener,
+        &ecs.fetch::<Map>(),
+    );
+}
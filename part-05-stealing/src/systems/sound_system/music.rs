@@ -0,0 +1,72 @@
+use super::SoundSystem;
+use crate::gamelog;
+use crate::map::Map;
+use crate::raws::{get_combat_music, get_music_for_depth, get_stinger, RAWS};
+use specs::prelude::*;
+
+/// Combat music keeps playing for this many turns after the last blow.
+const COMBAT_MUSIC_TURNS: i32 = 8;
+
+/// Picks the music track: the one raws give for the level, or the combat track while fighting.
+pub struct MusicDirector {
+    combat_turns: i32,
+    damage_seen: i32,
+}
+
+fn damage_events() -> i32 {
+    gamelog::get_event_count("Damage Taken") + gamelog::get_event_count("Damage Inflicted")
+}
+
+fn play_music(ecs: &World, file_name: &str) {
+    if let Some(sound_system) = ecs.try_fetch::<SoundSystem>() {
+        sound_system.play_music(file_name);
+    }
+}
+
+impl MusicDirector {
+    pub fn new() -> MusicDirector {
+        MusicDirector {
+            combat_turns: 0,
+            damage_seen: 0,
+        }
+    }
+
+    /// Fades into the current level's track.
+    pub fn enter_level(&mut self, ecs: &World) {
+        self.combat_turns = 0;
+        self.damage_seen = damage_events();
+        let depth = ecs.fetch::<Map>().depth;
+        let track = get_music_for_depth(&RAWS.lock().unwrap(), depth);
+        play_music(ecs, &track);
+    }
+
+    /// Sounds the combat stinger and switches to combat music when a turn involved a fight, and
+    /// switches back once things calm down.
+    pub fn end_turn(&mut self, ecs: &World) {
+        let damage = damage_events();
+        if damage != self.damage_seen {
+            self.damage_seen = damage;
+            if self.combat_turns == 0 {
+                play_stinger(ecs, "combat");
+                let combat_music = get_combat_music(&RAWS.lock().unwrap());
+                if let Some(track) = combat_music {
+                    play_music(ecs, &track);
+                }
+            }
+            self.combat_turns = COMBAT_MUSIC_TURNS;
+        } else if self.combat_turns > 0 {
+            self.combat_turns -= 1;
+            if self.combat_turns == 0 {
+                self.enter_level(ecs);
+            }
+        }
+    }
+}
+
+/// Plays the stinger raws give for `cue`, if there is one.
+pub fn play_stinger(ecs: &World, cue: &str) {
+    let stinger = get_stinger(&RAWS.lock().unwrap(), cue);
+    if let (Some(file_name), Some(sound_system)) = (stinger, ecs.try_fetch::<SoundSystem>()) {
+        sound_system.play_stinger(&file_name);
+    }
+}
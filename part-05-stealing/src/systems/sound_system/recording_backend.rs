@@ -0,0 +1,93 @@
+use super::AudioBackend;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+/// How many of the latest sounds are remembered, so a long run doesn't grow without limit.
+const MAX_RECORDED_SOUNDS: usize = 100;
+
+/// Plays nothing, but remembers what it was asked to play. Used by headless runs that want to
+/// check which sounds a turn made.
+pub struct RecordingBackend {
+    played: Mutex<VecDeque<String>>,
+    music: Mutex<Option<String>>,
+    background_volume: Mutex<f32>,
+    effects_volume: Mutex<f32>,
+}
+
+impl RecordingBackend {
+    pub fn new() -> RecordingBackend {
+        RecordingBackend {
+            played: Mutex::new(VecDeque::new()),
+            music: Mutex::new(None),
+            background_volume: Mutex::new(1.0),
+            effects_volume: Mutex::new(1.0),
+        }
+    }
+
+    fn record(&self, file_name: &str) {
+        let mut played = self.played.lock().unwrap();
+        if played.len() == MAX_RECORDED_SOUNDS {
+            played.pop_front();
+        }
+        played.push_back(file_name.to_string());
+    }
+}
+
+impl AudioBackend for RecordingBackend {
+    fn play(&self, file_name: &str, _volume: f32, _pan: f32) {
+        self.record(file_name);
+    }
+
+    fn background_volume(&self) -> f32 {
+        *self.background_volume.lock().unwrap()
+    }
+
+    fn effects_volume(&self) -> f32 {
+        *self.effects_volume.lock().unwrap()
+    }
+
+    fn set_background_volume(&self, volume: f32) {
+        *self.background_volume.lock().unwrap() = volume;
+    }
+
+    fn set_effects_volume(&self, volume: f32) {
+        *self.effects_volume.lock().unwrap() = volume;
+    }
+
+    fn play_music(&self, file_name: &str) -> bool {
+        *self.music.lock().unwrap() = Some(file_name.to_string());
+        true
+    }
+
+    fn play_stinger(&self, file_name: &str) {
+        self.record(file_name);
+    }
+
+    fn played(&self) -> Vec<String> {
+        self.played.lock().unwrap().iter().cloned().collect()
+    }
+
+    fn music(&self) -> Option<String> {
+        self.music.lock().unwrap().clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_the_latest_sounds_are_kept() {
+        let backend = RecordingBackend::new();
+        for i in 0..MAX_RECORDED_SOUNDS + 5 {
+            backend.play(&format!("{}.wav", i), 1.0, 0.0);
+        }
+        let played = backend.played();
+        assert_eq!(played.len(), MAX_RECORDED_SOUNDS);
+        assert_eq!(played[0], "5.wav");
+        assert_eq!(
+            played[MAX_RECORDED_SOUNDS - 1],
+            format!("{}.wav", MAX_RECORDED_SOUNDS + 4)
+        );
+    }
+}
@@ -0,0 +1,334 @@
+use super::AudioBackend;
+use rodio::{
+    source::{ChannelVolume, Source},
+    Decoder, OutputStreamHandle, PlayError, Sink,
+};
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+const SOUND_DIRECTORY: &str = "resources/sounds";
+
+/// How many sound effects can play at once.
+const VOICES: usize = 8;
+/// How many copies of the same sound effect can play at once. Stops a room full of goblins
+/// swinging in the same turn from drowning everything else out.
+const MAX_PER_SOUND: usize = 3;
+
+/// How long one music track takes to fade into the next.
+const CROSSFADE_MS: f32 = 2000.0;
+/// How loud the music stays while a stinger plays over it.
+const DUCKED: f32 = 0.3;
+
+/// A sound effect decoded into memory, ready to play as often as it is needed.
+struct DecodedSound {
+    channels: u16,
+    sample_rate: u32,
+    samples: Arc<[i16]>,
+}
+
+impl DecodedSound {
+    fn load(file_path: &Path) -> Option<DecodedSound> {
+        match open_sound(file_path) {
+            Ok(source) => Some(DecodedSound {
+                channels: source.channels(),
+                sample_rate: source.sample_rate(),
+                samples: source.collect(),
+            }),
+            Err(e) => {
+                eprintln!("Unable to play {}: {}", file_path.display(), e);
+                None
+            }
+        }
+    }
+
+    fn source(&self) -> DecodedSource {
+        DecodedSource {
+            channels: self.channels,
+            sample_rate: self.sample_rate,
+            samples: Arc::clone(&self.samples),
+            position: 0,
+        }
+    }
+}
+
+/// One playing of a `DecodedSound`, sharing its samples rather than copying them.
+struct DecodedSource {
+    channels: u16,
+    sample_rate: u32,
+    samples: Arc<[i16]>,
+    position: usize,
+}
+
+impl Iterator for DecodedSource {
+    type Item = i16;
+
+    fn next(&mut self) -> Option<i16> {
+        let sample = self.samples.get(self.position).copied();
+        self.position += 1;
+        sample
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.samples.len().saturating_sub(self.position);
+        (remaining, Some(remaining))
+    }
+}
+
+impl Source for DecodedSource {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        let frames = self.samples.len() as u64 / self.channels.max(1) as u64;
+        Some(Duration::from_micros(
+            frames * 1_000_000 / self.sample_rate.max(1) as u64,
+        ))
+    }
+}
+
+/// A sink that plays one sound effect at a time, and which effect that is.
+struct Voice {
+    sink: Sink,
+    playing: Option<String>,
+}
+
+impl Voice {
+    fn is_playing(&self, file_name: &str) -> bool {
+        !self.sink.empty() && self.playing.as_deref() == Some(file_name)
+    }
+}
+
+/// A looping music track, and how far it has faded in.
+struct MusicTrack {
+    file_name: String,
+    sink: Sink,
+    fade: f32,
+}
+
+#[derive(Default)]
+struct Music {
+    volume: f32,
+    playing: Option<MusicTrack>,
+    fading_out: Vec<MusicTrack>,
+    stinger: Option<Sink>,
+}
+
+impl Music {
+    fn apply_volume(&self) {
+        let duck = if self.stinger.is_some() { DUCKED } else { 1.0 };
+        for track in self.playing.iter().chain(self.fading_out.iter()) {
+            track.sink.set_volume(self.volume * track.fade * duck);
+        }
+        if let Some(stinger) = &self.stinger {
+            stinger.set_volume(self.volume);
+        }
+    }
+}
+
+/// Plays sounds through an audio device.
+pub struct RodioBackend {
+    stream_handle: OutputStreamHandle,
+    music: Mutex<Music>,
+    voices: Mutex<Vec<Voice>>,
+    effects_volume: Mutex<f32>,
+    cache: Mutex<HashMap<String, DecodedSound>>,
+    /// Music tracks that wouldn't open, so they are reported once rather than every time.
+    missing_music: Mutex<HashSet<String>>,
+}
+
+fn open_sound(file_path: &Path) -> Result<Decoder<BufReader<File>>, String> {
+    let file = File::open(file_path).map_err(|e| e.to_string())?;
+    Decoder::new(BufReader::new(file)).map_err(|e| e.to_string())
+}
+
+/// Decodes every sound effect up front, so playing one never waits on the disk.
+fn load_sound_effects() -> HashMap<String, DecodedSound> {
+    let mut cache = HashMap::new();
+    if let Ok(entries) = fs::read_dir(SOUND_DIRECTORY) {
+        for path in entries.flatten().map(|entry| entry.path()) {
+            if path.extension().and_then(|ext| ext.to_str()) != Some("wav") {
+                continue;
+            }
+            let file_name = path.file_name().and_then(|name| name.to_str());
+            if let (Some(file_name), Some(sound)) = (file_name, DecodedSound::load(&path)) {
+                cache.insert(file_name.to_string(), sound);
+            }
+        }
+    }
+    cache
+}
+
+impl RodioBackend {
+    /// Opens the effect voices. Music waits until the game asks for a track.
+    pub fn new(stream_handle: &OutputStreamHandle) -> Result<RodioBackend, PlayError> {
+        let mut voices = Vec::with_capacity(VOICES);
+        for _ in 0..VOICES {
+            voices.push(Voice {
+                sink: Sink::try_new(stream_handle)?,
+                playing: None,
+            });
+        }
+
+        Ok(RodioBackend {
+            stream_handle: stream_handle.clone(),
+            music: Mutex::new(Music {
+                volume: 1.0,
+                ..Default::default()
+            }),
+            voices: Mutex::new(voices),
+            effects_volume: Mutex::new(1.0),
+            cache: Mutex::new(load_sound_effects()),
+            missing_music: Mutex::new(HashSet::new()),
+        })
+    }
+}
+
+impl RodioBackend {
+    /// Runs `play` with the decoded sound, decoding it first if it wasn't around at startup.
+    fn with_sound(&self, file_name: &str, play: impl FnOnce(&DecodedSound)) {
+        let mut cache = self.cache.lock().unwrap();
+        if !cache.contains_key(file_name) {
+            match DecodedSound::load(&Path::new(SOUND_DIRECTORY).join(file_name)) {
+                Some(sound) => cache.insert(file_name.to_string(), sound),
+                None => return,
+            };
+        }
+        play(&cache[file_name]);
+    }
+
+    fn play_voice(&self, file_name: &str, sound: &DecodedSound, volume: f32, pan: f32) {
+        let mut voices = self.voices.lock().unwrap();
+        let copies = voices
+            .iter()
+            .filter(|voice| voice.is_playing(file_name))
+            .count();
+        if copies >= MAX_PER_SOUND {
+            return;
+        }
+
+        // With every voice busy, the sound is dropped rather than queued behind the others
+        if let Some(voice) = voices.iter_mut().find(|voice| voice.sink.empty()) {
+            let left = volume * f32::min(1.0, 1.0 - pan);
+            let right = volume * f32::min(1.0, 1.0 + pan);
+            voice
+                .sink
+                .append(ChannelVolume::new(sound.source(), vec![left, right]));
+            voice.playing = Some(file_name.to_string());
+        }
+    }
+}
+
+impl AudioBackend for RodioBackend {
+    fn play(&self, file_name: &str, volume: f32, pan: f32) {
+        self.with_sound(file_name, |sound| {
+            self.play_voice(file_name, sound, volume, pan)
+        });
+    }
+
+    fn background_volume(&self) -> f32 {
+        self.music.lock().unwrap().volume
+    }
+
+    fn effects_volume(&self) -> f32 {
+        *self.effects_volume.lock().unwrap()
+    }
+
+    fn set_background_volume(&self, volume: f32) {
+        let mut music = self.music.lock().unwrap();
+        music.volume = volume;
+        music.apply_volume();
+    }
+
+    fn set_effects_volume(&self, volume: f32) {
+        *self.effects_volume.lock().unwrap() = volume;
+        for voice in self.voices.lock().unwrap().iter() {
+            voice.sink.set_volume(volume);
+        }
+    }
+
+    fn play_music(&self, file_name: &str) -> bool {
+        let mut music = self.music.lock().unwrap();
+        if music
+            .playing
+            .as_ref()
+            .is_some_and(|track| track.file_name == file_name)
+        {
+            return true;
+        }
+
+        let mut missing_music = self.missing_music.lock().unwrap();
+        if missing_music.contains(file_name) {
+            return false;
+        }
+        let source = match open_sound(&Path::new(SOUND_DIRECTORY).join(file_name)) {
+            Ok(source) => source,
+            Err(e) => {
+                eprintln!("Unable to play {}: {}", file_name, e);
+                missing_music.insert(file_name.to_string());
+                return false;
+            }
+        };
+        // Without a sink no track will play, so there is nothing to fall back to
+        let sink = match Sink::try_new(&self.stream_handle) {
+            Ok(sink) => sink,
+            Err(_) => return true,
+        };
+        sink.append(source.repeat_infinite());
+
+        if let Some(track) = music.playing.take() {
+            music.fading_out.push(track);
+        }
+        music.playing = Some(MusicTrack {
+            file_name: file_name.to_string(),
+            sink,
+            fade: 0.0,
+        });
+        music.apply_volume();
+        true
+    }
+
+    fn play_stinger(&self, file_name: &str) {
+        self.with_sound(file_name, |sound| {
+            if let Ok(sink) = Sink::try_new(&self.stream_handle) {
+                sink.append(sound.source());
+                let mut music = self.music.lock().unwrap();
+                music.stinger = Some(sink);
+                music.apply_volume();
+            }
+        });
+    }
+
+    fn update(&self, frame_time_ms: f32) {
+        let step = frame_time_ms / CROSSFADE_MS;
+        let mut music = self.music.lock().unwrap();
+        if let Some(track) = &mut music.playing {
+            track.fade = f32::min(1.0, track.fade + step);
+        }
+        // Dropping a sink stops its track
+        for track in music.fading_out.iter_mut() {
+            track.fade -= step;
+        }
+        music.fading_out.retain(|track| track.fade > 0.0);
+        if music
+            .stinger
+            .as_ref()
+            .is_some_and(|stinger| stinger.empty())
+        {
+            music.stinger = None;
+        }
+        music.apply_volume();
+    }
+}
@@ -0,0 +1,44 @@
+use super::AudioBackend;
+use std::sync::Mutex;
+
+/// Plays nothing and keeps nothing but the volumes, so the options menu still works. Used when
+/// there is no audio device.
+pub struct SilentBackend {
+    background_volume: Mutex<f32>,
+    effects_volume: Mutex<f32>,
+}
+
+impl SilentBackend {
+    pub fn new() -> SilentBackend {
+        SilentBackend {
+            background_volume: Mutex::new(1.0),
+            effects_volume: Mutex::new(1.0),
+        }
+    }
+}
+
+impl AudioBackend for SilentBackend {
+    fn play(&self, _file_name: &str, _volume: f32, _pan: f32) {}
+
+    fn background_volume(&self) -> f32 {
+        *self.background_volume.lock().unwrap()
+    }
+
+    fn effects_volume(&self) -> f32 {
+        *self.effects_volume.lock().unwrap()
+    }
+
+    fn set_background_volume(&self, volume: f32) {
+        *self.background_volume.lock().unwrap() = volume;
+    }
+
+    fn set_effects_volume(&self, volume: f32) {
+        *self.effects_volume.lock().unwrap() = volume;
+    }
+
+    fn play_music(&self, _file_name: &str) -> bool {
+        true
+    }
+
+    fn play_stinger(&self, _file_name: &str) {}
+}
@@ -34,6 +34,7 @@ impl<'a> System<'a> for TriggerSystem {
                             let name = names.get(entity_id);
                             if let Some(name) = name {
                                 crate::gamelog::Logger::new()
+                                    .category(crate::gamelog::LogCategory::Combat)
                                     .color(rltk::RED)
                                     .append(&name.name)
                                     .color(rltk::WHITE)
@@ -41,9 +42,10 @@ impl<'a> System<'a> for TriggerSystem {
                                     .log();
                             }
 
-                            // Call the effects system
+                            // Call the effects system. The trap is what does the damage, since
+                            // nobody is hurt by their own effects
                             add_effect(
-                                Some(entity),
+                                Some(entity_id),
                                 EffectType::TriggerFire { trigger: entity_id },
                                 if let Some(aoe) = area_of_effect.get(entity_id) {
                                     Targets::Tiles {
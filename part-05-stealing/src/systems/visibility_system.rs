@@ -51,10 +51,11 @@ impl<'a> System<'a> for VisibilitySystem {
                         crate::spatial::for_each_tile_content(idx, |e| {
                             let maybe_hidden = hidden.get(e);
                             if let Some(_maybe_hidden) = maybe_hidden {
-                                if crate::rng::roll_dice(1, 24) == 1 {
+                                if crate::rng::combat::roll_dice(1, 24) == 1 {
                                     let name = names.get(e);
                                     if let Some(name) = name {
                                         crate::gamelog::Logger::new()
+                                            .category(crate::gamelog::LogCategory::Combat)
                                             .append("You spotted:")
                                             .color(rltk::RED)
                                             .append(&name.name)
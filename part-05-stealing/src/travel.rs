@@ -0,0 +1,601 @@
+use crate::map::{tile_cost, tile_walkable, Map, TileType};
+use crate::raws::Reaction;
+use crate::rltk;
+use crate::rltk::BaseMap;
+use crate::{
+    Door, EntryTrigger, Faction, Hidden, Item, ParticleLifetime, Player, Pools, Position,
+    Renderable, Viewshed,
+};
+use specs::prelude::*;
+use std::collections::HashSet;
+
+/// What an extra turn spent opening a door adds to a path's cost.
+const DOOR_COST: f32 = 1.0;
+/// How far exploring looks for somewhere new, in steps.
+const EXPLORE_DEPTH: f32 = 4096.0;
+
+/// Where a walk is headed.
+enum Goal {
+    /// A tile the player picked. The path there is planned up front.
+    Destination,
+    /// The nearest tile next to somewhere unexplored, picked again each step.
+    Explore { auto_pickup: bool },
+    /// Straight on across open ground, or along a corridor's turns, until something
+    /// interesting.
+    Run {
+        direction: (i32, i32),
+        corridor: bool,
+        /// The last couple of tiles walked, which a corridor run doesn't turn back into.
+        behind: Vec<usize>,
+        /// Whether the tiles either side were open, to notice passing a doorway.
+        sides: Option<(bool, bool)>,
+        started: bool,
+    },
+}
+
+/// What the player does on their next turn of a walk.
+pub enum Step {
+    Move(i32, i32),
+    PickUp,
+}
+
+/// Every direction a step can take, straight ones first.
+const DIRECTIONS: [(i32, i32); 8] = [
+    (-1, 0),
+    (1, 0),
+    (0, -1),
+    (0, 1),
+    (-1, -1),
+    (1, -1),
+    (-1, 1),
+    (1, 1),
+];
+
+/// The kinds of things that interrupt a walk when they come into view, most urgent first.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Sighting {
+    Hostile,
+    Trap,
+    Item,
+    Prop,
+}
+
+impl Sighting {
+    fn message(self) -> &'static str {
+        match self {
+            Sighting::Hostile => "You stop: something hostile comes into view.",
+            Sighting::Trap => "You stop: you spot a trap.",
+            Sighting::Item => "You stop: you spot an item.",
+            Sighting::Prop => "You stop: you spot something.",
+        }
+    }
+}
+
+/// A walk the player takes one step per turn, until it arrives or something worth stopping
+/// for happens.
+pub struct Travel {
+    goal: Goal,
+    /// The tiles still to walk, with the next step last.
+    path: Vec<usize>,
+    /// The player's hit points before the last step, to notice being hurt.
+    hit_points: i32,
+    /// Things already in view, which don't stop the walk again.
+    noticed: HashSet<Entity>,
+    /// Set when the next step is into a closed door, which takes a turn to open.
+    opening_door: bool,
+    /// Items exploring has tried to pick up, so one that can't be taken isn't tried forever.
+    tried_items: HashSet<Entity>,
+}
+
+/// The map as the player knows it: revealed walkable tiles, where the closed doors they have
+/// seen are only one more turn to walk through.
+struct KnownMap<'a> {
+    map: &'a Map,
+    doors: HashSet<usize>,
+    destination: Option<usize>,
+}
+
+impl<'a> KnownMap<'a> {
+    fn new(ecs: &World, map: &'a Map, destination: Option<usize>) -> KnownMap<'a> {
+        let doors = (&ecs.read_storage::<Door>(), &ecs.read_storage::<Position>())
+            .join()
+            .filter(|(door, _)| !door.open)
+            .map(|(_, pos)| map.xy_idx(pos.x, pos.y))
+            .filter(|idx| map.revealed_tiles[*idx])
+            .collect();
+        KnownMap {
+            map,
+            doors,
+            destination,
+        }
+    }
+
+    /// Whether a step onto `(x, y)` belongs in a path. Walking onto stairs takes them, so
+    /// they are only stepped on at the end; anything visibly in the way is walked around.
+    fn is_passable(&self, x: i32, y: i32) -> bool {
+        if x < 1 || x > self.map.width - 1 || y < 1 || y > self.map.height - 1 {
+            return false;
+        }
+        let idx = self.map.xy_idx(x, y);
+        if Some(idx) == self.destination {
+            return true;
+        }
+        self.map.revealed_tiles[idx]
+            && tile_walkable(self.map.tiles[idx])
+            && !matches!(
+                self.map.tiles[idx],
+                TileType::DownStairs | TileType::UpStairs
+            )
+            && (self.doors.contains(&idx)
+                || !self.map.visible_tiles[idx]
+                || !crate::spatial::is_blocked(idx))
+    }
+
+    /// The steps from `pos` onto tiles that can be walked, other than those in `behind`.
+    fn openings(&self, pos: rltk::Point, behind: &[usize]) -> Vec<(i32, i32)> {
+        DIRECTIONS
+            .iter()
+            .filter(|(dx, dy)| {
+                let (x, y) = (pos.x + dx, pos.y + dy);
+                self.is_passable(x, y) && !behind.contains(&self.map.xy_idx(x, y))
+            })
+            .copied()
+            .collect()
+    }
+
+    /// Known tiles that can be walked to and are next to tiles nobody has seen yet.
+    fn frontier(&self) -> Vec<usize> {
+        let mut frontier = Vec::new();
+        for y in 1..self.map.height - 1 {
+            for x in 1..self.map.width - 1 {
+                if !self.is_passable(x, y) {
+                    continue;
+                }
+                let unexplored_neighbour = (-1..=1)
+                    .flat_map(|dy| (-1..=1).map(move |dx| (x + dx, y + dy)))
+                    .any(|(nx, ny)| !self.map.revealed_tiles[self.map.xy_idx(nx, ny)]);
+                if unexplored_neighbour {
+                    frontier.push(self.map.xy_idx(x, y));
+                }
+            }
+        }
+        frontier
+    }
+}
+
+impl<'a> BaseMap for KnownMap<'a> {
+    fn get_pathing_distance(&self, idx1: usize, idx2: usize) -> f32 {
+        self.map.get_pathing_distance(idx1, idx2)
+    }
+
+    fn get_available_exits(&self, idx: usize) -> rltk::SmallVec<[(usize, f32); 10]> {
+        let mut exits = rltk::SmallVec::new();
+        let x = idx as i32 % self.map.width;
+        let y = idx as i32 / self.map.width;
+        let cost = tile_cost(self.map.tiles[idx]);
+        for (dx, dy) in DIRECTIONS {
+            if !self.is_passable(x + dx, y + dy) {
+                continue;
+            }
+            let exit = self.map.xy_idx(x + dx, y + dy);
+            let mut exit_cost = if dx != 0 && dy != 0 {
+                cost * 1.45
+            } else {
+                cost
+            };
+            if self.doors.contains(&exit) {
+                exit_cost += DOOR_COST;
+            }
+            exits.push((exit, exit_cost));
+        }
+        exits
+    }
+}
+
+impl Travel {
+    fn new(ecs: &World, goal: Goal, path: Vec<usize>) -> Travel {
+        Travel {
+            goal,
+            path,
+            hit_points: player_hit_points(ecs),
+            noticed: noticed(ecs),
+            opening_door: false,
+            tried_items: HashSet::new(),
+        }
+    }
+
+    /// Plans a walk from the player to `destination`, through tiles the player has seen. Gives
+    /// `None` if the destination is unknown, can't be stood on, or can't be reached.
+    pub fn to(ecs: &World, destination: rltk::Point) -> Option<Travel> {
+        let map = ecs.fetch::<Map>();
+        let player_pos = *ecs.fetch::<rltk::Point>();
+        if destination.x < 1
+            || destination.x > map.width - 1
+            || destination.y < 1
+            || destination.y > map.height - 1
+            || destination == player_pos
+        {
+            return None;
+        }
+        let end = map.xy_idx(destination.x, destination.y);
+        if !map.revealed_tiles[end] || !tile_walkable(map.tiles[end]) {
+            return None;
+        }
+
+        let start = map.xy_idx(player_pos.x, player_pos.y);
+        let path = rltk::a_star_search(start, end, &KnownMap::new(ecs, &map, Some(end)));
+        if !path.success {
+            crate::gamelog::Logger::new()
+                .category(crate::gamelog::LogCategory::System)
+                .append("You don't know a way there.")
+                .log();
+            return None;
+        }
+        let mut steps = path.steps;
+        steps.reverse();
+        steps.pop(); // The player's own tile
+        Some(Travel::new(ecs, Goal::Destination, steps))
+    }
+
+    /// Plans a walk to the nearest stairs of the kind given that the player has seen.
+    pub fn to_stairs(ecs: &World, stairs: TileType) -> Option<Travel> {
+        let (player_pos, known_stairs) = {
+            let map = ecs.fetch::<Map>();
+            let known_stairs: Vec<rltk::Point> = (0..map.tiles.len())
+                .filter(|idx| map.revealed_tiles[*idx] && map.tiles[*idx] == stairs)
+                .map(|idx| rltk::Point::new(idx as i32 % map.width, idx as i32 / map.width))
+                .collect();
+            (*ecs.fetch::<rltk::Point>(), known_stairs)
+        };
+        let direction = if stairs == TileType::DownStairs {
+            "down"
+        } else {
+            "up"
+        };
+        if known_stairs.contains(&player_pos) {
+            crate::gamelog::Logger::new()
+                .category(crate::gamelog::LogCategory::System)
+                .append(format!("You are already on the stairs {}.", direction))
+                .log();
+            return None;
+        }
+        let nearest = known_stairs.into_iter().min_by(|a, b| {
+            let distance = |p| rltk::DistanceAlg::Pythagoras.distance2d(player_pos, p);
+            distance(*a).partial_cmp(&distance(*b)).unwrap()
+        });
+        match nearest {
+            Some(nearest) => Travel::to(ecs, nearest),
+            None => {
+                crate::gamelog::Logger::new()
+                    .category(crate::gamelog::LogCategory::System)
+                    .append(format!("You haven't found the stairs {} yet.", direction))
+                    .log();
+                None
+            }
+        }
+    }
+
+    /// Starts running in `direction`. A run that starts in a corridor follows it; one that
+    /// starts in the open goes straight.
+    pub fn run(ecs: &World, direction: (i32, i32)) -> Option<Travel> {
+        let (corridor, behind) = {
+            let map = ecs.fetch::<Map>();
+            let known = KnownMap::new(ecs, &map, None);
+            let player_pos = *ecs.fetch::<rltk::Point>();
+            let (x, y) = (player_pos.x + direction.0, player_pos.y + direction.1);
+            if !known.is_passable(x, y) {
+                return None;
+            }
+            let behind = vec![map.xy_idx(
+                (player_pos.x - direction.0).clamp(0, map.width - 1),
+                (player_pos.y - direction.1).clamp(0, map.height - 1),
+            )];
+            let openings = known.openings(player_pos, &behind);
+            (is_one_way(&openings), behind)
+        };
+        let goal = Goal::Run {
+            direction,
+            corridor,
+            behind,
+            sides: None,
+            started: false,
+        };
+        Some(Travel::new(ecs, goal, Vec::new()))
+    }
+
+    /// Starts exploring, unless something hostile is already in view.
+    pub fn explore(ecs: &World, auto_pickup: bool) -> Option<Travel> {
+        let travel = Travel::new(ecs, Goal::Explore { auto_pickup }, Vec::new());
+        if travel
+            .noticed
+            .iter()
+            .any(|entity| sighting(ecs, *entity) == Some(Sighting::Hostile))
+        {
+            crate::gamelog::Logger::new()
+                .category(crate::gamelog::LogCategory::System)
+                .append("You can't explore with enemies in view.")
+                .log();
+            return None;
+        }
+        Some(travel)
+    }
+
+    /// The player's next turn, or `None` once the walk is over. Whatever interrupts it is
+    /// logged.
+    pub fn next_step(&mut self, ecs: &World) -> Option<Step> {
+        let hit_points = player_hit_points(ecs);
+        if hit_points < self.hit_points {
+            return interrupt("You stop: you are hurt.");
+        }
+        self.hit_points = hit_points;
+
+        let now_noticed = noticed(ecs);
+        let spotted = now_noticed
+            .difference(&self.noticed)
+            .filter_map(|entity| sighting(ecs, *entity))
+            .filter(|sighting| !(self.picks_up() && *sighting == Sighting::Item))
+            .min();
+        self.noticed = now_noticed;
+        if let Some(spotted) = spotted {
+            return interrupt(spotted.message());
+        }
+
+        if let Goal::Explore { .. } = self.goal {
+            if self.picks_up() && self.take_item_here(ecs) {
+                self.path.clear();
+                return Some(Step::PickUp);
+            }
+            let next = self.explore_step(ecs)?;
+            self.path = vec![next];
+        }
+        if let Goal::Run { .. } = self.goal {
+            let next = self.run_step(ecs)?;
+            self.path = vec![next];
+        }
+
+        let next = *self.path.last()?;
+        let map = ecs.fetch::<Map>();
+        let player_pos = *ecs.fetch::<rltk::Point>();
+        let (x, y) = (next as i32 % map.width, next as i32 / map.width);
+        if i32::abs(x - player_pos.x) > 1 || i32::abs(y - player_pos.y) > 1 {
+            // Knocked off the path, by a swap or a trap
+            return interrupt("You stop.");
+        }
+        self.opening_door = (&ecs.read_storage::<Door>(), &ecs.read_storage::<Position>())
+            .join()
+            .any(|(door, pos)| !door.open && pos.x == x && pos.y == y);
+        Some(Step::Move(x - player_pos.x, y - player_pos.y))
+    }
+
+    /// Called after the turn `next_step` gave; whether the walk goes on.
+    pub fn stepped(&mut self, ecs: &World) -> bool {
+        let map = ecs.fetch::<Map>();
+        let player_pos = *ecs.fetch::<rltk::Point>();
+        if let Some(next) = self.path.last() {
+            if *next == map.xy_idx(player_pos.x, player_pos.y) {
+                self.path.pop();
+            } else if !self.opening_door {
+                // Something was in the way
+                return false;
+            }
+        }
+        match self.goal {
+            Goal::Destination => !self.path.is_empty(),
+            Goal::Explore { .. } | Goal::Run { .. } => true,
+        }
+    }
+
+    fn picks_up(&self) -> bool {
+        matches!(self.goal, Goal::Explore { auto_pickup: true })
+    }
+
+    /// Whether there's an item under the player that exploring hasn't tried to take yet.
+    fn take_item_here(&mut self, ecs: &World) -> bool {
+        let player_pos = *ecs.fetch::<rltk::Point>();
+        let item = (
+            &ecs.entities(),
+            &ecs.read_storage::<Item>(),
+            &ecs.read_storage::<Position>(),
+        )
+            .join()
+            .find(|(entity, _, pos)| {
+                pos.x == player_pos.x && pos.y == player_pos.y && !self.tried_items.contains(entity)
+            })
+            .map(|(entity, _, _)| entity);
+        match item {
+            Some(item) => self.tried_items.insert(item),
+            None => false,
+        }
+    }
+
+    /// The next tile of a run, or `None` where the run should stop: at a junction or a door,
+    /// on an item, or where the walls open out.
+    fn run_step(&mut self, ecs: &World) -> Option<usize> {
+        let map = ecs.fetch::<Map>();
+        let known = KnownMap::new(ecs, &map, None);
+        let player_pos = *ecs.fetch::<rltk::Point>();
+        let player_idx = map.xy_idx(player_pos.x, player_pos.y);
+        let (direction, corridor, behind, sides, started) = match &mut self.goal {
+            Goal::Run {
+                direction,
+                corridor,
+                behind,
+                sides,
+                started,
+            } => (direction, corridor, behind, sides, started),
+            _ => return None,
+        };
+
+        if *started && item_at(ecs, player_pos) {
+            return None;
+        }
+
+        let step = if !*started || !*corridor {
+            // Straight on, while the walls either side stay the same
+            let (dx, dy) = *direction;
+            if dx == 0 || dy == 0 {
+                let open_sides = (
+                    known.is_passable(player_pos.x - dy, player_pos.y + dx),
+                    known.is_passable(player_pos.x + dy, player_pos.y - dx),
+                );
+                if *started && !*corridor && *sides != Some(open_sides) {
+                    return None;
+                }
+                *sides = Some(open_sides);
+            }
+            (dx, dy)
+        } else {
+            // Along the corridor, while there's only one way on
+            let openings = known.openings(player_pos, behind);
+            if !is_one_way(&openings) {
+                return None;
+            }
+            *openings
+                .iter()
+                .find(|(dx, dy)| *dx == 0 || *dy == 0)
+                .or_else(|| openings.first())?
+        };
+
+        let next = rltk::Point::new(player_pos.x + step.0, player_pos.y + step.1);
+        if !known.is_passable(next.x, next.y) || door_at(ecs, next) {
+            return None;
+        }
+        *started = true;
+        *direction = step;
+        behind.push(player_idx);
+        if behind.len() > 2 {
+            behind.remove(0);
+        }
+        Some(map.xy_idx(next.x, next.y))
+    }
+
+    /// The next tile towards the nearest unexplored part of the map, or towards an item in
+    /// view when picking them up.
+    fn explore_step(&self, ecs: &World) -> Option<usize> {
+        let map = ecs.fetch::<Map>();
+        let known = KnownMap::new(ecs, &map, None);
+        let mut targets = known.frontier();
+        if self.picks_up() {
+            let items = ecs.read_storage::<Item>();
+            let positions = ecs.read_storage::<Position>();
+            for entity in self.noticed.iter() {
+                if let (Some(_item), Some(pos)) = (items.get(*entity), positions.get(*entity)) {
+                    if !self.tried_items.contains(entity) {
+                        targets.push(map.xy_idx(pos.x, pos.y));
+                    }
+                }
+            }
+        }
+
+        let player_pos = *ecs.fetch::<rltk::Point>();
+        let player_idx = map.xy_idx(player_pos.x, player_pos.y);
+        let distances = rltk::DijkstraMap::new(
+            map.width as usize,
+            map.height as usize,
+            &targets,
+            &known,
+            EXPLORE_DEPTH,
+        );
+        let next = known
+            .get_available_exits(player_idx)
+            .iter()
+            .map(|(exit, _)| *exit)
+            .filter(|exit| distances.map[*exit] < f32::MAX)
+            .min_by(|a, b| distances.map[*a].partial_cmp(&distances.map[*b]).unwrap());
+        if next.is_none() {
+            crate::gamelog::Logger::new()
+                .category(crate::gamelog::LogCategory::System)
+                .append("There is nowhere left to explore.")
+                .log();
+        }
+        next
+    }
+}
+
+/// Whether `openings` are all next to each other, so they are really one way on, like a
+/// corridor's bend, rather than a fork.
+fn is_one_way(openings: &[(i32, i32)]) -> bool {
+    !openings.is_empty()
+        && openings.iter().all(|a| {
+            openings
+                .iter()
+                .all(|b| i32::abs(a.0 - b.0) <= 1 && i32::abs(a.1 - b.1) <= 1)
+        })
+}
+
+fn door_at(ecs: &World, pos: rltk::Point) -> bool {
+    (&ecs.read_storage::<Door>(), &ecs.read_storage::<Position>())
+        .join()
+        .any(|(_, door_pos)| door_pos.x == pos.x && door_pos.y == pos.y)
+}
+
+fn item_at(ecs: &World, pos: rltk::Point) -> bool {
+    (&ecs.read_storage::<Item>(), &ecs.read_storage::<Position>())
+        .join()
+        .any(|(_, item_pos)| item_pos.x == pos.x && item_pos.y == pos.y)
+}
+
+fn interrupt(reason: &str) -> Option<Step> {
+    crate::gamelog::Logger::new()
+        .category(crate::gamelog::LogCategory::System)
+        .append(reason)
+        .log();
+    None
+}
+
+fn player_hit_points(ecs: &World) -> i32 {
+    let player_entity = ecs.fetch::<Entity>();
+    ecs.read_storage::<Pools>()
+        .get(*player_entity)
+        .map_or(0, |pools| pools.hit_points.current)
+}
+
+/// What kind of thing `entity` is, if it is worth stopping for.
+fn sighting(ecs: &World, entity: Entity) -> Option<Sighting> {
+    let hostile = ecs
+        .read_storage::<Faction>()
+        .get(entity)
+        .is_some_and(|faction| {
+            crate::crime::reaction_to_player(ecs, &faction.name) == Reaction::Attack
+        });
+    if hostile {
+        Some(Sighting::Hostile)
+    } else if ecs.read_storage::<Hidden>().get(entity).is_some()
+        || ecs.read_storage::<Player>().get(entity).is_some()
+        || ecs.read_storage::<ParticleLifetime>().get(entity).is_some()
+    {
+        None
+    } else if ecs.read_storage::<EntryTrigger>().get(entity).is_some() {
+        Some(Sighting::Trap)
+    } else if ecs.read_storage::<Item>().get(entity).is_some() {
+        Some(Sighting::Item)
+    } else if ecs.read_storage::<Renderable>().get(entity).is_some()
+        && ecs.read_storage::<Pools>().get(entity).is_none()
+        && ecs.read_storage::<Door>().get(entity).is_none()
+    {
+        // Something that doesn't move and isn't a door, like a chest or an altar
+        Some(Sighting::Prop)
+    } else {
+        None
+    }
+}
+
+/// The things the player can see that are worth stopping for.
+fn noticed(ecs: &World) -> HashSet<Entity> {
+    let player_entity = ecs.fetch::<Entity>();
+    let viewsheds = ecs.read_storage::<Viewshed>();
+    let map = ecs.fetch::<Map>();
+
+    let mut noticed = HashSet::new();
+    if let Some(viewshed) = viewsheds.get(*player_entity) {
+        for tile in viewshed.visible_tiles.iter() {
+            let idx = map.xy_idx(tile.x, tile.y);
+            crate::spatial::for_each_tile_content(idx, |entity| {
+                if sighting(ecs, entity).is_some() {
+                    noticed.insert(entity);
+                }
+            });
+        }
+    }
+    noticed
+}
@@ -0,0 +1,194 @@
+use part_05_stealing::headless::parse_script;
+use part_05_stealing::map_builders::level_builder;
+use part_05_stealing::raws::{spawn_named_entity, SpawnType, RAWS};
+use part_05_stealing::{
+    rltk, rng, spatial, tile_walkable, BlocksTile, DamageOverTime, Duration, HeadlessGame, Map,
+    Name, Pools, Position, StatusEffect, TileType,
+};
+use specs::prelude::*;
+
+fn play(game: &mut HeadlessGame, script: &str) {
+    game.play(&parse_script(script).unwrap());
+}
+
+#[test]
+fn scripted_input_advances_turns() {
+    let mut game = HeadlessGame::new(1);
+    assert_eq!(game.turns(), 0);
+
+    play(&mut game, "Space\nNumpad5  # wait\n\nSpace\n");
+
+    assert!(!game.is_player_dead());
+    assert_eq!(game.turns(), 3);
+    assert!(game.report().contains("Turns: 3"));
+}
+
+#[test]
+fn a_new_game_starts_over() {
+    let mut game = HeadlessGame::new(1);
+    play(&mut game, "Space\nSpace\n");
+    assert_eq!(game.turns(), 2);
+    drop(game);
+
+    let game = HeadlessGame::new(1);
+    assert_eq!(game.turns(), 0);
+    assert!(!game.report().contains("Turns: 2"));
+}
+
+/// The current map, and the name and place of everything on it.
+fn layout(game: &HeadlessGame) -> (Vec<TileType>, Vec<(String, i32, i32)>) {
+    let ecs = &game.state.ecs;
+    let names = ecs.read_storage::<Name>();
+    let positions = ecs.read_storage::<Position>();
+    let things = (&names, &positions)
+        .join()
+        .map(|(name, pos)| (name.name.clone(), pos.x, pos.y))
+        .collect();
+    (ecs.fetch::<Map>().tiles.clone(), things)
+}
+
+#[test]
+fn a_seed_always_starts_the_same_run() {
+    let first = layout(&HeadlessGame::new(7));
+    assert!(layout(&HeadlessGame::new(7)) == first);
+    assert!(layout(&HeadlessGame::new(8)) != first);
+}
+
+#[test]
+fn a_seed_always_builds_the_same_levels() {
+    // Holding a game keeps the other tests off the random number streams
+    let _game = HeadlessGame::new(7);
+    let build = |depth| {
+        rng::reseed_level(7, depth);
+        let mut builder = level_builder(depth, 80, 50, false);
+        builder.build_map();
+        (builder.build_data.map.tiles, builder.build_data.spawn_list)
+    };
+    // Past the themed levels, each depth rolls its own mix of builders
+    for depth in 2..=24 {
+        assert!(build(depth) == build(depth), "depth {} changed", depth);
+    }
+}
+
+#[test]
+fn descending_away_from_the_stairs_is_refused() {
+    let mut game = HeadlessGame::new(1);
+    play(&mut game, "Period\n");
+
+    assert!(game.report().contains("Depth: 1"));
+    assert!(game.report().contains("There is no way down from here."));
+}
+
+#[test]
+fn menus_are_closed_without_a_screen() {
+    let mut game = HeadlessGame::new(1);
+    play(&mut game, "I\nSpace\n");
+
+    assert!(game
+        .report()
+        .contains("A headless run can't use menus; the menu was closed."));
+    assert!(!game.is_player_dead());
+}
+
+/// Spawns `name` on an open tile next to the player, and returns the key that walks into it.
+fn place_beside_player(game: &mut HeadlessGame, name: &str) -> &'static str {
+    let player = *game.state.ecs.fetch::<rltk::Point>();
+    let (key, x, y) = {
+        let map = game.state.ecs.fetch::<Map>();
+        [
+            ("Right", 1, 0),
+            ("Left", -1, 0),
+            ("Down", 0, 1),
+            ("Up", 0, -1),
+        ]
+        .into_iter()
+        .map(|(key, dx, dy)| (key, player.x + dx, player.y + dy))
+        .find(|(_, x, y)| {
+            let idx = map.xy_idx(*x, *y);
+            tile_walkable(map.tiles[idx]) && !spatial::is_blocked(idx)
+        })
+        .expect("the player is boxed in")
+    };
+    let entity = spawn_named_entity(
+        &RAWS.lock().unwrap(),
+        &mut game.state.ecs,
+        name,
+        SpawnType::AtPosition { x, y },
+    )
+    .unwrap();
+    // The map indexing system won't see it until the next turn
+    let idx = game.state.ecs.fetch::<Map>().xy_idx(x, y);
+    let blocks = game
+        .state
+        .ecs
+        .read_storage::<BlocksTile>()
+        .get(entity)
+        .is_some();
+    spatial::index_entity(entity, idx, blocks);
+    key
+}
+
+fn hit_points(game: &HeadlessGame) -> i32 {
+    let player = *game.state.ecs.fetch::<Entity>();
+    game.state
+        .ecs
+        .read_storage::<Pools>()
+        .get(player)
+        .unwrap()
+        .hit_points
+        .current
+}
+
+fn heard(game: &HeadlessGame, file_name: &str) -> bool {
+    game.sounds_played().iter().any(|sound| sound == file_name)
+}
+
+#[test]
+fn fighting_is_heard() {
+    let mut game = HeadlessGame::new(1);
+    let key = place_beside_player(&mut game, "Rat");
+
+    // Blows can miss, but not every time
+    for _ in 0..20 {
+        play(&mut game, key);
+        if heard(&game, "hit.wav") {
+            break;
+        }
+    }
+    assert!(heard(&game, "hit.wav"), "{}", game.report());
+}
+
+#[test]
+fn traps_hurt_and_are_heard() {
+    let mut game = HeadlessGame::new(1);
+    let key = place_beside_player(&mut game, "Bear Trap");
+    play(&mut game, key);
+
+    assert_eq!(hit_points(&game), 24, "{}", game.report());
+    assert!(heard(&game, "trap.wav"), "{}", game.report());
+    assert!(heard(&game, "hit.wav"), "{}", game.report());
+}
+
+#[test]
+fn damage_over_time_is_heard() {
+    let mut game = HeadlessGame::new(1);
+    let player = *game.state.ecs.fetch::<Entity>();
+    game.state
+        .ecs
+        .create_entity()
+        .with(StatusEffect { target: player })
+        .with(Duration { turns: 2 })
+        .with(DamageOverTime { damage: 1 })
+        .build();
+    play(&mut game, "Numpad5");
+
+    assert_eq!(hit_points(&game), 29, "{}", game.report());
+    assert!(heard(&game, "poison.wav"), "{}", game.report());
+}
+
+#[test]
+fn scripts_name_the_line_of_an_unknown_key() {
+    let error = parse_script("Space\n\nNotAKey\n").unwrap_err();
+    assert!(error.starts_with("line 3:"), "{}", error);
+    assert!(error.contains("NotAKey"), "{}", error);
+}